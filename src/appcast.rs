@@ -0,0 +1,91 @@
+//! Sparkle(macOS应用自动更新)的appcast.xml: 替换其中enclosure的下载地址, 使应用从镜像或CDN下载更新
+
+use crate::model::Cli;
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// enclosure元素的url属性(包括sparkle:deltas中的增量更新)
+static ENCLOSURE_URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(<enclosure\b[^>]*?\surl\s*=\s*)(?:"([^"]*)"|'([^']*)')"#).expect("valid regex")
+});
+
+/// 替换appcast.xml中enclosure的下载地址, 其他内容保持不变
+/// - 设置了--appcast-url-base时, 源仓库的附件地址替换为 {base}/{tag}/{name}
+/// - 否则与latest.json相同: 替换为目标仓库的地址, 然后执行--rewrite规则
+pub fn rewrite(cli: &Cli, content: &str) -> String {
+    let source = cli.source().download_url();
+    rewrite_enclosures(content, |url| {
+        if let Some(base) = &cli.appcast_url_base
+            && let Some(path) = url.strip_prefix(&source)
+        {
+            return format!("{}{path}", base.trim_end_matches('/'));
+        }
+        crate::replace_download_url(cli, url.to_string())
+    })
+}
+
+fn rewrite_enclosures(content: &str, rewrite_url: impl Fn(&str) -> String) -> String {
+    ENCLOSURE_URL
+        .replace_all(content, |caps: &Captures| {
+            let (quote, url) = match (caps.get(2), caps.get(3)) {
+                (Some(url), _) => ('"', url.as_str()),
+                (_, Some(url)) => ('\'', url.as_str()),
+                _ => unreachable!("one of the alternatives matches"),
+            };
+            // 属性值中的&转义为&amp;
+            let url = rewrite_url(&url.replace("&amp;", "&")).replace('&', "&amp;");
+            format!("{}{quote}{url}{quote}", &caps[1])
+        })
+        .into_owned()
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    const APPCAST: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0" xmlns:sparkle="http://www.andymatuschak.org/xml-namespaces/sparkle">
+  <channel>
+    <item>
+      <sparkle:releaseNotesLink>https://github.com/hepengju/redis-me/releases/tag/v1.2.0</sparkle:releaseNotesLink>
+      <enclosure url="https://github.com/hepengju/redis-me/releases/download/v1.2.0/RedisMe.zip" length="1024" type="application/octet-stream" sparkle:edSignature="abc=="/>
+      <sparkle:deltas>
+        <enclosure sparkle:deltaFrom="1.1.0" url='https://github.com/hepengju/redis-me/releases/download/v1.2.0/RedisMe-1.1.0.delta?a=1&amp;b=2' length="64"/>
+      </sparkle:deltas>
+    </item>
+  </channel>
+</rss>"#;
+
+    #[test]
+    fn test_appcast_rewrite() {
+        let args = [
+            "release2gitee",
+            "--github-owner=hepengju",
+            "--github-repo=redis-me",
+            "--gitee-owner=hepengju",
+            "--gitee-repo=redis-me",
+            "--gitee-token=t",
+        ];
+        let cli = Cli::parse_from(args);
+        let content = rewrite(&cli, APPCAST);
+        assert!(content.contains(
+            r#"<enclosure url="https://gitee.com/hepengju/redis-me/releases/download/v1.2.0/RedisMe.zip" length="1024""#
+        ));
+        assert!(content.contains(
+            "url='https://gitee.com/hepengju/redis-me/releases/download/v1.2.0/RedisMe-1.1.0.delta?a=1&amp;b=2'"
+        ));
+        // 只替换enclosure的地址
+        assert!(content.contains(
+            "<sparkle:releaseNotesLink>https://github.com/hepengju/redis-me/releases/tag/v1.2.0<"
+        ));
+
+        let cli = Cli::parse_from(
+            args.iter()
+                .copied()
+                .chain(["--appcast-url-base=https://cdn.example.cn/redis-me/"]),
+        );
+        let content = rewrite(&cli, APPCAST);
+        assert!(content.contains(r#"url="https://cdn.example.cn/redis-me/v1.2.0/RedisMe.zip""#));
+    }
+}
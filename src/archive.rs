@@ -0,0 +1,267 @@
+//! 压缩包附件中的下载地址替换(--rewrite-archive): 解包, 替换--rewrite-archive-entry匹配的文本文件, 重新打包
+//! - 其他文件保持原样, 条目的顺序、时间、权限不变, 相同的输入得到相同的输出(多次同步的摘要一致)
+//! - 支持.tar.gz/.tgz和.zip, 内容没有变化时不修改文件
+
+use crate::AnyResult;
+use crate::rewrite::glob_match;
+use flate2::read::GzDecoder;
+use flate2::{Compression, GzBuilder};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// 替换压缩包中匹配的文本文件, 返回是否有变化; 不支持的格式返回None
+pub fn rewrite(
+    path: &Path,
+    entries: &[String],
+    rewrite: impl Fn(String) -> String,
+) -> AnyResult<Option<bool>> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let matches = |entry: &str| entries.iter().any(|pattern| glob_match(pattern, entry));
+    let tmp_path = repack_path(path);
+    let result = if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        rewrite_tar_gz(path, &tmp_path, matches, rewrite)
+    } else if name.ends_with(".zip") {
+        rewrite_zip(path, &tmp_path, matches, rewrite)
+    } else {
+        return Ok(None);
+    };
+    match result {
+        Ok(true) => {
+            fs::rename(&tmp_path, path)?;
+            Ok(Some(true))
+        }
+        Ok(false) => {
+            let _ = fs::remove_file(&tmp_path);
+            Ok(Some(false))
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e)
+        }
+    }
+}
+
+fn repack_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".repack");
+    path.with_file_name(name)
+}
+
+/// 替换UTF-8文本的内容, 非文本或没有变化时返回None
+fn rewrite_text(data: &[u8], rewrite: &impl Fn(String) -> String) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(data).ok()?;
+    let rewritten = rewrite(text.to_string());
+    (rewritten != text).then(|| rewritten.into_bytes())
+}
+
+fn rewrite_tar_gz(
+    path: &Path,
+    tmp_path: &Path,
+    matches: impl Fn(&str) -> bool,
+    rewrite: impl Fn(String) -> String,
+) -> AnyResult<bool> {
+    let mut archive = tar::Archive::new(GzDecoder::new(BufReader::new(File::open(path)?)));
+    // gzip头中的时间为0, 保证相同的内容得到相同的输出
+    let encoder = GzBuilder::new().write(
+        BufWriter::new(File::create(tmp_path)?),
+        Compression::default(),
+    );
+    let mut builder = tar::Builder::new(encoder);
+    let mut changed = false;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let mut header = entry.header().clone();
+        let entry_path = entry.path()?.into_owned();
+        let link_name = entry.link_name()?.map(|link| link.into_owned());
+        if let Some(extensions) = entry.pax_extensions()? {
+            let extensions = extensions
+                .map(|extension| {
+                    let extension = extension?;
+                    Ok((
+                        extension.key()?.to_string(),
+                        extension.value_bytes().to_vec(),
+                    ))
+                })
+                .collect::<AnyResult<Vec<_>>>()?;
+            builder.append_pax_extensions(
+                extensions
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.as_slice())),
+            )?;
+        }
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        let entry_type = header.entry_type();
+        if entry_type.is_file()
+            && matches(&entry_path.to_string_lossy())
+            && let Some(rewritten) = rewrite_text(&data, &rewrite)
+        {
+            data = rewritten;
+            header.set_size(data.len() as u64);
+            changed = true;
+        }
+        match link_name {
+            Some(link_name) if entry_type.is_symlink() || entry_type.is_hard_link() => {
+                builder.append_link(&mut header, &entry_path, &link_name)?
+            }
+            _ if entry_type.is_pax_global_extensions() => builder.append(&header, &*data)?,
+            _ => builder.append_data(&mut header, &entry_path, &*data)?,
+        }
+    }
+    builder.into_inner()?.finish()?.flush()?;
+    Ok(changed)
+}
+
+fn rewrite_zip(
+    path: &Path,
+    tmp_path: &Path,
+    matches: impl Fn(&str) -> bool,
+    rewrite: impl Fn(String) -> String,
+) -> AnyResult<bool> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let mut writer = ZipWriter::new(BufWriter::new(File::create(tmp_path)?));
+    let mut changed = false;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if !file.is_dir() && matches(file.name()) {
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            if let Some(rewritten) = rewrite_text(&data, &rewrite) {
+                // 只支持deflate压缩, 其他压缩方式的文件改为deflate
+                let method = match file.compression() {
+                    CompressionMethod::Stored => CompressionMethod::Stored,
+                    _ => CompressionMethod::Deflated,
+                };
+                let mut options = SimpleFileOptions::default()
+                    .compression_method(method)
+                    .last_modified_time(file.last_modified().unwrap_or_default())
+                    .large_file(rewritten.len() as u64 >= u32::MAX as u64);
+                if let Some(mode) = file.unix_mode() {
+                    options = options.unix_permissions(mode);
+                }
+                writer.start_file(file.name(), options)?;
+                writer.write_all(&rewritten)?;
+                changed = true;
+                continue;
+            }
+        }
+        drop(file);
+        // 其他文件复制压缩后的原始数据
+        writer.raw_copy_file(archive.by_index_raw(i)?)?;
+    }
+    writer.set_raw_comment(archive.comment().into());
+    writer.finish()?.flush()?;
+    Ok(changed)
+}
+
+/// 校验和文件(如checksums.txt)中替换重新打包的附件的sha256, 返回是否有变化
+pub fn update_checksums(content: &str, replaced: &[(String, String)]) -> Option<String> {
+    let mut content = content.to_string();
+    let mut changed = false;
+    for (old, new) in replaced {
+        for old in [old.clone(), old.to_uppercase()] {
+            if content.contains(&old) {
+                content = content.replace(&old, new);
+                changed = true;
+            }
+        }
+    }
+    changed.then_some(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsutil::sha256_file;
+    use std::env;
+
+    fn replace(text: String) -> String {
+        text.replace(
+            "https://github.com/hepengju/redis-me/releases/download",
+            "https://gitee.com/hepengju/redis-me/releases/download",
+        )
+    }
+
+    #[test]
+    fn test_rewrite_archive() -> AnyResult<()> {
+        let dir = env::temp_dir().join(format!("release2gitee-archive-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let config =
+            "url = https://github.com/hepengju/redis-me/releases/download/v1.0.0/app.bin\n";
+
+        // tar.gz
+        let tar_gz = dir.join("installer.tar.gz");
+        let mut builder = tar::Builder::new(
+            GzBuilder::new().write(File::create(&tar_gz)?, Compression::default()),
+        );
+        for (name, content) in [("app/config.toml", config), ("app/README", config)] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(1_700_000_000);
+            builder.append_data(&mut header, name, content.as_bytes())?;
+        }
+        builder.into_inner()?.finish()?;
+        let entries = ["*/config.toml".to_string()];
+        assert_eq!(rewrite(&tar_gz, &entries, replace)?, Some(true));
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&tar_gz)?));
+        let mut contents = Vec::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            assert_eq!(entry.header().mtime()?, 1_700_000_000);
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            contents.push(content);
+        }
+        assert!(
+            contents[0].contains("https://gitee.com/hepengju/redis-me/releases/download/v1.0.0")
+        );
+        assert_eq!(contents[1], config);
+        // 已替换过时没有变化
+        let hash = sha256_file(&tar_gz)?;
+        assert_eq!(rewrite(&tar_gz, &entries, replace)?, Some(false));
+        assert_eq!(sha256_file(&tar_gz)?, hash);
+
+        // zip: 相同的输入得到相同的输出
+        let zip_path = dir.join("installer.zip");
+        let mut writer = ZipWriter::new(File::create(&zip_path)?);
+        writer.start_file("config.toml", SimpleFileOptions::default())?;
+        writer.write_all(config.as_bytes())?;
+        writer.start_file("app.bin", SimpleFileOptions::default())?;
+        writer.write_all(&[0, 1, 2])?;
+        writer.finish()?;
+        let original = fs::read(&zip_path)?;
+        let entries = ["config.toml".to_string()];
+        assert_eq!(rewrite(&zip_path, &entries, replace)?, Some(true));
+        let first = fs::read(&zip_path)?;
+        fs::write(&zip_path, &original)?;
+        rewrite(&zip_path, &entries, replace)?;
+        assert_eq!(fs::read(&zip_path)?, first);
+        let mut archive = ZipArchive::new(File::open(&zip_path)?)?;
+        let mut content = String::new();
+        archive
+            .by_name("config.toml")?
+            .read_to_string(&mut content)?;
+        assert!(content.contains("https://gitee.com/"));
+        assert_eq!(archive.by_name("app.bin")?.size(), 3);
+
+        assert_eq!(rewrite(&dir.join("app.dmg"), &entries, replace)?, None);
+
+        let checksums = "ABCD  installer.zip\nef01  other.zip\n";
+        assert_eq!(
+            update_checksums(checksums, &[("abcd".to_string(), "9999".to_string())]).as_deref(),
+            Some("9999  installer.zip\nef01  other.zip\n")
+        );
+        assert_eq!(update_checksums(checksums, &[]), None);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::AnyResult;
+use crate::fsutil::FileWriter;
+use crate::model::{Assert, Cli};
+use crate::provider;
+use crate::units::ByteSize;
+use log::{debug, info, warn};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// --cache-max-size的默认值
+const DEFAULT_MAX_SIZE: ByteSize = ByteSize(10 * 1000 * 1000 * 1000);
+
+/// 按摘要保存的附件缓存(--cache-dir): {cache_dir}/.assets/sha256/{hex}
+/// - 多次运行之间保留下载的附件, 不同仓库、不同tag中相同的附件只保存一份
+/// - 总大小超过--cache-max-size时按修改时间(取出时更新)淘汰最近最少使用的附件
+/// - 只缓存有摘要(github的sha256)的附件, 取出后仍校验摘要
+#[derive(Debug)]
+pub struct AssetCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl AssetCache {
+    /// 未设置--cache-dir时不缓存(系统临时目录可能随时被清理)
+    pub fn open(cli: &Cli) -> Option<Self> {
+        let cache_dir = cli.cache_dir.as_ref()?;
+        Some(AssetCache {
+            dir: cache_dir.join(".assets").join("sha256"),
+            max_size: cli.cache_max_size.unwrap_or(DEFAULT_MAX_SIZE).as_u64(),
+        })
+    }
+
+    /// 缓存文件路径: 摘要只能是sha256的十六进制
+    fn path(&self, asset: &Assert) -> Option<PathBuf> {
+        let hex = asset.digest.as_deref()?.strip_prefix("sha256:")?;
+        if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(self.dir.join(hex.to_ascii_lowercase()))
+    }
+
+    /// 取出缓存的附件到本地文件, 不存在时返回false; 复制而不是硬链接(latest.json会被原地修改)
+    pub fn get(&self, asset: &Assert, file_path: &Path) -> AnyResult<bool> {
+        let Some(path) = self.path(asset) else {
+            return Ok(false);
+        };
+        if !path.is_file() {
+            return Ok(false);
+        }
+        let mut writer = FileWriter::create(file_path, true)?;
+        io::copy(&mut File::open(&path)?, &mut writer)?;
+        writer.commit()?;
+        provider::verify_asset_digest(asset, file_path)?;
+        // 更新修改时间: 淘汰时按修改时间排序
+        File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(SystemTime::now())?;
+        info!("asset restored from cache dir: {}", asset.name);
+        Ok(true)
+    }
+
+    /// 保存下载的附件, 然后淘汰超出总大小上限的附件
+    pub fn put(&self, asset: &Assert, file_path: &Path) -> AnyResult<()> {
+        let Some(path) = self.path(asset) else {
+            return Ok(());
+        };
+        if !path.is_file() {
+            fs::create_dir_all(&self.dir)?;
+            let mut writer = FileWriter::create(&path, true)?;
+            io::copy(&mut File::open(file_path)?, &mut writer)?;
+            writer.commit()?;
+            debug!("asset saved to cache dir: {}", path.display());
+        }
+        self.evict()
+    }
+
+    /// 按修改时间从旧到新删除, 直到总大小不超过上限
+    fn evict(&self) -> AnyResult<()> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                entries.push((metadata.modified()?, metadata.len(), entry.path()));
+            }
+        }
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        entries.sort();
+        for (_, size, path) in entries {
+            if total <= self.max_size {
+                break;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => {
+                    total -= size;
+                    info!("asset evicted from cache dir: {}", path.display());
+                }
+                Err(e) => warn!("cache dir evict error: {}, {e}", path.display()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsutil;
+    use std::env;
+    use std::time::Duration;
+
+    #[test]
+    fn test_asset_cache_evict() -> AnyResult<()> {
+        let root = env::temp_dir().join(format!("asset-cache-test-{}", std::process::id()));
+        let cache = AssetCache {
+            dir: root.join("sha256"),
+            max_size: 10,
+        };
+        fs::create_dir_all(&root)?;
+        let asset = |content: &str| -> AnyResult<(Assert, PathBuf)> {
+            let file = root.join(content);
+            fs::write(&file, content)?;
+            let asset = Assert {
+                id: None,
+                name: content.to_string(),
+                size: Some(content.len() as u64),
+                browser_download_url: String::new(),
+                digest: Some(format!("sha256:{}", fsutil::sha256_file(&file)?)),
+                label: None,
+                download_count: None,
+                content_type: None,
+                api_url: None,
+            };
+            Ok((asset, file))
+        };
+
+        let (a, a_file) = asset("aaaa")?;
+        let (b, b_file) = asset("bbbb")?;
+        let (c, c_file) = asset("cccc")?;
+        cache.put(&a, &a_file)?;
+        cache.put(&b, &b_file)?;
+        // a比b旧, 取出后a变为最近使用
+        let now = SystemTime::now();
+        for (asset, age) in [(&a, 120), (&b, 60)] {
+            File::options()
+                .write(true)
+                .open(cache.path(asset).unwrap())?
+                .set_modified(now - Duration::from_secs(age))?;
+        }
+        let restored = root.join("restored");
+        assert!(cache.get(&a, &restored)?);
+        assert_eq!(fs::read_to_string(&restored)?, "aaaa");
+
+        // 超过10字节: 淘汰最近最少使用的b
+        cache.put(&c, &c_file)?;
+        assert!(!cache.get(&b, &restored)?);
+        assert!(cache.get(&a, &restored)?);
+        assert!(cache.get(&c, &restored)?);
+
+        // 没有摘要的附件不缓存
+        let no_digest = Assert { digest: None, ..a };
+        assert!(!cache.get(&no_digest, &restored)?);
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
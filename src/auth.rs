@@ -0,0 +1,83 @@
+use crate::AnyResult;
+use crate::model::{AuthArgs, AuthCommand, TokenPlatform};
+#[cfg(feature = "keyring")]
+use {
+    anyhow::bail,
+    log::{debug, info},
+    std::io::{self, IsTerminal},
+};
+
+/// 系统密钥环中的服务名, 账号为平台名(github/gitee/gitlab/gitea)
+#[cfg(feature = "keyring")]
+const SERVICE: &str = "release2gitee";
+
+/// auth子命令: login保存token, logout删除token
+pub fn run(args: &AuthArgs) -> AnyResult<()> {
+    match &args.command {
+        AuthCommand::Login(args) => login(args.platform),
+        AuthCommand::Logout(args) => logout(args.platform),
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn entry(platform: TokenPlatform) -> AnyResult<keyring::Entry> {
+    Ok(keyring::Entry::new(SERVICE, &platform.to_string())?)
+}
+
+/// 从标准输入读取token(不出现在进程列表和shell历史中), 保存到系统密钥环
+#[cfg(feature = "keyring")]
+fn login(platform: TokenPlatform) -> AnyResult<()> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        eprint!("paste {platform} token: ");
+    }
+    let mut token = String::new();
+    stdin.read_line(&mut token)?;
+    let token = token.trim();
+    if token.is_empty() {
+        bail!("{platform} token is empty");
+    }
+    entry(platform)?.set_password(token)?;
+    info!("{platform} token saved to keyring");
+    Ok(())
+}
+
+#[cfg(feature = "keyring")]
+fn logout(platform: TokenPlatform) -> AnyResult<()> {
+    match entry(platform)?.delete_credential() {
+        Ok(()) => info!("{platform} token removed from keyring"),
+        Err(keyring::Error::NoEntry) => info!("{platform} token not found in keyring"),
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+/// 读取auth login保存的token: 不存在或密钥环不可用(如没有桌面环境的CI)时返回None
+#[cfg(feature = "keyring")]
+pub fn keyring_token(platform: TokenPlatform) -> Option<String> {
+    match entry(platform).and_then(|entry| Ok(entry.get_password()?)) {
+        Ok(token) => {
+            debug!("{platform} token read from keyring");
+            Some(token)
+        }
+        Err(e) => {
+            debug!("{platform} token not read from keyring: {e:#}");
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn login(_: TokenPlatform) -> AnyResult<()> {
+    anyhow::bail!("auth login is not supported, rebuild with --features keyring")
+}
+
+#[cfg(not(feature = "keyring"))]
+fn logout(_: TokenPlatform) -> AnyResult<()> {
+    anyhow::bail!("auth logout is not supported, rebuild with --features keyring")
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_token(_: TokenPlatform) -> Option<String> {
+    None
+}
@@ -0,0 +1,393 @@
+use crate::cancel::{Cancellation, CancellationToken};
+use crate::model::{BenchArgs, Cli, Direction, Forge, RepoEndpoint, SyncConfig, Target};
+use crate::provider::{self, RestForge};
+use crate::report::SyncReport;
+use crate::units::{ByteRate, ByteSize};
+use crate::{AnyResult, http, httpd};
+use log::{debug, info};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use std::{env, fs, process};
+
+const OWNER: &str = "bench";
+const SOURCE_REPO: &str = "source";
+const TARGET_REPO: &str = "target";
+
+/// 性能测试结果
+#[derive(Debug, Default)]
+pub struct BenchReport {
+    pub releases: usize,
+    pub assets: usize,
+    pub elapsed: Duration,
+    // 模拟服务收到的请求: 操作 -> 次数
+    pub ops: BTreeMap<&'static str, u64>,
+    pub downloaded_bytes: u64,
+    pub uploaded_bytes: u64,
+}
+
+impl BenchReport {
+    /// 吞吐量: 下载和上传的总字节数/耗时
+    pub fn throughput(&self) -> ByteRate {
+        let secs = self.elapsed.as_secs_f64().max(0.001);
+        let bytes = (self.downloaded_bytes + self.uploaded_bytes) as f64;
+        ByteRate((bytes / secs) as u64)
+    }
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "releases: {}, assets: {}, elapsed: {:.3}s",
+            self.releases,
+            self.assets,
+            self.elapsed.as_secs_f64()
+        )?;
+        writeln!(
+            f,
+            "downloaded: {}, uploaded: {}, throughput: {}",
+            ByteSize(self.downloaded_bytes),
+            ByteSize(self.uploaded_bytes),
+            self.throughput()
+        )?;
+        let ops = self
+            .ops
+            .iter()
+            .map(|(op, count)| format!("{op}={count}"))
+            .collect::<Vec<_>>();
+        write!(f, "ops: {}", ops.join(", "))
+    }
+}
+
+/// 性能测试: 启动本地模拟服务(源仓库和目标仓库均为gitea风格的接口), 运行完整的同步流程
+/// - 不访问外部网络, 结果可重复, 用于对比不同参数(如--fsync, --http-timeout)的影响
+pub fn run(cli: &Cli, args: &BenchArgs) -> AnyResult<BenchReport> {
+    let server = MockServer::start(args)?;
+    let cli = &bench_cli(cli, args, &server.url);
+    info!(
+        "bench start: {} releases x {} assets x {}, mock server: {}",
+        args.releases, args.assets, args.asset_size, server.url
+    );
+
+    let client = &http::init_client(cli)?;
+    let config = &SyncConfig::from(cli);
+    let source = RestForge::with_transport(
+        cli,
+        provider::transport(client, config),
+        server.endpoint(SOURCE_REPO),
+    );
+    let target = provider::target(cli, client, config)?;
+    let cancellation = Cancellation::new(&CancellationToken::new(), None);
+    let mut report = SyncReport::default();
+
+    let start = Instant::now();
+    let result = crate::sync_releases(
+        cli,
+        &source,
+        target.as_ref(),
+        None,
+        &cancellation,
+        &mut report,
+    );
+    let elapsed = start.elapsed();
+    let stats = server.stop();
+    let _ = fs::remove_dir_all(env::temp_dir().join(&cli.github_repo));
+    result?;
+
+    Ok(BenchReport {
+        releases: args.releases,
+        assets: args.releases * args.assets,
+        elapsed,
+        ops: stats.ops,
+        downloaded_bytes: stats.downloaded_bytes,
+        uploaded_bytes: stats.uploaded_bytes,
+    })
+}
+
+/// 同步参数: 保留用户指定的参数, 源仓库和目标仓库指向模拟服务
+fn bench_cli(cli: &Cli, args: &BenchArgs, url: &str) -> Cli {
+    let mut cli = cli.clone();
+    // 本地状态文件(缓存、同步日志等)在临时目录的github_repo下, 结束后删除
+    cli.github_owner = OWNER.to_string();
+    cli.github_repo = format!("release2gitee-bench-{}", process::id());
+    cli.direction = Direction::GithubToGitee;
+    cli.target = vec![Target::Gitea];
+    cli.gitea_url = url.to_string();
+    cli.gitea_owner = Some(OWNER.to_string());
+    cli.gitea_repo = Some(TARGET_REPO.to_string());
+    cli.gitea_token = Some("bench".to_string());
+    cli.github_latest_release_count = args.releases;
+    cli.gitee_retain_release_count = args.releases.max(cli.gitee_retain_release_count);
+    cli.release_id_range = None;
+    cli.github_download_mirror = Vec::new();
+    cli.cache_store = None;
+    cli.cache_dir = None;
+    cli.allow_stale_source = None;
+    cli.propagate_deletes = false;
+    cli.no_cache = true;
+    cli.no_resume = true;
+    cli.command = None;
+    cli
+}
+
+/// 模拟服务的统计
+#[derive(Debug, Default)]
+struct MockStats {
+    ops: BTreeMap<&'static str, u64>,
+    downloaded_bytes: u64,
+    uploaded_bytes: u64,
+}
+
+/// 模拟服务的状态: 源仓库的releases固定生成, 目标仓库的releases由同步流程创建
+#[derive(Debug, Default)]
+struct MockState {
+    source_releases: Vec<Value>,
+    target_releases: Vec<Value>,
+    stats: MockStats,
+}
+
+/// 本地http模拟服务: 每个连接处理一个请求(Connection: close)
+struct MockServer {
+    url: String,
+    state: Arc<Mutex<MockState>>,
+    stopped: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    fn start(args: &BenchArgs) -> AnyResult<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let asset_size = args.asset_size.as_u64();
+        let state = Arc::new(Mutex::new(MockState {
+            source_releases: source_releases(&url, args),
+            ..Default::default()
+        }));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = state.clone();
+            let stopped = stopped.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    let state = state.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = handle_connection(stream, &state, asset_size) {
+                            debug!("mock server connection error: {e:#}");
+                        }
+                    });
+                }
+            })
+        };
+        Ok(MockServer {
+            url,
+            state,
+            stopped,
+            handle,
+        })
+    }
+
+    fn endpoint(&self, repo: &str) -> RepoEndpoint {
+        RepoEndpoint {
+            forge: Forge::Gitea,
+            host: self.url.clone(),
+            owner: OWNER.to_string(),
+            repo: repo.to_string(),
+            token: Some("bench".to_string()),
+        }
+    }
+
+    /// 停止服务, 返回统计
+    fn stop(self) -> MockStats {
+        self.stopped.store(true, Ordering::SeqCst);
+        // 唤醒阻塞在accept的线程
+        let _ = TcpStream::connect(self.url.trim_start_matches("http://"));
+        let _ = self.handle.join();
+        let mut state = self.state.lock().expect("mock state lock");
+        std::mem::take(&mut state.stats)
+    }
+}
+
+/// 源仓库的releases: id越大越新
+fn source_releases(url: &str, args: &BenchArgs) -> Vec<Value> {
+    (1..=args.releases)
+        .rev()
+        .map(|i| {
+            let tag_name = format!("v0.{i}.0");
+            let assets = (1..=args.assets)
+                .map(|j| {
+                    let name = format!("asset-{j}.bin");
+                    json!({
+                        "name": name,
+                        "size": args.asset_size.as_u64(),
+                        "browser_download_url": format!("{url}/download/{tag_name}/{name}"),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "id": i,
+                "tag_name": tag_name,
+                "name": tag_name,
+                "body": format!("release {tag_name}"),
+                "prerelease": false,
+                "target_commitish": "main",
+                "assets": assets,
+            })
+        })
+        .collect()
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    state: &Mutex<MockState>,
+    asset_size: u64,
+) -> AnyResult<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Some(request) = httpd::read_request(&mut reader, usize::MAX)? else {
+        return Ok(());
+    };
+    let mut writer = stream;
+    let api = format!("/api/v1/repos/{OWNER}");
+    let path = request.path.split('?').next().unwrap_or_default();
+    let segments = path
+        .strip_prefix(&api)
+        .unwrap_or(path)
+        .trim_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+
+    let mut state = state.lock().expect("mock state lock");
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", [SOURCE_REPO, "releases"]) => {
+            state.stats.count("source_list");
+            // 只有第一页
+            let releases = if request.path.contains("page=1") {
+                Value::from(state.source_releases.clone())
+            } else {
+                json!([])
+            };
+            write_json(&mut writer, 200, &releases)
+        }
+        ("GET", ["download", _, _]) => {
+            state.stats.count("download");
+            state.stats.downloaded_bytes += asset_size;
+            drop(state);
+            write_asset(&mut writer, asset_size)
+        }
+        ("GET", [TARGET_REPO, "releases"]) => {
+            state.stats.count("target_list");
+            write_json(
+                &mut writer,
+                200,
+                &Value::from(state.target_releases.clone()),
+            )
+        }
+        ("POST", [TARGET_REPO, "releases"]) => {
+            state.stats.count("release_create");
+            let mut release: Value = serde_json::from_slice(&request.body)?;
+            release["id"] = json!(state.target_releases.len() + 1);
+            release["assets"] = json!([]);
+            state.target_releases.push(release.clone());
+            write_json(&mut writer, 201, &release)
+        }
+        ("PATCH", [TARGET_REPO, "releases", _]) => {
+            state.stats.count("release_update");
+            write_json(&mut writer, 200, &json!({}))
+        }
+        ("DELETE", [TARGET_REPO, "releases", ..]) => {
+            state.stats.count("delete");
+            write_json(&mut writer, 204, &json!({}))
+        }
+        ("POST", [TARGET_REPO, "releases", id, "assets"]) => {
+            state.stats.count("upload");
+            state.stats.uploaded_bytes += request.body.len() as u64;
+            let name = request
+                .path
+                .split_once("name=")
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_default();
+            let asset = json!({
+                "name": name,
+                "size": asset_size,
+                "browser_download_url": format!("/download/{name}"),
+            });
+            if let Some(release) = state
+                .target_releases
+                .iter_mut()
+                .find(|release| release["id"].as_u64() == id.parse().ok())
+                && let Some(assets) = release["assets"].as_array_mut()
+            {
+                assets.push(asset.clone());
+            }
+            write_json(&mut writer, 201, &asset)
+        }
+        _ => {
+            state.stats.count("not_found");
+            write_json(&mut writer, 404, &json!({"message": "not found"}))
+        }
+    }
+}
+
+impl MockStats {
+    fn count(&mut self, op: &'static str) {
+        *self.ops.entry(op).or_default() += 1;
+    }
+}
+
+fn write_json(writer: &mut impl Write, status: u16, value: &Value) -> AnyResult<()> {
+    httpd::write_response(
+        writer,
+        status,
+        "application/json",
+        &serde_json::to_vec(value)?,
+    )
+}
+
+/// 附件内容: 按块写入, 不在内存中生成完整文件
+fn write_asset(writer: &mut impl Write, size: u64) -> AnyResult<()> {
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {size}\r\nConnection: close\r\n\r\n"
+    )?;
+    io::copy(&mut io::repeat(0xA5).take(size), writer)?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_bench() -> AnyResult<()> {
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "bench",
+            "--releases=2",
+            "--assets=2",
+            "--asset-size=64KiB",
+        ]);
+        let Some(crate::model::Command::Bench(args)) = &cli.command else {
+            panic!("bench subcommand expected");
+        };
+        let report = run(&cli, args)?;
+        assert_eq!(report.ops["release_create"], 2);
+        assert_eq!(report.ops["download"], 4);
+        assert_eq!(report.ops["upload"], 4);
+        assert_eq!(report.downloaded_bytes, 4 * 64 * 1024);
+        assert!(report.uploaded_bytes > report.downloaded_bytes);
+        assert!(!report.ops.contains_key("not_found"));
+        Ok(())
+    }
+}
@@ -0,0 +1,631 @@
+use crate::AnyResult;
+use crate::i18n::{self, Lang};
+use crate::model::{Cli, Release, RepoEndpoint};
+use crate::provenance;
+use crate::schedule;
+use crate::units::ByteSize;
+use anyhow::Context;
+use handlebars::Handlebars;
+use regex::{Captures, Regex};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::thread;
+
+/// Release body转换: 同步到Gitee之前依次处理
+/// 1. github仓库地址替换为gitee仓库地址, 然后执行--rewrite自定义的替换规则
+/// 2. #123、@user和相对链接转换为源仓库的绝对地址(在目标仓库中无法访问)
+/// 3. emoji短代码(:rocket:)转换为Unicode表情(Gitee不渲染短代码)
+pub fn convert_release_body(cli: &Cli, tag_name: &str, body: String) -> String {
+    let mut body = body;
+    if cli.release_body_url_replace {
+        body = crate::replace_download_url(cli, body);
+    }
+    if cli.release_body_absolute_links {
+        body = absolute_references(&cli.source(), tag_name, &body);
+    }
+    if cli.release_body_emoji {
+        body = replace_emoji_shortcodes(&body);
+    }
+    body
+}
+
+/// 通过--body-transform-cmd外部命令转换源仓库的body(如机器翻译): body从标准输入传入, 标准输出为转换结果
+/// - 环境变量RELEASE2GITEE_TAG、RELEASE2GITEE_REPO为tag和源仓库(owner/repo)
+pub fn transform_body(cli: &Cli, tag_name: &str, body: String) -> AnyResult<String> {
+    let Some(cmd) = &cli.body_transform_cmd else {
+        return Ok(body);
+    };
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(cmd);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(cmd);
+        command
+    };
+    let mut child = command
+        .env("RELEASE2GITEE_TAG", tag_name)
+        .env("RELEASE2GITEE_REPO", cli.source().path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("body transform cmd start error: {cmd}"))?;
+
+    // 另起线程写入标准输入, 避免输出较多时互相等待
+    let mut stdin = child.stdin.take().context("body transform cmd stdin")?;
+    let writer = thread::spawn(move || stdin.write_all(body.as_bytes()));
+    let mut stdout = String::new();
+    child
+        .stdout
+        .take()
+        .context("body transform cmd stdout")?
+        .read_to_string(&mut stdout)
+        .with_context(|| format!("body transform cmd output is not utf-8: {cmd}"))?;
+    let output = child.wait_with_output()?;
+    // 命令不读取标准输入时写入失败(broken pipe), 以退出码为准
+    let _ = writer.join();
+    if !output.status.success() {
+        anyhow::bail!(
+            "body transform cmd failed ({}): {cmd}, {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(stdout)
+}
+
+/// 转换命令的标记: 源body和命令的摘要, 未变化时不重新转换(翻译等结果可能每次不同, 避免反复更新)
+pub fn transform_marker(cli: &Cli, body: &str) -> Option<String> {
+    let cmd = cli.body_transform_cmd.as_ref()?;
+    let mut hasher = Sha256::new();
+    hasher.update(cmd.as_bytes());
+    hasher.update([0]);
+    hasher.update(body.as_bytes());
+    let digest = crate::s3::hex(&hasher.finalize()[..8]);
+    Some(format!("<!-- release2gitee:transform {digest} -->"))
+}
+
+/// 按--release-body-template(Handlebars模板)组合目标仓库的release body, 未设置时为转换后的body
+/// 变量: body、tag、name、date、prerelease、source_url、target_url、assets(name、size、url、source_url)
+pub fn render_template(cli: &Cli, release: &Release, body: String) -> AnyResult<String> {
+    let Some(path) = &cli.release_body_template else {
+        return Ok(body);
+    };
+    let template = fs::read_to_string(path)
+        .with_context(|| format!("read release body template error: {}", path.display()))?;
+    let (source, target) = (cli.source(), cli.target());
+    let tag_name = &release.tag_name;
+    let assets = release
+        .assets
+        .iter()
+        .map(|asset| {
+            json!({
+                "name": asset.name,
+                "size": asset.size.map(|size| ByteSize(size).to_string()),
+                "url": format!("{}/{tag_name}/{}", target.download_url(), asset.name),
+                "source_url": asset.browser_download_url,
+            })
+        })
+        .collect::<Vec<_>>();
+    let data = json!({
+        "body": body,
+        "tag": tag_name,
+        "name": release.name,
+        // 2025-12-25T08:22:42Z => 2025-12-25
+        "date": release.updated_at.as_deref().map(|date| date.get(..10).unwrap_or(date)),
+        "prerelease": release.prerelease,
+        "source_url": format!("{}/releases/tag/{tag_name}", source.web_url()),
+        "target_url": format!("{}/releases/tag/{tag_name}", target.web_url()),
+        "assets": assets,
+    });
+    let mut handlebars = Handlebars::new();
+    // Markdown不转义HTML字符
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars
+        .render_template(&template, &data)
+        .with_context(|| format!("render release body template error: {}", path.display()))
+}
+
+/// 在body开头注明原始发布日期(--release-body-published-date)
+pub fn prepend_published_date(cli: &Cli, release: &Release, body: String) -> String {
+    let Some(date) = release
+        .published_date()
+        .filter(|_| cli.release_body_published_date)
+    else {
+        return body;
+    };
+    let header = match i18n::lang() {
+        Lang::En => format!("> 📅 Originally published {date}"),
+        Lang::Zh => format!("> 📅 原始发布于 {date}"),
+    };
+    format!("{header}\n\n{body}")
+}
+
+/// 截断时为页脚和部分同步的标注预留的字符数
+const RESERVED_CHARS: usize = 2000;
+
+/// body超过最大字符数时在Markdown段落处截断, 末尾附上源仓库完整说明的链接
+/// - 优先在空行处截断, 不在代码块中间截断; 没有合适的位置时在行尾截断
+pub fn truncate_body(body: String, max_chars: usize, notes_url: &str) -> String {
+    if body.chars().count() <= max_chars {
+        return body;
+    }
+    let notice = match i18n::lang() {
+        Lang::En => {
+            format!("\n\n---\n> ✂️ release notes truncated, see the full notes: {notes_url}")
+        }
+        Lang::Zh => format!("\n\n---\n> ✂️ 发布说明过长已截断, 完整内容请查看: {notes_url}"),
+    };
+    let budget = max_chars
+        .saturating_sub(RESERVED_CHARS.min(max_chars / 2))
+        .saturating_sub(notice.chars().count());
+    let limit = body
+        .char_indices()
+        .nth(budget)
+        .map_or(body.len(), |(i, _)| i);
+
+    // 代码块之外的空行和行尾: 截断位置
+    let (mut paragraph, mut line) = (0, 0);
+    let mut in_fence = false;
+    let mut offset = 0;
+    for text in body.split_inclusive('\n') {
+        let end = offset + text.len();
+        if end > limit {
+            break;
+        }
+        let trimmed = text.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if !in_fence {
+            line = end;
+            if text.trim().is_empty() {
+                paragraph = offset;
+            }
+        }
+        offset = end;
+    }
+    let cut = match (paragraph, line) {
+        (0, 0) => limit,
+        (0, line) => line,
+        (paragraph, _) => paragraph,
+    };
+    format!("{}{notice}", body[..cut].trim_end())
+}
+
+const PARTIAL_MIRROR_BEGIN: &str = "<!-- release2gitee:partial-mirror -->";
+const PARTIAL_MIRROR_END: &str = "<!-- /release2gitee:partial-mirror -->";
+
+/// 部分同步的标注: body末尾列出未同步的附件, 没有缺失的附件时移除标注
+pub fn partial_mirror_body(body: &str, missing_assets: &[String]) -> String {
+    let body = strip_partial_mirror(body);
+    if missing_assets.is_empty() {
+        return body;
+    }
+
+    let count = missing_assets.len();
+    let mut footer = match i18n::lang() {
+        Lang::En => format!(
+            "{PARTIAL_MIRROR_BEGIN}\n---\n> ⚠️ **partial mirror**: {count} asset(s) not synced, please download from the source repository:\n"
+        ),
+        Lang::Zh => format!(
+            "{PARTIAL_MIRROR_BEGIN}\n---\n> ⚠️ **部分同步**: {count}个附件未同步, 请从源仓库下载:\n"
+        ),
+    };
+    for name in missing_assets {
+        footer.push_str(&format!("> - {name}\n"));
+    }
+    footer.push_str(PARTIAL_MIRROR_END);
+    format!("{body}\n\n{footer}")
+}
+
+/// 移除部分同步的标注(对比body是否一致时忽略标注)
+pub fn strip_partial_mirror(body: &str) -> String {
+    strip_section(body, PARTIAL_MIRROR_BEGIN, PARTIAL_MIRROR_END)
+}
+
+const FOOTER_BEGIN: &str = "<!-- release2gitee:footer -->";
+const FOOTER_END: &str = "<!-- /release2gitee:footer -->";
+
+/// 按--body-footer的模板在body末尾追加页脚, 已有的页脚先移除
+pub fn append_footer(cli: &Cli, tag_name: &str, body: String) -> String {
+    let Some(template) = &cli.body_footer else {
+        return body;
+    };
+    let github_url = format!("{}/releases/tag/{tag_name}", cli.source().web_url());
+    let time = schedule::format_time(provenance::now_secs(), cli.schedule_utc_offset);
+    let footer = template
+        .replace("{github_url}", &github_url)
+        .replace("{tag}", tag_name)
+        .replace("{time}", &time);
+    let body = strip_footer(&body);
+    format!("{body}\n\n{FOOTER_BEGIN}\n---\n{footer}\n{FOOTER_END}")
+}
+
+/// 移除页脚(对比body是否一致时忽略页脚, 避免同步时间变化导致反复更新)
+pub fn strip_footer(body: &str) -> String {
+    strip_section(body, FOOTER_BEGIN, FOOTER_END)
+}
+
+fn strip_section(body: &str, begin_mark: &str, end_mark: &str) -> String {
+    match (body.find(begin_mark), body.find(end_mark)) {
+        (Some(begin), Some(end)) if begin < end => {
+            let rest = &body[end + end_mark.len()..];
+            format!("{}{}", body[..begin].trim_end(), rest)
+        }
+        _ => body.to_string(),
+    }
+}
+
+/// Markdown中的引用: 链接(保持文字, 转换相对地址)、已有的网址(不处理)、#123和owner/repo#123、@user
+static REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?P<link>!?\[[^\]]*\]\()(?P<target>[^)\s]+)\)",
+        r"|https?://\S+",
+        r"|(?P<prefix>^|[\s(\[,;])(?:(?P<repo>[\w.-]+/[\w.-]+)?#(?P<number>\d+)\b",
+        r"|@(?P<user>[A-Za-z0-9](?:[A-Za-z0-9-]*[A-Za-z0-9])?)\b)",
+    ))
+    .expect("valid regex")
+});
+
+/// #123、owner/repo#123、@user和相对链接转换为源仓库的绝对地址, 代码块和行内代码中的内容保持不变
+/// - 相对路径按tag对应的文件: {web_url}/blob/{tag}/{path}, 以/开头的路径按站点根目录
+pub fn absolute_references(source: &RepoEndpoint, tag_name: &str, body: &str) -> String {
+    let web_url = source.web_url();
+    map_outside_code(body, |text, result| {
+        let replaced = REFERENCE.replace_all(text, |caps: &Captures| {
+            if let (Some(link), Some(target)) = (caps.name("link"), caps.name("target")) {
+                let target = absolute_link(source, &web_url, tag_name, target.as_str());
+                return format!("{}{target})", link.as_str());
+            }
+            let prefix = caps.name("prefix").map_or("", |prefix| prefix.as_str());
+            if let Some(number) = caps.name("number") {
+                let reference = &caps[0][prefix.len()..];
+                let repo_url = caps.name("repo").map_or(web_url.clone(), |repo| {
+                    format!("{}/{}", source.host, repo.as_str())
+                });
+                return format!(
+                    "{prefix}[{reference}]({repo_url}/issues/{})",
+                    number.as_str()
+                );
+            }
+            if let Some(user) = caps.name("user") {
+                let user = user.as_str();
+                return format!("{prefix}[@{user}]({}/{user})", source.host);
+            }
+            caps[0].to_string()
+        });
+        result.push_str(&replaced);
+    })
+}
+
+fn absolute_link(source: &RepoEndpoint, web_url: &str, tag_name: &str, target: &str) -> String {
+    // 锚点、网址、mailto:等保持不变
+    let has_scheme = target
+        .split_once(':')
+        .is_some_and(|(scheme, _)| !scheme.is_empty() && !scheme.contains('/'));
+    if target.starts_with('#') || target.starts_with("//") || has_scheme {
+        return target.to_string();
+    }
+    if target.starts_with('/') {
+        return format!("{}{target}", source.host);
+    }
+    let path = target.trim_start_matches("./");
+    format!("{web_url}/blob/{tag_name}/{path}")
+}
+
+/// 替换emoji短代码, 代码块和行内代码中的内容保持不变
+pub fn replace_emoji_shortcodes(body: &str) -> String {
+    map_outside_code(body, replace_segment)
+}
+
+/// 处理代码块和行内代码之外的文本, 代码保持不变
+fn map_outside_code(body: &str, replace: impl Fn(&str, &mut String)) -> String {
+    let mut result = String::with_capacity(body.len());
+    let mut in_fence = false;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            result.push_str(line);
+        } else if in_fence {
+            result.push_str(line);
+        } else {
+            replace_line(line, &mut result, &replace);
+        }
+    }
+    result
+}
+
+// 按反引号切分, 奇数段为行内代码
+fn replace_line(line: &str, result: &mut String, replace: &impl Fn(&str, &mut String)) {
+    for (i, segment) in line.split('`').enumerate() {
+        if i > 0 {
+            result.push('`');
+        }
+        if i % 2 == 1 {
+            result.push_str(segment);
+        } else {
+            replace(segment, result);
+        }
+    }
+}
+
+fn replace_segment(text: &str, result: &mut String) {
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find(|c: char| !is_shortcode_char(c));
+        match end {
+            Some(end) if end > 0 && after[end..].starts_with(':') => {
+                let shortcode = &after[..end];
+                match emojis::get_by_shortcode(shortcode) {
+                    Some(emoji) => {
+                        result.push_str(emoji.as_str());
+                        rest = &after[end + 1..];
+                    }
+                    None => {
+                        // 不是短代码, 结尾的冒号可能是下一个短代码的开头
+                        result.push(':');
+                        result.push_str(shortcode);
+                        rest = &after[end..];
+                    }
+                }
+            }
+            _ => {
+                result.push(':');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+}
+
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '+' || c == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_emoji_shortcodes() {
+        assert_eq!(
+            replace_emoji_shortcodes(":rocket: release :tada::bug: fixed"),
+            "🚀 release 🎉🐛 fixed"
+        );
+        assert_eq!(
+            replace_emoji_shortcodes("time 10:30:00 :not_an_emoji: :+1:"),
+            "time 10:30:00 :not_an_emoji: 👍"
+        );
+        assert_eq!(
+            replace_emoji_shortcodes("`:rocket:` :rocket:\n```\n:rocket:\n```\n:rocket:"),
+            "`:rocket:` 🚀\n```\n:rocket:\n```\n🚀"
+        );
+    }
+
+    #[test]
+    fn test_absolute_references() {
+        let source = RepoEndpoint {
+            forge: crate::model::Forge::GitHub,
+            host: "https://github.com".to_string(),
+            owner: "hepengju".to_string(),
+            repo: "redis-me".to_string(),
+            token: None,
+        };
+        let body = "- fix crash (#12) by @hepengju, see tauri-apps/tauri#345\n\
+                    - [docs](docs/usage.md) ![logo](./logo.png) [pr](/hepengju/redis-me/pull/3) [top](#top)\n\
+                    - https://github.com/hepengju/redis-me/pull/4#issue mail a@b.com `#5 @x`\n\
+                    ```\n#6 @y\n```\n";
+        assert_eq!(
+            absolute_references(&source, "v1.0.0", body),
+            "- fix crash ([#12](https://github.com/hepengju/redis-me/issues/12)) by [@hepengju](https://github.com/hepengju), \
+             see [tauri-apps/tauri#345](https://github.com/tauri-apps/tauri/issues/345)\n\
+             - [docs](https://github.com/hepengju/redis-me/blob/v1.0.0/docs/usage.md) \
+             ![logo](https://github.com/hepengju/redis-me/blob/v1.0.0/logo.png) \
+             [pr](https://github.com/hepengju/redis-me/pull/3) [top](#top)\n\
+             - https://github.com/hepengju/redis-me/pull/4#issue mail a@b.com `#5 @x`\n\
+             ```\n#6 @y\n```\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_render_template() -> AnyResult<()> {
+        use clap::Parser;
+        let path = std::env::temp_dir().join(format!(
+            "release2gitee-body-template-{}.hbs",
+            std::process::id()
+        ));
+        fs::write(
+            &path,
+            "{{body}}\n\n| 文件 | 大小 | 下载 |\n|---|---|---|\n\
+             {{#each assets}}| {{name}} | {{size}} | [国内镜像]({{url}}) |\n{{/each}}\
+             \n> {{tag}} ({{date}}) {{source_url}}",
+        )?;
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=hepengju",
+            "--github-repo=redis-me",
+            "--gitee-owner=hepengju",
+            "--gitee-repo=redis-me",
+            "--gitee-token=t",
+            &format!("--release-body-template={}", path.display()),
+        ]);
+        let release = Release {
+            id: 1,
+            tag_name: "v1.0.0".to_string(),
+            name: "v1.0.0".to_string(),
+            body: None,
+            prerelease: false,
+            draft: false,
+            target_commitish: "main".to_string(),
+            updated_at: Some("2025-12-25T08:22:42Z".to_string()),
+            created_at: None,
+            published_at: None,
+            assets: vec![crate::model::Assert {
+                id: None,
+                name: "app.zip".to_string(),
+                size: Some(3 << 20),
+                browser_download_url: String::new(),
+                digest: None,
+                label: None,
+                download_count: None,
+                content_type: None,
+                api_url: None,
+            }],
+        };
+        let body = render_template(&cli, &release, "- fix <b>bug</b>".to_string())?;
+        fs::remove_file(&path)?;
+        assert_eq!(
+            body,
+            "- fix <b>bug</b>\n\n| 文件 | 大小 | 下载 |\n|---|---|---|\n\
+             | app.zip | 3MiB | [国内镜像](https://gitee.com/hepengju/redis-me/releases/download/v1.0.0/app.zip) |\n\
+             > v1.0.0 (2025-12-25) https://github.com/hepengju/redis-me/releases/tag/v1.0.0"
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_transform_body() -> AnyResult<()> {
+        use clap::Parser;
+        let args = [
+            "release2gitee",
+            "--github-owner=hepengju",
+            "--github-repo=redis-me",
+            "--gitee-owner=hepengju",
+            "--gitee-repo=redis-me",
+            "--gitee-token=t",
+        ];
+        let cli = Cli::parse_from(args.iter().copied().chain([
+            r#"--body-transform-cmd=tr a-z A-Z; echo "($RELEASE2GITEE_REPO $RELEASE2GITEE_TAG)""#,
+        ]));
+        assert_eq!(
+            transform_body(&cli, "v1.0.0", "fix bug\n".to_string())?,
+            "FIX BUG\n(hepengju/redis-me v1.0.0)\n"
+        );
+        let marker = transform_marker(&cli, "fix bug").unwrap_or_default();
+        assert!(marker.starts_with("<!-- release2gitee:transform "));
+        assert_ne!(transform_marker(&cli, "fix bugs"), Some(marker));
+
+        let cli = Cli::parse_from(
+            args.iter()
+                .copied()
+                .chain(["--body-transform-cmd=echo failed >&2; exit 3"]),
+        );
+        let e = transform_body(&cli, "v1.0.0", "fix bug".to_string()).unwrap_err();
+        assert!(e.to_string().contains("failed"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncate_body() {
+        let url = "https://github.com/hepengju/redis-me/releases/tag/v1.0.0";
+        assert_eq!(truncate_body("short".to_string(), 100, url), "short");
+
+        let paragraph = "- change\n".repeat(100);
+        let body = format!(
+            "{paragraph}\n```\n{}```\n\n{paragraph}",
+            "code\n".repeat(500)
+        );
+        let truncated = truncate_body(body, 3000, url);
+        assert!(truncated.chars().count() <= 3000);
+        // 在代码块之前的空行处截断
+        assert!(truncated.starts_with(paragraph.trim_end()));
+        assert!(!truncated.contains("```"));
+        assert!(truncated.ends_with(url));
+
+        // 没有空行时在行尾截断, 没有换行时按字符截断
+        let truncated = truncate_body("中文说明\n".repeat(1000), 3000, url);
+        assert!(truncated.starts_with("中文说明\n") && truncated.contains("中文说明\n\n---\n"));
+        let truncated = truncate_body("中".repeat(5000), 3000, url);
+        assert!(truncated.chars().count() <= 3000);
+    }
+
+    #[test]
+    fn test_partial_mirror_body() {
+        let missing = vec!["a.zip".to_string(), "b.dmg".to_string()];
+        let body = partial_mirror_body("release notes", &missing);
+        assert!(body.starts_with("release notes\n\n<!-- release2gitee:partial-mirror -->"));
+        assert!(body.contains("2 asset(s) not synced") && body.contains("> - b.dmg\n"));
+
+        // 再次标注不会重复, 全部同步后移除
+        assert_eq!(partial_mirror_body(&body, &missing), body);
+        assert_eq!(partial_mirror_body(&body, &[]), "release notes");
+        assert_eq!(strip_partial_mirror("no footer"), "no footer");
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_append_footer() {
+        use clap::Parser;
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=hepengju",
+            "--github-repo=redis-me",
+            "--gitee-owner=hepengju",
+            "--gitee-repo=redis-me",
+            "--gitee-token=t",
+            "--body-footer=Mirrored from GitHub: {github_url}, synced at {time}",
+        ]);
+        let body = append_footer(&cli, "v1.0.0", "release notes".to_string());
+        assert!(body.starts_with(
+            "release notes\n\n<!-- release2gitee:footer -->\n---\nMirrored from GitHub: https://github.com/hepengju/redis-me/releases/tag/v1.0.0, synced at 20"
+        ));
+        // 页脚不重复追加, 对比时忽略页脚和部分同步的标注
+        assert_eq!(
+            append_footer(&cli, "v1.0.0", body.clone())
+                .matches("Mirrored")
+                .count(),
+            1
+        );
+        let body = partial_mirror_body(&body, &["a.zip".to_string()]);
+        assert_eq!(strip_footer(&strip_partial_mirror(&body)), "release notes");
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_prepend_published_date() -> AnyResult<()> {
+        use clap::Parser;
+        let args = [
+            "release2gitee",
+            "--github-owner=hepengju",
+            "--github-repo=redis-me",
+            "--gitee-owner=hepengju",
+            "--gitee-repo=redis-me",
+            "--gitee-token=t",
+        ];
+        let mut release: Release = serde_json::from_str(
+            r#"{"id":1,"tag_name":"v1.0.0","name":"v1.0.0","body":null,"prerelease":false,"target_commitish":"main",
+                "created_at":"2025-01-01T23:00:00Z","published_at":"2025-01-02T08:22:42Z"}"#,
+        )?;
+        let body = || "release notes".to_string();
+        assert_eq!(
+            prepend_published_date(&Cli::parse_from(args), &release, body()),
+            "release notes"
+        );
+        let cli = Cli::parse_from(
+            args.iter()
+                .copied()
+                .chain(["--release-body-published-date"]),
+        );
+        assert_eq!(
+            prepend_published_date(&cli, &release, body()),
+            "> 📅 Originally published 2025-01-02\n\nrelease notes"
+        );
+        // 没有发布时间时使用创建时间
+        release.published_at = None;
+        assert_eq!(release.published_date(), Some("2025-01-01"));
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+use crate::cancel::{Cancellation, CancellationToken};
+use crate::error::SyncInProgress;
+use crate::fsutil::FileWriter;
+use crate::http::Conditional;
+use crate::local::{LocalTarget, ReleaseMetadata};
+use crate::lock::SyncLock;
+use crate::model::{
+    Assert, Cli, Direction, ExportArgs, Forge, ImportArgs, Release, RepoEndpoint, SyncConfig,
+    Target,
+};
+use crate::provider::{self, ReleaseSource};
+use crate::report::SyncReport;
+use crate::{AnyResult, Error, github_app, http, provenance};
+use anyhow::{Context, anyhow, bail};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+
+/// tar包中的清单文件
+const MANIFEST_FILE: &str = "bundle.json";
+
+/// 导出清单: 导入时据此替换下载地址、区分本地状态文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub github_owner: String,
+    pub github_repo: String,
+    // 导出时间(unix秒)
+    pub exported_at: u64,
+    pub tags: Vec<String>,
+}
+
+/// 离线同步第一步: 同步到本地目录(与--target local相同的结构)后打包为tar
+/// - release body和latest.json保持原样, 导入时再替换下载地址
+pub fn export(
+    cli: &Cli,
+    config: &SyncConfig,
+    cancel: &CancellationToken,
+    args: &ExportArgs,
+) -> Result<SyncReport, Error> {
+    if cli.github_owner.is_empty() || cli.github_repo.is_empty() {
+        return Err(anyhow!("--github-owner and --github-repo are required for export").into());
+    }
+    let dir = work_dir("export");
+    let result = github_app::authenticate(cli).and_then(|cli| {
+        let cli = &export_cli(&cli, &dir);
+        export_to(cli, config, cancel, &dir, &args.output)
+    });
+    let _ = fs::remove_dir_all(&dir);
+    Ok(result?)
+}
+
+fn export_to(
+    cli: &Cli,
+    config: &SyncConfig,
+    cancel: &CancellationToken,
+    dir: &Path,
+    output: &Path,
+) -> AnyResult<SyncReport> {
+    let client = &http::init_client(cli)?;
+    let source = provider::source(cli, client, config);
+    let target = LocalTarget::open(cli.target(), dir, cli.fsync)?;
+    let cancellation = Cancellation::new(cancel, config.overall_timeout);
+    let mut report = SyncReport::default();
+    let result = crate::sync_releases(
+        cli,
+        source.as_ref(),
+        &target,
+        None,
+        &cancellation,
+        &mut report,
+    );
+    report.mirrors = source.mirror_stats();
+    result?;
+
+    let manifest = BundleManifest {
+        github_owner: cli.github_owner.clone(),
+        github_repo: cli.github_repo.clone(),
+        exported_at: provenance::now_secs(),
+        tags: target.metadata()?.into_iter().map(|r| r.tag_name).collect(),
+    };
+    fs::write(
+        dir.join(MANIFEST_FILE),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+
+    let mut writer = FileWriter::create(output, true)?;
+    {
+        let mut builder = tar::Builder::new(&mut writer);
+        builder.append_dir_all(".", dir)?;
+        builder.finish()?;
+    }
+    writer.commit()?;
+    info!(
+        "export success: {}, {} releases",
+        output.display(),
+        manifest.tags.len()
+    );
+    Ok(report)
+}
+
+/// 导出时的同步参数: 目标为本地目录, 不转换body和latest.json等附件, 不使用缓存和同步日志
+fn export_cli(cli: &Cli, dir: &Path) -> Cli {
+    let mut cli = cli.clone();
+    cli.direction = Direction::GithubToGitee;
+    cli.target = vec![Target::Local];
+    cli.output_dir = Some(dir.display().to_string());
+    cli.local_public_url = None;
+    cli.fan_out = false;
+    cli.release_body_url_replace = false;
+    cli.release_body_emoji = false;
+    cli.body_footer = None;
+    cli.release_body_absolute_links = false;
+    cli.release_body_template = None;
+    cli.body_transform_cmd = None;
+    cli.source_archives = false;
+    cli.sync_code = false;
+    cli.latest_json_url_replace = false;
+    cli.rewrite_asset_glob.clear();
+    cli.appcast_asset.clear();
+    cli.rewrite_archive.clear();
+    cli.propagate_deletes = false;
+    cli.no_cache = true;
+    cli.no_resume = true;
+    cli.command = None;
+    cli
+}
+
+/// 离线同步第二步: 解压tar包, 以其中的releases为源仓库同步到目标平台
+pub fn import(
+    cli: &Cli,
+    config: &SyncConfig,
+    cancel: &CancellationToken,
+    args: &ImportArgs,
+) -> Result<SyncReport, Error> {
+    let dir = work_dir("import");
+    let result = import_from(cli, config, cancel, &dir, &args.input);
+    let _ = fs::remove_dir_all(&dir);
+    Ok(result?)
+}
+
+fn import_from(
+    cli: &Cli,
+    config: &SyncConfig,
+    cancel: &CancellationToken,
+    dir: &Path,
+    input: &Path,
+) -> AnyResult<SyncReport> {
+    let file = File::open(input).with_context(|| format!("open {}", input.display()))?;
+    tar::Archive::new(file).unpack(dir)?;
+    let manifest: BundleManifest = serde_json::from_slice(
+        &fs::read(dir.join(MANIFEST_FILE))
+            .context("bundle.json not found, not an export bundle")?,
+    )?;
+    info!(
+        "import bundle: {}/{}, {} releases",
+        manifest.github_owner,
+        manifest.github_repo,
+        manifest.tags.len()
+    );
+
+    // github仓库以导出时为准(替换下载地址、临时目录)
+    let mut cli = cli.clone();
+    cli.github_owner = manifest.github_owner;
+    cli.github_repo = manifest.github_repo;
+    cli.direction = Direction::GithubToGitee;
+    // 导出的releases只是一部分, 不能据此删除目标平台的release
+    cli.propagate_deletes = false;
+    cli.command = None;
+    let cli = &cli;
+    cli.validate()?;
+    if cli.target.len() > 1 {
+        bail!("import only supports a single --target");
+    }
+
+    let Some(_lock) = SyncLock::acquire(cli)? else {
+        return Err(SyncInProgress {
+            gitee_repo: cli.mirror().path(),
+        }
+        .into());
+    };
+
+    let client = &http::init_client(cli)?;
+    let source = BundleSource::open(dir)?;
+    let target = provider::target(cli, client, config)?;
+    let cancellation = Cancellation::new(cancel, config.overall_timeout);
+    let mut report = SyncReport::default();
+    crate::sync_releases(
+        cli,
+        &source,
+        target.as_ref(),
+        None,
+        &cancellation,
+        &mut report,
+    )?;
+    Ok(report)
+}
+
+/// 临时工作目录: ~/tmp/release2gitee-{export|import}-{pid}
+fn work_dir(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("release2gitee-{name}-{}", process::id()))
+}
+
+/// 解压后的导出目录作为源仓库: 附件从目录中复制
+struct BundleSource {
+    local: LocalTarget,
+    endpoint: RepoEndpoint,
+}
+
+impl BundleSource {
+    fn open(dir: &Path) -> AnyResult<Self> {
+        let endpoint = RepoEndpoint {
+            forge: Forge::Local,
+            host: dir.display().to_string(),
+            owner: String::new(),
+            repo: dir.display().to_string(),
+            token: None,
+        };
+        Ok(BundleSource {
+            local: LocalTarget::open(endpoint.clone(), dir, false)?,
+            endpoint,
+        })
+    }
+}
+
+impl ReleaseSource for BundleSource {
+    fn endpoint(&self) -> &RepoEndpoint {
+        &self.endpoint
+    }
+
+    /// 按页返回metadata.json的列表
+    fn fetch_releases(
+        &self,
+        per_page: usize,
+        page: usize,
+        _: Option<&str>,
+    ) -> AnyResult<Conditional> {
+        let metadata = self.local.metadata()?;
+        let page = metadata
+            .chunks(per_page.max(1))
+            .nth(page.saturating_sub(1))
+            .unwrap_or_default();
+        Ok(Conditional::Modified {
+            text: serde_json::to_string(page)?,
+            etag: None,
+        })
+    }
+
+    fn parse_releases(&self, text: &str) -> AnyResult<Vec<Release>> {
+        let metadata: Vec<ReleaseMetadata> = serde_json::from_str(text)?;
+        Ok(metadata
+            .into_iter()
+            .map(|metadata| self.local.to_release(metadata))
+            .collect())
+    }
+
+    /// 附件的下载地址即为解压目录中的文件路径
+    fn download_asset(
+        &self,
+        asset: &Assert,
+        file_path: &Path,
+        durable: bool,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()> {
+        cancellation.check()?;
+        let mut writer = FileWriter::create(file_path, durable)?;
+        io::copy(&mut File::open(&asset.browser_download_url)?, &mut writer)?;
+        writer.commit()?;
+        provider::verify_asset_digest(asset, file_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsutil;
+    use crate::provider::ReleaseTarget;
+
+    #[test]
+    fn test_bundle_source() -> AnyResult<()> {
+        let dir = work_dir("test");
+        let source = BundleSource::open(&dir)?;
+        let release = Release {
+            id: 100,
+            tag_name: "v1.0.0".to_string(),
+            name: "v1.0.0".to_string(),
+            body: Some("notes".to_string()),
+            prerelease: false,
+            draft: false,
+            target_commitish: "main".to_string(),
+            updated_at: None,
+            created_at: None,
+            published_at: None,
+            assets: Vec::new(),
+        };
+        let created = source.local.create_release(&release)?;
+        let file = dir.join("asset");
+        fs::write(&file, "asset")?;
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        source
+            .local
+            .upload_asset(&created, "a.zip", &file, &cancellation)?;
+
+        let Conditional::Modified { text, .. } = source.fetch_releases(10, 1, None)? else {
+            bail!("releases expected");
+        };
+        let releases = source.parse_releases(&text)?;
+        assert_eq!(releases[0].tag_name, "v1.0.0");
+        let Conditional::Modified { text, .. } = source.fetch_releases(10, 2, None)? else {
+            bail!("releases expected");
+        };
+        assert!(source.parse_releases(&text)?.is_empty());
+
+        // 复制附件并校验摘要
+        let copied = dir.join("copied");
+        source.download_asset(&releases[0].assets[0], &copied, false, &cancellation)?;
+        assert_eq!(fs::read(&copied)?, b"asset".to_vec());
+        assert_eq!(fsutil::sha256_file(&copied)?, fsutil::sha256_file(&file)?);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use crate::model::Cli;
+use crate::{AnyResult, fsutil, provenance};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// github接口的ETag缓存: 记录上次同步成功时的请求地址和ETag
+/// - 下次运行时携带If-None-Match, 返回304说明github没有变化, 跳过后续所有步骤
+/// - 只在全部同步成功后写入, 保证失败或中断的同步下次一定会重新执行
+/// - 同时保存releases列表, github不可访问时(--allow-stale-source)使用
+#[derive(Debug)]
+pub struct HttpCache {
+    path: PathBuf,
+    last: Option<CacheEntry>,
+    pending: Option<CacheEntry>,
+    stale: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    // 最后一次确认与github一致的时间
+    #[serde(default)]
+    fetched_at: u64,
+}
+
+impl HttpCache {
+    pub fn open(cli: &Cli) -> Self {
+        let path = cli
+            .work_dir()
+            .join(&cli.github_repo)
+            .join(format!(".http-cache-{}.json", cli.mirror_key()));
+
+        let mut last = None;
+        if cli.no_cache {
+            let _ = fs::remove_file(&path);
+        } else if path.exists() {
+            match fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<CacheEntry>(&content)?))
+            {
+                Ok(entry) => last = Some(entry),
+                Err(e) => warn!("http cache read error, ignore: {}, {e:#}", path.display()),
+            }
+        }
+        HttpCache {
+            path,
+            last,
+            pending: None,
+            stale: false,
+        }
+    }
+
+    /// 上次同步成功时该地址的ETag
+    pub fn etag(&self, url: &str) -> Option<&str> {
+        self.last
+            .as_ref()
+            .filter(|entry| entry.url == url)
+            .and_then(|entry| entry.etag.as_deref())
+    }
+
+    /// 记录本次的响应, 同步成功后调用commit写入
+    pub fn stage(&mut self, url: &str, etag: Option<String>, text: &str) {
+        self.pending = Some(CacheEntry {
+            url: url.to_string(),
+            etag,
+            text: Some(text.to_string()),
+            fetched_at: provenance::now_secs(),
+        });
+    }
+
+    /// 返回304: 上次的响应仍然有效, 更新确认时间
+    pub fn stage_not_modified(&mut self) {
+        self.pending = self.last.clone().map(|entry| CacheEntry {
+            fetched_at: provenance::now_secs(),
+            ..entry
+        });
+    }
+
+    /// github不可访问时使用的过期响应: 不超过max_age才可用, 使用后标记为stale
+    pub fn stale_text(&mut self, url: &str, max_age: Duration) -> Option<String> {
+        let entry = self.last.as_ref().filter(|entry| entry.url == url)?;
+        let age = provenance::now_secs().saturating_sub(entry.fetched_at);
+        if age > max_age.as_secs() {
+            info!(
+                "cached source releases too old: {age}s > {}s",
+                max_age.as_secs()
+            );
+            return None;
+        }
+        let text = entry.text.clone()?;
+        self.stale = true;
+        Some(text)
+    }
+
+    /// 本次同步是否使用了过期的releases列表
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    pub fn commit(self) -> AnyResult<()> {
+        let Some(entry) = self.pending else {
+            return Ok(());
+        };
+        if self.last.as_ref() == Some(&entry) {
+            return Ok(());
+        }
+
+        fs::create_dir_all(self.path.parent().expect("cache dir"))?;
+        let content = serde_json::to_string_pretty(&entry)?;
+        fsutil::write_file(&self.path, content, false)?;
+        debug!("http cache write: {}, etag: {:?}", entry.url, entry.etag);
+        info!("http cache updated: {}", self.path.display());
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::env;
+
+    #[test]
+    fn test_http_cache_commit() -> AnyResult<()> {
+        let repo = format!("http-cache-test-{}", std::process::id());
+        let args = [
+            "release2gitee",
+            "--github-owner=o",
+            &format!("--github-repo={repo}"),
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+        ];
+        let cli = Cli::parse_from(args);
+        let url = "https://api.github.com/repos/o/r/releases?per_page=5&page=1";
+
+        // 未commit(同步失败)时不写入
+        let mut cache = HttpCache::open(&cli);
+        assert_eq!(cache.etag(url), None);
+        cache.stage(url, Some("W/\"abc\"".to_string()), "[]");
+        assert_eq!(HttpCache::open(&cli).etag(url), None);
+
+        cache.commit()?;
+        let mut cache = HttpCache::open(&cli);
+        assert_eq!(cache.etag(url), Some("W/\"abc\""));
+        assert_eq!(cache.etag("https://api.github.com/other"), None);
+
+        // --allow-stale-source: 未超过最大时长时使用缓存的响应
+        assert!(!cache.is_stale());
+        assert_eq!(
+            cache.stale_text(url, Duration::from_secs(3600)),
+            Some("[]".to_string())
+        );
+        assert!(cache.is_stale());
+        assert_eq!(
+            cache.stale_text("https://api.github.com/other", Duration::MAX),
+            None
+        );
+
+        // --no-cache: 忽略并删除缓存
+        let cli = Cli::parse_from(args.iter().chain(&["--no-cache"]));
+        assert_eq!(HttpCache::open(&cli).etag(url), None);
+        fs::remove_dir_all(env::temp_dir().join(&repo))?;
+        Ok(())
+    }
+}
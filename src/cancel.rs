@@ -0,0 +1,91 @@
+use crate::error::Cancelled;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// 取消令牌: 可以clone后在其他线程调用cancel()取消正在进行的同步
+/// - 已取消的同步在附件传输的分块之间中止, 同步日志保留已完成的部分, 下次运行从中断处继续
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// 使用调用方已有的取消标志, 设置为true时取消
+impl From<Arc<AtomicBool>> for CancellationToken {
+    fn from(cancelled: Arc<AtomicBool>) -> Self {
+        CancellationToken { cancelled }
+    }
+}
+
+/// 同步过程中的取消检查: 取消令牌 + 总超时时间
+/// 在release之间、附件分块下载上传之间检查
+#[derive(Debug, Clone)]
+pub struct Cancellation {
+    token: CancellationToken,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl Cancellation {
+    pub fn new(token: &CancellationToken, timeout: Option<Duration>) -> Self {
+        Cancellation {
+            token: token.clone(),
+            timeout,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.token.is_cancelled() {
+            return Err(Cancelled { timeout: None });
+        }
+        if let Some(deadline) = self.deadline
+            && Instant::now() >= deadline
+        {
+            return Err(Cancelled {
+                timeout: self.timeout,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_check() {
+        let token = CancellationToken::new();
+        let cancellation = Cancellation::new(&token, None);
+        assert!(cancellation.check().is_ok());
+
+        // 其他线程取消
+        let other = token.clone();
+        std::thread::spawn(move || other.cancel()).join().unwrap();
+        assert!(cancellation.check().is_err_and(|e| e.timeout.is_none()));
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let cancellation = Cancellation::new(&CancellationToken::from(flag.clone()), None);
+        assert!(cancellation.check().is_ok());
+        flag.store(true, Ordering::SeqCst);
+        assert!(cancellation.check().is_err());
+
+        let cancellation = Cancellation::new(&CancellationToken::new(), Some(Duration::ZERO));
+        assert!(cancellation.check().is_err_and(|e| e.timeout.is_some()));
+    }
+}
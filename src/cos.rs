@@ -0,0 +1,229 @@
+use crate::object::{self, ObjectStore};
+use crate::s3::{hex, uri_encode};
+use crate::trace::SendTraced;
+use crate::{AnyResult, http, provenance};
+use anyhow::{Context, bail};
+use hmac::{Hmac, Mac};
+use log::debug;
+use reqwest::blocking::{Body, Client, Response};
+use reqwest::{Method, StatusCode};
+use sha1::{Digest, Sha1};
+use std::env;
+use std::fs::File;
+use std::path::Path;
+
+/// 签名的有效期(秒): 大文件上传时间较长
+const SIGN_EXPIRES: u64 = 3600;
+
+/// 腾讯云COS bucket(可带前缀), 使用COS原生的签名(q-sign-algorithm=sha1)
+/// - 访问密钥读取COS_SECRET_ID, COS_SECRET_KEY环境变量
+#[derive(Debug)]
+pub struct CosBucket {
+    client: Client,
+    // 如: examplebucket-1250000000.cos.ap-guangzhou.myqcloud.com
+    host: String,
+    prefix: String,
+    secret_id: String,
+    secret_key: String,
+}
+
+impl CosBucket {
+    /// 解析 cos://bucket-appid/prefix
+    pub fn open(client: &Client, region: &str, location: &str) -> AnyResult<Self> {
+        let (bucket, prefix) = parse_location(location)?;
+        Ok(CosBucket {
+            client: client.clone(),
+            host: bucket_host(bucket, region),
+            prefix: prefix.to_string(),
+            secret_id: env::var("COS_SECRET_ID").context("COS_SECRET_ID is required")?,
+            secret_key: env::var("COS_SECRET_KEY").context("COS_SECRET_KEY is required")?,
+        })
+    }
+
+    fn send(
+        &self,
+        method: Method,
+        key: &str,
+        content_type: &str,
+        body: Option<Body>,
+    ) -> AnyResult<Response> {
+        let key = match self.prefix.as_str() {
+            "" => key.to_string(),
+            prefix => format!("{prefix}/{key}"),
+        };
+        let encoded_key = key.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+        let url = format!("https://{}/{encoded_key}", self.host);
+
+        let now = provenance::now_secs();
+        let key_time = format!("{now};{}", now + SIGN_EXPIRES);
+        let authorization = authorization(
+            &self.secret_id,
+            &self.secret_key,
+            &key_time,
+            method.as_str(),
+            &format!("/{key}"),
+            &[("host", &self.host)],
+        );
+        debug!("{method}: {url}");
+        let mut builder = self
+            .client
+            .request(method, url)
+            .header("Authorization", authorization);
+        if !content_type.is_empty() {
+            builder = builder.header("Content-Type", content_type);
+        }
+        if let Some(body) = body {
+            builder = builder.body(body);
+        }
+        Ok(builder.send_traced()?)
+    }
+}
+
+impl ObjectStore for CosBucket {
+    fn read(&self, key: &str) -> AnyResult<Option<Vec<u8>>> {
+        let res = self.send(Method::GET, key, "", None)?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(http::check_status(res)?.bytes()?.to_vec()))
+    }
+
+    fn write(&self, key: &str, content: Vec<u8>) -> AnyResult<()> {
+        let content_type = object::content_type(key);
+        let res = self.send(Method::PUT, key, content_type, Some(Body::from(content)))?;
+        http::check_status(res)?;
+        Ok(())
+    }
+
+    fn upload(&self, key: &str, path: &Path) -> AnyResult<()> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let content_type = object::content_type(key);
+        let res = self.send(
+            Method::PUT,
+            key,
+            content_type,
+            Some(Body::sized(file, size)),
+        )?;
+        http::check_status(res)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> AnyResult<()> {
+        let res = self.send(Method::DELETE, key, "", None)?;
+        if res.status() != StatusCode::NOT_FOUND {
+            http::check_status(res)?;
+        }
+        Ok(())
+    }
+}
+
+/// cos://bucket-appid/prefix => (bucket-appid, prefix)
+pub fn parse_location(location: &str) -> AnyResult<(&str, &str)> {
+    let Some(rest) = location.strip_prefix("cos://") else {
+        bail!("invalid cos location, cos://bucket-appid/prefix expected: {location}");
+    };
+    let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+    // bucket名称必须带APPID后缀, 如: examplebucket-1250000000
+    if !bucket
+        .rsplit_once('-')
+        .is_some_and(|(name, appid)| !name.is_empty() && appid.chars().all(|c| c.is_ascii_digit()))
+    {
+        bail!("invalid cos location, bucket-appid is required: {location}");
+    }
+    Ok((bucket, prefix.trim_matches('/')))
+}
+
+/// 访问域名: {bucket-appid}.cos.{region}.myqcloud.com
+pub fn bucket_host(bucket: &str, region: &str) -> String {
+    format!("{bucket}.cos.{region}.myqcloud.com")
+}
+
+/// COS签名, 返回Authorization请求头
+/// - SignKey = hex(hmac-sha1(SecretKey, KeyTime))
+/// - StringToSign = sha1\nKeyTime\nhex(sha1(HttpString))\n
+/// - Signature = hex(hmac-sha1(SignKey, StringToSign))
+fn authorization(
+    secret_id: &str,
+    secret_key: &str,
+    key_time: &str,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+) -> String {
+    let mut headers = headers
+        .iter()
+        .map(|(name, value)| (uri_encode(&name.to_lowercase()), uri_encode(value)))
+        .collect::<Vec<_>>();
+    headers.sort();
+    let header_list = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let http_headers = headers
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let http_string = format!("{}\n{path}\n\n{http_headers}\n", method.to_lowercase());
+    let string_to_sign = format!(
+        "sha1\n{key_time}\n{}\n",
+        hex(&Sha1::digest(http_string.as_bytes()))
+    );
+    let sign_key = hex(&hmac_sha1(secret_key.as_bytes(), key_time));
+    let signature = hex(&hmac_sha1(sign_key.as_bytes(), &string_to_sign));
+    format!(
+        "q-sign-algorithm=sha1&q-ak={secret_id}&q-sign-time={key_time}&q-key-time={key_time}&q-header-list={header_list}&q-url-param-list=&q-signature={signature}"
+    )
+}
+
+fn hmac_sha1(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorization() -> AnyResult<()> {
+        // COS文档中的签名示例
+        let authorization = authorization(
+            "AKIDQjz3ltompVjBni5LitkWHFlFpwkn9U5q",
+            "BQYIM75p8x0iWVFSIgqEKwFprpRSVHlz",
+            "1557989151;1557996351",
+            "PUT",
+            "/exampleobject(腾讯云)",
+            &[
+                ("Date", "Thu, 16 May 2019 06:45:51 GMT"),
+                (
+                    "Host",
+                    "examplebucket-1250000000.cos.ap-beijing.myqcloud.com",
+                ),
+                ("Content-Type", "text/plain"),
+                ("Content-Length", "13"),
+                ("Content-MD5", "mQ/fVh815F3k6TAUm8m0eg=="),
+                ("x-cos-acl", "private"),
+                ("x-cos-grant-read", "uin=\"100000000011\""),
+            ],
+        );
+        assert!(authorization.ends_with(
+            "q-header-list=content-length;content-md5;content-type;date;host;x-cos-acl;x-cos-grant-read&q-url-param-list=&q-signature=3b8851a11a569213c17ba8fa7dcf2abec6935172"
+        ));
+
+        assert_eq!(
+            parse_location("cos://releases-1250000000/mirror/")?,
+            ("releases-1250000000", "mirror")
+        );
+        assert!(parse_location("cos://releases/mirror").is_err());
+        assert_eq!(
+            bucket_host("releases-1250000000", "ap-guangzhou"),
+            "releases-1250000000.cos.ap-guangzhou.myqcloud.com"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,425 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// 接口请求失败
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: u16,
+    pub url: String,
+    pub message: String,
+    /// 速率限制耗尽(429, 或剩余次数为0)
+    pub rate_limited: bool,
+    /// 速率限制的重置时间(unix秒, x-ratelimit-reset)
+    pub rate_limit_reset: Option<u64>,
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "response err: {} {}, {}",
+            self.status, self.url, self.message
+        )
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ApiError {
+    pub fn is_auth(&self) -> bool {
+        !self.rate_limited && (self.status == 401 || self.status == 403)
+    }
+}
+
+/// 附件下载或上传失败(作为context附加到错误上)
+#[derive(Debug, Clone)]
+pub struct AssetError {
+    pub tag_name: String,
+    pub asset_name: String,
+}
+
+impl Display for AssetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "asset sync error: {}/{}", self.tag_name, self.asset_name)
+    }
+}
+
+/// 同一个gitee仓库已有其他同步进程在运行
+#[derive(Debug, Clone)]
+pub struct SyncInProgress {
+    pub gitee_repo: String,
+}
+
+impl Display for SyncInProgress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "another sync is in progress: {}", self.gitee_repo)
+    }
+}
+
+impl std::error::Error for SyncInProgress {}
+
+/// 严格模式(--strict)下镜像与源仓库不一致: 跳过或重命名的附件、部分同步等
+#[derive(Debug, Clone)]
+pub struct Diverged {
+    pub tag_name: String,
+    pub reasons: Vec<String>,
+}
+
+impl Display for Diverged {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "mirror diverged from source: {}, {}",
+            self.tag_name,
+            self.reasons.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for Diverged {}
+
+/// 同步被取消或超过总超时时间
+#[derive(Debug, Clone)]
+pub struct Cancelled {
+    /// 超时取消时为总超时时间, 主动取消时为None
+    pub timeout: Option<Duration>,
+}
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.timeout {
+            Some(timeout) => write!(f, "sync timed out after {}s", timeout.as_secs()),
+            None => write!(f, "sync cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// 库接口(如sync_github_releases_to_gitee)的错误: 按失败类别区分, 便于调用方匹配处理
+/// - 内部使用anyhow, 在公开接口处按错误链分类, 原始错误(包括上下文)作为source保留
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// 同一个目标仓库已有其他同步进程在运行
+    #[error("another sync is in progress: {repo}")]
+    AlreadyRunning {
+        repo: String,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 同步被取消或超过总超时时间(timeout)
+    #[error("sync cancelled")]
+    Cancelled {
+        timeout: Option<Duration>,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 速率限制耗尽, reset为重置时间(unix秒)
+    #[error("rate limited: {url}")]
+    RateLimited {
+        url: String,
+        reset: Option<u64>,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 认证失败(401/403): token无效或没有权限
+    #[error("authentication failed: {status} {url}")]
+    Auth {
+        status: u16,
+        url: String,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 严格模式(--strict)下镜像与源仓库不一致
+    #[error("mirror diverged from source: {tag_name}")]
+    Diverged {
+        tag_name: String,
+        reasons: Vec<String>,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 附件超过目标平台的大小限制(413)
+    #[error("asset too large: {tag_name}/{asset_name}")]
+    AssetTooLarge {
+        tag_name: String,
+        asset_name: String,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 附件下载或上传失败
+    #[error("asset sync error: {tag_name}/{asset_name}")]
+    Asset {
+        tag_name: String,
+        asset_name: String,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 接口请求失败(gitee、github等)
+    #[error("api error: {status} {url}, {message}")]
+    Api {
+        status: u16,
+        url: String,
+        message: String,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 读写本地文件失败
+    #[error("io error: {kind}")]
+    Io {
+        kind: std::io::ErrorKind,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// 其他错误(参数错误、解析失败等)
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl From<anyhow::Error> for Error {
+    /// 按错误链分类, 优先级与退出码相同
+    fn from(source: anyhow::Error) -> Self {
+        if let Some(e) = find::<SyncInProgress>(&source) {
+            let repo = e.gitee_repo.clone();
+            return Error::AlreadyRunning { repo, source };
+        }
+        if let Some(e) = find::<Cancelled>(&source) {
+            let timeout = e.timeout;
+            return Error::Cancelled { timeout, source };
+        }
+        let api_error = find::<ApiError>(&source).cloned();
+        if let Some(e) = &api_error {
+            if e.rate_limited {
+                let (url, reset) = (e.url.clone(), e.rate_limit_reset);
+                return Error::RateLimited { url, reset, source };
+            }
+            if e.is_auth() {
+                let (status, url) = (e.status, e.url.clone());
+                return Error::Auth {
+                    status,
+                    url,
+                    source,
+                };
+            }
+        }
+        if let Some(e) = find::<Diverged>(&source) {
+            let (tag_name, reasons) = (e.tag_name.clone(), e.reasons.clone());
+            return Error::Diverged {
+                tag_name,
+                reasons,
+                source,
+            };
+        }
+        if let Some(e) = source.downcast_ref::<AssetError>() {
+            let (tag_name, asset_name) = (e.tag_name.clone(), e.asset_name.clone());
+            if api_error.as_ref().is_some_and(|e| e.status == 413) {
+                return Error::AssetTooLarge {
+                    tag_name,
+                    asset_name,
+                    source,
+                };
+            }
+            return Error::Asset {
+                tag_name,
+                asset_name,
+                source,
+            };
+        }
+        if let Some(e) = api_error {
+            let ApiError {
+                status,
+                url,
+                message,
+                ..
+            } = e;
+            return Error::Api {
+                status,
+                url,
+                message,
+                source,
+            };
+        }
+        if let Some(kind) = find::<std::io::Error>(&source).map(std::io::Error::kind) {
+            return Error::Io { kind, source };
+        }
+        Error::Other(source)
+    }
+}
+
+impl Error {
+    /// 原始错误(包括上下文): 命令行程序在边界处继续使用anyhow
+    pub fn into_anyhow(self) -> anyhow::Error {
+        match self {
+            Error::AlreadyRunning { source, .. }
+            | Error::Cancelled { source, .. }
+            | Error::RateLimited { source, .. }
+            | Error::Auth { source, .. }
+            | Error::Diverged { source, .. }
+            | Error::AssetTooLarge { source, .. }
+            | Error::Asset { source, .. }
+            | Error::Api { source, .. }
+            | Error::Io { source, .. }
+            | Error::Other(source) => source,
+        }
+    }
+}
+
+/// 错误链中第一个指定类型的错误
+fn find<T: std::error::Error + 'static>(e: &anyhow::Error) -> Option<&T> {
+    e.chain().find_map(|cause| cause.downcast_ref::<T>())
+}
+
+/// 进程退出码: 便于定时任务根据失败类别区分告警
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Success = 0,
+    Failure = 1,
+    Auth = 3,
+    RateLimited = 4,
+    PartialAssets = 5,
+    NothingToSync = 6,
+    AlreadyRunning = 7,
+    Cancelled = 8,
+    Diverged = 9,
+}
+
+impl ExitStatus {
+    /// 根据错误链判断失败类别
+    pub fn from_error(e: &anyhow::Error) -> Self {
+        if e.downcast_ref::<SyncInProgress>().is_some() {
+            return ExitStatus::AlreadyRunning;
+        }
+        if e.chain().any(|cause| cause.is::<Cancelled>()) {
+            return ExitStatus::Cancelled;
+        }
+
+        let api_error = e.chain().find_map(|cause| cause.downcast_ref::<ApiError>());
+        if let Some(api_error) = api_error {
+            if api_error.rate_limited {
+                return ExitStatus::RateLimited;
+            }
+            if api_error.is_auth() {
+                return ExitStatus::Auth;
+            }
+        }
+
+        if e.chain().any(|cause| cause.is::<Diverged>()) {
+            return ExitStatus::Diverged;
+        }
+        if e.downcast_ref::<AssetError>().is_some() {
+            return ExitStatus::PartialAssets;
+        }
+        ExitStatus::Failure
+    }
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    fn api_error(status: u16, rate_limited: bool) -> anyhow::Error {
+        anyhow::Error::new(ApiError {
+            status,
+            url: "https://gitee.com/api/v5/repos".to_string(),
+            message: String::new(),
+            rate_limited,
+            rate_limit_reset: None,
+        })
+    }
+
+    #[test]
+    fn test_exit_status_from_error() {
+        assert_eq!(
+            ExitStatus::from_error(&api_error(401, false)),
+            ExitStatus::Auth
+        );
+        assert_eq!(
+            ExitStatus::from_error(&api_error(403, true)),
+            ExitStatus::RateLimited
+        );
+
+        let asset_error = |e: anyhow::Error| {
+            Err::<(), _>(e)
+                .context(AssetError {
+                    tag_name: "v0.9.4".to_string(),
+                    asset_name: "a.zip".to_string(),
+                })
+                .unwrap_err()
+        };
+        assert_eq!(
+            ExitStatus::from_error(&asset_error(api_error(502, false))),
+            ExitStatus::PartialAssets
+        );
+        assert_eq!(
+            ExitStatus::from_error(&asset_error(api_error(401, false))),
+            ExitStatus::Auth
+        );
+        assert_eq!(
+            ExitStatus::from_error(&asset_error(Cancelled { timeout: None }.into())),
+            ExitStatus::Cancelled
+        );
+        assert_eq!(
+            ExitStatus::from_error(&anyhow::Error::new(Diverged {
+                tag_name: "v0.9.4".to_string(),
+                reasons: vec!["asset conflict skipped: a.zip".to_string()],
+            })),
+            ExitStatus::Diverged
+        );
+        assert_eq!(
+            ExitStatus::from_error(&anyhow::anyhow!("io error")),
+            ExitStatus::Failure
+        );
+    }
+
+    #[test]
+    fn test_error_from_anyhow() {
+        let asset_error = |e: anyhow::Error| {
+            Err::<(), _>(e)
+                .context(AssetError {
+                    tag_name: "v0.9.4".to_string(),
+                    asset_name: "a.zip".to_string(),
+                })
+                .unwrap_err()
+        };
+        assert!(matches!(
+            Error::from(api_error(403, true)),
+            Error::RateLimited { .. }
+        ));
+        assert!(matches!(
+            Error::from(api_error(401, false).context("releases fetch error")),
+            Error::Auth { status: 401, .. }
+        ));
+        assert!(matches!(
+            Error::from(asset_error(api_error(413, false))),
+            Error::AssetTooLarge { asset_name, .. } if asset_name == "a.zip"
+        ));
+        assert!(matches!(
+            Error::from(asset_error(api_error(502, false))),
+            Error::Asset { .. }
+        ));
+        assert!(matches!(
+            Error::from(api_error(404, false)),
+            Error::Api { status: 404, .. }
+        ));
+        let io_error = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(matches!(
+            Error::from(io_error.context("open journal error")),
+            Error::Io {
+                kind: std::io::ErrorKind::NotFound,
+                ..
+            }
+        ));
+        // 原始错误(包括上下文)保留
+        let e = Error::from(anyhow::anyhow!("invalid args").context("validate error"));
+        assert!(matches!(e, Error::Other(_)));
+        assert_eq!(
+            format!("{:#}", e.into_anyhow()),
+            "validate error: invalid args"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! 同步事件: 库的调用方通过SyncOptionsBuilder::events传入发送端, 实时接收本次同步中的事件(如GUI展示进度、自定义日志), 无需解析日志
+//! - 使用有界通道(mpsc::sync_channel), 接收端处理不及时(通道已满)时丢弃事件, 不阻塞同步
+//! - 同步结束后释放发送端
+
+use crate::report::ReleaseAction;
+use serde::Serialize;
+use std::sync::mpsc::SyncSender;
+
+/// 同步事件, target为目标仓库(如: gitee:hepengju/redis-me)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SyncEvent {
+    ReleaseStarted {
+        target: String,
+        tag_name: String,
+    },
+    ReleaseSynced {
+        target: String,
+        tag_name: String,
+        action: ReleaseAction,
+        uploaded_assets: usize,
+    },
+    /// 未同步的release(不在同步计划中、上次运行已完成、超出目标仓库容量)
+    ReleaseSkipped {
+        target: String,
+        tag_name: String,
+        reason: String,
+    },
+    ReleaseFailed {
+        target: String,
+        tag_name: String,
+        error: String,
+    },
+    ReleaseCleaned {
+        target: String,
+        tag_name: String,
+    },
+    ReleaseDeleted {
+        target: String,
+        tag_name: String,
+    },
+    /// 从源仓库下载附件(不包括从缓存中取出的附件)
+    AssetDownloaded {
+        target: String,
+        tag_name: String,
+        asset_name: String,
+        size: u64,
+    },
+    AssetUploaded {
+        target: String,
+        tag_name: String,
+        asset_name: String,
+        size: u64,
+    },
+    Warning {
+        target: String,
+        message: String,
+    },
+    /// 非release维度的失败(如查询releases信息失败)
+    Error {
+        target: String,
+        error: String,
+    },
+}
+
+/// 一次同步的事件发送端(由SyncOptionsBuilder::events设置), 未设置时不发送
+#[derive(Debug, Clone, Default)]
+pub struct Events(Option<SyncSender<SyncEvent>>);
+
+impl Events {
+    pub fn new(sender: Option<SyncSender<SyncEvent>>) -> Self {
+        Events(sender)
+    }
+
+    /// 通道已满或接收端已释放时丢弃
+    pub fn emit(&self, event: SyncEvent) {
+        if let Some(sender) = &self.0 {
+            let _ = sender.try_send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_emit() {
+        let event = |tag_name: &str| SyncEvent::ReleaseStarted {
+            target: "gitee:o/r".to_string(),
+            tag_name: tag_name.to_string(),
+        };
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let events = Events::new(Some(sender));
+        events.emit(event("v1.0.0"));
+        // 通道已满: 丢弃, 不阻塞
+        events.emit(event("v2.0.0"));
+        drop(events);
+        let received = receiver.iter().collect::<Vec<_>>();
+        assert_eq!(received, [event("v1.0.0")]);
+        assert_eq!(
+            serde_json::to_string(&received[0]).unwrap(),
+            r#"{"event":"release_started","target":"gitee:o/r","tag_name":"v1.0.0"}"#
+        );
+
+        // 接收端已释放
+        let (sender, receiver) = mpsc::sync_channel(1);
+        drop(receiver);
+        Events::new(Some(sender)).emit(event("v1.0.0"));
+        Events::default().emit(event("v1.0.0"));
+    }
+}
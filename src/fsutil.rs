@@ -0,0 +1,444 @@
+use crate::AnyResult;
+use log::{debug, warn};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+// 重命名遇到EBUSY时的重试次数
+const RENAME_RETRY_TIMES: u32 = 5;
+
+// 网络文件系统类型(/proc/self/mounts中的fstype)
+const REMOTE_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb",
+    "smb2",
+    "smb3",
+    "smbfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "fuse.sshfs",
+];
+
+/// 判断目录是否位于网络文件系统(NFS/SMB等)上
+pub fn is_remote_fs(path: &Path) -> bool {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    remote_fs_type(&path).is_some()
+}
+
+#[cfg(target_os = "linux")]
+fn remote_fs_type(path: &Path) -> Option<String> {
+    let mounts = fs::read_to_string("/proc/self/mounts").ok()?;
+    find_mount_fs_type(&mounts, path).filter(|fs_type| is_remote_fs_type(fs_type))
+}
+
+#[cfg(windows)]
+fn remote_fs_type(path: &Path) -> Option<String> {
+    // UNC路径: \\server\share 或 \\?\UNC\server\share
+    let path = path.to_string_lossy();
+    if path.starts_with(r"\\?\UNC\") || (path.starts_with(r"\\") && !path.starts_with(r"\\?\")) {
+        Some("smb".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn remote_fs_type(_path: &Path) -> Option<String> {
+    None
+}
+
+fn is_remote_fs_type(fs_type: &str) -> bool {
+    REMOTE_FS_TYPES.contains(&fs_type) || fs_type.starts_with("nfs")
+}
+
+/// 在mounts内容中找到路径所属(最长前缀匹配)挂载点的文件系统类型
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn find_mount_fs_type(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((mount_point, fs_type.to_string()))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// 文件写入器
+/// - 普通模式: 直接写入目标文件
+/// - 安全模式(网络文件系统或--fsync): 先写临时文件, fsync后再重命名为目标文件, 避免中断导致缓存文件损坏
+pub struct FileWriter {
+    file: File,
+    path: PathBuf,
+    tmp_path: Option<PathBuf>,
+}
+
+impl FileWriter {
+    pub fn create(path: &Path, durable: bool) -> AnyResult<Self> {
+        if durable {
+            let tmp_path = part_path(path);
+            let file = File::create(&tmp_path)?;
+            Ok(FileWriter {
+                file,
+                path: path.to_path_buf(),
+                tmp_path: Some(tmp_path),
+            })
+        } else {
+            let file = File::create(path)?;
+            Ok(FileWriter {
+                file,
+                path: path.to_path_buf(),
+                tmp_path: None,
+            })
+        }
+    }
+
+    /// 按附件大小预分配磁盘空间: 减少碎片, 空间不足时立即失败(而不是下载到一半)
+    /// - 不改变文件大小(中断后的文件大小与已写入的内容一致, 不会误判为下载完成)
+    /// - 文件系统不支持预分配时忽略
+    pub fn preallocate(&self, size: u64) -> AnyResult<()> {
+        if size == 0 {
+            return Ok(());
+        }
+        match preallocate(&self.file, size) {
+            Ok(true) => debug!("file preallocated: {}, {size}", self.path.display()),
+            Ok(false) => debug!("file preallocate unsupported: {}", self.path.display()),
+            Err(e) => return Err(e.into()),
+        }
+        Ok(())
+    }
+
+    /// 清空已写入的内容, 从头重新写入(服务端不支持断点续传时)
+    pub fn restart(&mut self) -> AnyResult<()> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+
+    /// 以独立的文件句柄从offset开始写入(分段并行下载时各分段互不影响)
+    pub fn open_at(&self, offset: u64) -> AnyResult<File> {
+        let path = self.tmp_path.as_deref().unwrap_or(&self.path);
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        Ok(file)
+    }
+
+    /// 写入完成: 安全模式下fsync并重命名
+    pub fn commit(mut self) -> AnyResult<()> {
+        self.file.flush()?;
+        let Some(tmp_path) = self.tmp_path.take() else {
+            return Ok(());
+        };
+
+        self.file.sync_all()?;
+        rename_with_retry(&tmp_path, &self.path)?;
+        sync_parent_dir(&self.path);
+        debug!("file write committed: {}", self.path.display());
+        Ok(())
+    }
+}
+
+impl Write for FileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for FileWriter {
+    fn drop(&mut self) {
+        // 未提交的临时文件直接删除
+        if let Some(tmp_path) = self.tmp_path.take() {
+            let _ = fs::remove_file(tmp_path);
+        }
+    }
+}
+
+/// 预分配磁盘空间(不改变文件大小), 返回是否支持
+#[cfg(target_os = "linux")]
+fn preallocate(file: &File, size: u64) -> io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    let Ok(len) = libc::off_t::try_from(size) else {
+        return Ok(false);
+    };
+    // FALLOC_FL_KEEP_SIZE: 只分配空间, 文件大小不变
+    let ret = unsafe { libc::fallocate(file.as_raw_fd(), libc::FALLOC_FL_KEEP_SIZE, 0, len) };
+    if ret == 0 {
+        return Ok(true);
+    }
+    let e = io::Error::last_os_error();
+    match e.raw_os_error() {
+        // 文件系统不支持(如部分网络文件系统、tmpfs旧版本)
+        Some(libc::EOPNOTSUPP | libc::ENOSYS | libc::EINVAL) => Ok(false),
+        _ => Err(e),
+    }
+}
+
+/// 预分配磁盘空间(不改变文件大小), 返回是否支持
+#[cfg(windows)]
+fn preallocate(file: &File, size: u64) -> io::Result<bool> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{
+        ERROR_INVALID_FUNCTION, ERROR_INVALID_PARAMETER, ERROR_NOT_SUPPORTED,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_ALLOCATION_INFO, FileAllocationInfo, SetFileInformationByHandle,
+    };
+
+    let Ok(allocation_size) = i64::try_from(size) else {
+        return Ok(false);
+    };
+    // 设置分配大小, 文件结尾(EOF)不变
+    let info = FILE_ALLOCATION_INFO {
+        AllocationSize: allocation_size,
+    };
+    let ok = unsafe {
+        SetFileInformationByHandle(
+            file.as_raw_handle(),
+            FileAllocationInfo,
+            &info as *const FILE_ALLOCATION_INFO as *const std::ffi::c_void,
+            size_of::<FILE_ALLOCATION_INFO>() as u32,
+        )
+    };
+    if ok != 0 {
+        return Ok(true);
+    }
+    let e = io::Error::last_os_error();
+    match e.raw_os_error().map(|code| code as u32) {
+        // 文件系统不支持(如FAT/exFAT、部分SMB共享)
+        Some(ERROR_INVALID_PARAMETER | ERROR_INVALID_FUNCTION | ERROR_NOT_SUPPORTED) => Ok(false),
+        _ => Err(e),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn preallocate(_file: &File, _size: u64) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// 写入整个文件内容
+pub fn write_file(path: &Path, content: impl AsRef<[u8]>, durable: bool) -> AnyResult<()> {
+    let mut writer = FileWriter::create(path, durable)?;
+    writer.write_all(content.as_ref())?;
+    writer.commit()
+}
+
+/// 删除dir下包含marker文件且超过max_age没有修改的子目录, 返回删除的数量(dir不存在时为0)
+/// - 没有marker的目录不是本工具创建的(如同名仓库的其他目录), 不删除
+pub fn remove_stale_dirs(dir: &Path, marker: &str, max_age: Duration) -> AnyResult<usize> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut count = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_dir()
+            || !entry.path().join(marker).is_file()
+            || metadata.modified()?.elapsed().unwrap_or_default() < max_age
+        {
+            continue;
+        }
+        fs::remove_dir_all(entry.path())?;
+        debug!("stale dir removed: {}", entry.path().display());
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// 计算文件的sha256(小写十六进制)
+pub fn sha256_file(path: &Path) -> AnyResult<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// 校验文件摘要(github附件的digest格式: sha256:xxx), 不支持的算法视为通过
+pub fn verify_digest(path: &Path, digest: &str) -> AnyResult<bool> {
+    match digest.split_once(':') {
+        Some(("sha256", expected)) => Ok(sha256_file(path)?.eq_ignore_ascii_case(expected)),
+        _ => {
+            debug!("unsupported digest, skip verify: {digest}");
+            Ok(true)
+        }
+    }
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+fn rename_with_retry(from: &Path, to: &Path) -> AnyResult<()> {
+    let mut attempt = 1;
+    loop {
+        match fs::rename(from, to) {
+            Ok(()) => return Ok(()),
+            Err(e) if is_busy(&e) && attempt < RENAME_RETRY_TIMES => {
+                warn!(
+                    "rename busy, retry {attempt}/{RENAME_RETRY_TIMES}: {}",
+                    to.display()
+                );
+                thread::sleep(Duration::from_millis(200 * attempt as u64));
+                attempt += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn is_busy(e: &io::Error) -> bool {
+    if e.kind() == io::ErrorKind::ResourceBusy {
+        return true;
+    }
+    // Windows: ERROR_SHARING_VIOLATION(32), ERROR_LOCK_VIOLATION(33)
+    cfg!(windows) && matches!(e.raw_os_error(), Some(32) | Some(33))
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) {
+    if let Some(parent) = path.parent()
+        && let Ok(dir) = File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_find_mount_fs_type() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+                      server:/export /mnt/build\\040farm nfs4 rw 0 0\n\
+                      //nas/share /mnt/share cifs rw 0 0\n";
+        let fs_type = |path: &str| find_mount_fs_type(mounts, Path::new(path));
+        assert_eq!(fs_type("/tmp/a").as_deref(), Some("ext4"));
+        assert_eq!(fs_type("/mnt/build farm/a").as_deref(), Some("nfs4"));
+        assert_eq!(fs_type("/mnt/share/x").as_deref(), Some("cifs"));
+        assert!(is_remote_fs_type("nfs4") && is_remote_fs_type("cifs"));
+        assert!(!is_remote_fs_type("ext4"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_preallocate_linux() -> AnyResult<()> {
+        use std::os::unix::fs::MetadataExt;
+
+        let path = env::temp_dir().join(format!("preallocate-test-{}", std::process::id()));
+        let mut writer = FileWriter::create(&path, false)?;
+        let supported = preallocate(&writer.file, 1 << 20)?;
+        writer.preallocate(1 << 20)?;
+        // 文件大小不变, 支持时已分配磁盘块(512字节)
+        let metadata = fs::metadata(&path)?;
+        assert_eq!(metadata.len(), 0);
+        if supported {
+            assert!(metadata.blocks() * 512 >= 1 << 20);
+        }
+        writer.write_all(b"asset")?;
+        writer.commit()?;
+        assert_eq!(fs::read(&path)?, b"asset".to_vec());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_preallocate_windows() -> AnyResult<()> {
+        let path = env::temp_dir().join(format!("preallocate-test-{}", std::process::id()));
+        let mut writer = FileWriter::create(&path, false)?;
+        // FAT/exFAT等不支持时返回false
+        preallocate(&writer.file, 1 << 20)?;
+        // 分配大小不影响文件结尾
+        assert_eq!(fs::metadata(&path)?.len(), 0);
+        writer.write_all(b"asset")?;
+        writer.commit()?;
+        assert_eq!(fs::read(&path)?, b"asset".to_vec());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_file_durable() -> AnyResult<()> {
+        let dir = env::temp_dir().join(format!("release2gitee-fsutil-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("latest.json");
+        write_file(&path, "{}", true)?;
+        assert_eq!(fs::read_to_string(&path)?, "{}");
+        assert!(!part_path(&path).exists());
+        assert!(verify_digest(
+            &path,
+            "sha256:44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        )?);
+        assert!(!verify_digest(&path, "sha256:00")?);
+
+        // 未提交时不覆盖目标文件
+        let mut writer = FileWriter::create(&path, true)?;
+        writer.write_all(b"broken")?;
+        drop(writer);
+        assert_eq!(fs::read_to_string(&path)?, "{}");
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_stale_dirs() -> AnyResult<()> {
+        let dir = env::temp_dir().join(format!("release2gitee-stale-{}", std::process::id()));
+        assert_eq!(remove_stale_dirs(&dir, ".marker", Duration::ZERO)?, 0);
+        for tag in ["v1.0.0", "v1.1.0"] {
+            fs::create_dir_all(dir.join(tag))?;
+            fs::write(dir.join(tag).join(".marker"), "")?;
+        }
+        fs::create_dir_all(dir.join("other"))?;
+        fs::write(dir.join(".journal.json"), "{}")?;
+        let old = std::time::SystemTime::now() - Duration::from_secs(3600);
+        File::open(dir.join("v1.0.0"))?.set_modified(old)?;
+        File::open(dir.join("other"))?.set_modified(old)?;
+
+        // 只删除带marker且超过时间的目录, 文件和其他目录保留
+        assert_eq!(
+            remove_stale_dirs(&dir, ".marker", Duration::from_secs(60))?,
+            1
+        );
+        assert!(!dir.join("v1.0.0").exists());
+        assert!(dir.join("v1.1.0").exists());
+        assert!(dir.join("other").exists());
+        assert!(dir.join(".journal.json").exists());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+use crate::cancel::Cancellation;
+use crate::model::{Assert, Release, RepoEndpoint};
+use crate::progress::ProgressSink;
+use crate::provider::ReleaseTarget;
+use crate::transfer::{self, UploadBody};
+use crate::{AnyResult, http};
+use anyhow::anyhow;
+use log::info;
+use reqwest::Url;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+
+/// gitlab目标仓库: 附件上传到generic package registry, 并添加为release link
+pub struct GitlabTarget {
+    client: Client,
+    endpoint: RepoEndpoint,
+    upload_retry_times: u32,
+    sink: Option<Arc<dyn ProgressSink>>,
+}
+
+impl GitlabTarget {
+    pub fn new(
+        client: &Client,
+        endpoint: RepoEndpoint,
+        upload_retry_times: u32,
+        sink: Option<Arc<dyn ProgressSink>>,
+    ) -> Self {
+        GitlabTarget {
+            client: client.clone(),
+            endpoint,
+            upload_retry_times,
+            sink,
+        }
+    }
+}
+
+impl ReleaseTarget for GitlabTarget {
+    fn endpoint(&self) -> &RepoEndpoint {
+        &self.endpoint
+    }
+
+    fn releases(&self) -> AnyResult<Vec<Release>> {
+        releases(&self.client, &self.endpoint)
+    }
+
+    fn create_release(&self, release: &Release) -> AnyResult<Release> {
+        release_create(&self.client, &self.endpoint, release)
+    }
+
+    fn update_release(&self, release: &Release) -> AnyResult<()> {
+        release_update(&self.client, &self.endpoint, release)
+    }
+
+    fn delete_release(&self, release: &Release) -> AnyResult<()> {
+        release_delete(&self.client, &self.endpoint, &release.tag_name)
+    }
+
+    fn upload_asset(
+        &self,
+        release: &Release,
+        asset_name: &str,
+        file_path: &Path,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()> {
+        let url = package_url(&self.endpoint, &release.tag_name, asset_name)?;
+        transfer::upload(
+            &self.client,
+            url.as_str(),
+            &self.endpoint.require_authorization()?,
+            file_path,
+            UploadBody::Package,
+            self.upload_retry_times,
+            self.sink.as_ref(),
+            cancellation,
+        )?;
+        release_link_create(
+            &self.client,
+            &self.endpoint,
+            &release.tag_name,
+            asset_name,
+            &url,
+        )
+    }
+
+    /// 只删除release link, package registry中的文件保留
+    fn delete_asset(&self, release: &Release, asset: &Assert) -> AnyResult<()> {
+        let Some(id) = asset.id else {
+            anyhow::bail!("gitlab release link id is unknown: {}", asset.name);
+        };
+        let url = api_url(
+            &self.endpoint,
+            &[
+                "releases",
+                &release.tag_name,
+                "assets",
+                "links",
+                &id.to_string(),
+            ],
+        )?;
+        http::delete(
+            &self.client,
+            url.as_str(),
+            &self.endpoint.require_authorization()?,
+        )
+    }
+
+    fn supports_prerelease(&self) -> bool {
+        false
+    }
+}
+
+/// gitlab release: 没有数字id, 以tag_name为标识; 附件为release links
+#[derive(Debug, Deserialize)]
+struct GitlabRelease {
+    tag_name: String,
+    name: Option<String>,
+    description: Option<String>,
+    released_at: Option<String>,
+    commit: Option<GitlabCommit>,
+    #[serde(default)]
+    assets: GitlabAssets,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabCommit {
+    id: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GitlabAssets {
+    #[serde(default)]
+    links: Vec<GitlabLink>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabLink {
+    id: u64,
+    name: String,
+    url: String,
+    direct_asset_url: Option<String>,
+}
+
+impl GitlabRelease {
+    /// 转换为通用的Release, id仅用于排序(越新越大)
+    fn into_release(self, id: u64) -> Release {
+        Release {
+            id,
+            name: self.name.unwrap_or_else(|| self.tag_name.clone()),
+            tag_name: self.tag_name,
+            body: self.description,
+            prerelease: false,
+            draft: false,
+            target_commitish: self.commit.map(|commit| commit.id).unwrap_or_default(),
+            updated_at: self.released_at.clone(),
+            created_at: None,
+            published_at: self.released_at,
+            assets: self
+                .assets
+                .links
+                .into_iter()
+                .map(|link| Assert {
+                    id: Some(link.id),
+                    name: link.name,
+                    size: None,
+                    browser_download_url: link.direct_asset_url.unwrap_or(link.url),
+                    digest: None,
+                    label: None,
+                    download_count: None,
+                    content_type: None,
+                    api_url: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 项目接口下的地址, 路径的每一段都会编码(tag中可能包含/)
+fn api_url(target: &RepoEndpoint, segments: &[&str]) -> AnyResult<Url> {
+    let mut url = Url::parse(&target.api_url())?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("invalid gitlab url: {}", target.api_url()))?
+        .extend(segments);
+    Ok(url)
+}
+
+/// 查询最近100个release: gitlab按released_at倒序返回
+fn releases(client: &Client, target: &RepoEndpoint) -> AnyResult<Vec<Release>> {
+    let mut url = api_url(target, &["releases"])?;
+    url.set_query(Some("per_page=100"));
+    let result = http::get(client, url.as_str(), target.authorization())?;
+    parse_releases(&result)
+}
+
+fn parse_releases(text: &str) -> AnyResult<Vec<Release>> {
+    let releases: Vec<GitlabRelease> = serde_json::from_str(text)?;
+    let count = releases.len() as u64;
+    Ok(releases
+        .into_iter()
+        .enumerate()
+        .map(|(i, release)| release.into_release(count - i as u64))
+        .collect())
+}
+
+/// 创建release: tag不存在时按ref(github的target_commitish)创建
+fn release_create(client: &Client, target: &RepoEndpoint, release: &Release) -> AnyResult<Release> {
+    let url = api_url(target, &["releases"])?;
+    let mut param = json!({
+        "tag_name": release.tag_name,
+        "name": release.name,
+        "description": release.body,
+        "ref": release.target_commitish,
+    });
+    // 过去的发布时间: gitlab标记为历史版本
+    if let Some(published_at) = &release.published_at {
+        param["released_at"] = json!(published_at);
+    }
+    let result = http::post(
+        client,
+        url.as_str(),
+        &target.require_authorization()?,
+        &param,
+    )?;
+    let release: GitlabRelease = serde_json::from_str(&result)?;
+    Ok(release.into_release(0))
+}
+
+/// 更新release的名称和描述(gitlab没有预发布标记)
+fn release_update(client: &Client, target: &RepoEndpoint, release: &Release) -> AnyResult<()> {
+    let url = api_url(target, &["releases", &release.tag_name])?;
+    let param = json!({
+        "name": release.name,
+        "description": release.body,
+    });
+    http::put(
+        client,
+        url.as_str(),
+        &target.require_authorization()?,
+        &param,
+    )?;
+    Ok(())
+}
+
+fn release_delete(client: &Client, target: &RepoEndpoint, tag_name: &str) -> AnyResult<()> {
+    let url = api_url(target, &["releases", tag_name])?;
+    http::delete(client, url.as_str(), &target.require_authorization()?)
+}
+
+/// 附件上传地址: generic package registry, 包名为仓库名, 版本为tag
+fn package_url(target: &RepoEndpoint, tag_name: &str, asset_name: &str) -> AnyResult<Url> {
+    api_url(
+        target,
+        &["packages", "generic", &target.repo, tag_name, asset_name],
+    )
+}
+
+/// 上传到package registry的附件添加为release link
+fn release_link_create(
+    client: &Client,
+    target: &RepoEndpoint,
+    tag_name: &str,
+    asset_name: &str,
+    package_url: &Url,
+) -> AnyResult<()> {
+    let url = api_url(target, &["releases", tag_name, "assets", "links"])?;
+    let param = json!({
+        "name": asset_name,
+        "url": package_url.as_str(),
+        "link_type": "package",
+    });
+    http::post(
+        client,
+        url.as_str(),
+        &target.require_authorization()?,
+        &param,
+    )?;
+    info!("gitlab release link create success: {tag_name}, {asset_name}");
+    Ok(())
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use crate::model::Cli;
+    use clap::Parser;
+
+    #[test]
+    fn test_gitlab_release() -> AnyResult<()> {
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=hepengju",
+            "--github-repo=redis-me",
+            "--target=gitlab",
+            "--gitlab-project=hepengju/mirror/redis-me",
+        ]);
+        cli.validate()?;
+        let target = cli.target();
+        assert_eq!(
+            target.api_url(),
+            "https://gitlab.com/api/v4/projects/hepengju%2Fmirror%2Fredis-me"
+        );
+        assert_eq!(
+            package_url(&target, "v1.0/beta", "redis-me.dmg")?.as_str(),
+            "https://gitlab.com/api/v4/projects/hepengju%2Fmirror%2Fredis-me/packages/generic/redis-me/v1.0%2Fbeta/redis-me.dmg"
+        );
+        assert_eq!(target.key(), "hepengju_mirror-redis-me");
+
+        let result = r#"[
+            {"tag_name":"v0.2.0","name":null,"description":"new","released_at":"2025-12-25T08:22:42Z",
+             "commit":{"id":"abc"},"assets":{"count":1,"sources":[],"links":[
+               {"id":1,"name":"a.zip","url":"https://gitlab.com/x/a.zip","direct_asset_url":"https://gitlab.com/d/a.zip","link_type":"package"}]}},
+            {"tag_name":"v0.1.0","name":"v0.1.0","description":null,"assets":{"links":[]}}
+        ]"#;
+        let releases = parse_releases(result)?;
+        assert_eq!(releases[0].name, "v0.2.0");
+        assert!(releases[0].id > releases[1].id);
+        assert_eq!(
+            releases[0].assets[0].browser_download_url,
+            "https://gitlab.com/d/a.zip"
+        );
+        Ok(())
+    }
+}
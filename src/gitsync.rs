@@ -0,0 +1,183 @@
+//! 同步代码(--sync-code): 同步release之前, 通过git将源仓库的分支和tag推送到目标仓库
+//! - 本地保留裸仓库({工作目录}/.git-mirror/{镜像仓库}.git), 之后每次只拉取增量
+//! - token通过环境变量中的git配置(http.extraHeader)传递, 不出现在命令行参数和远程地址中
+
+use crate::AnyResult;
+use crate::cancel::Cancellation;
+use crate::model::{Cli, Forge, RepoEndpoint};
+use anyhow::{Context, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use log::info;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+
+/// 同步的引用: 所有分支和tag(不包括github的refs/pull/*)
+const REFSPECS: [&str; 2] = ["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"];
+
+pub fn sync_code(cli: &Cli, cancellation: &Cancellation) -> AnyResult<()> {
+    let dir = cli
+        .work_dir()
+        .join(".git-mirror")
+        .join(format!("{}.git", cli.mirror_key()));
+    mirror(&dir, &cli.source(), &cli.mirror(), cancellation)
+}
+
+/// 拉取源仓库的分支和tag到本地裸仓库, 然后强制推送到目标仓库
+fn mirror(
+    dir: &Path,
+    source: &RepoEndpoint,
+    target: &RepoEndpoint,
+    cancellation: &Cancellation,
+) -> AnyResult<()> {
+    let started = Instant::now();
+    if !dir.join("HEAD").exists() {
+        fs::create_dir_all(dir)?;
+        git(dir, None, &["init", "--bare", "--quiet"])?;
+        info!("git mirror init: {}", dir.display());
+    }
+
+    cancellation.check()?;
+    let source_url = git_url(source)?;
+    let mut args = vec!["fetch", "--prune", "--quiet", &source_url];
+    args.extend(REFSPECS);
+    git(dir, Some(source), &args).context("git fetch from source error")?;
+
+    cancellation.check()?;
+    let target_url = git_url(target)?;
+    let mut args = vec!["push", "--quiet", &target_url];
+    args.extend(REFSPECS);
+    git(dir, Some(target), &args).context("git push to target error")?;
+    info!(
+        "code sync success: {source_url} -> {target_url}, {:.1}s",
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// 仓库的git地址: {web_url}.git
+fn git_url(endpoint: &RepoEndpoint) -> AnyResult<String> {
+    match endpoint.forge {
+        Forge::GitHub | Forge::Gitee | Forge::GitLab | Forge::Gitea => {
+            Ok(format!("{}.git", endpoint.web_url()))
+        }
+        forge => bail!("--sync-code requires a git repository, {forge} is not supported"),
+    }
+}
+
+/// git的Basic认证用户名: token作为密码
+fn auth_header(endpoint: &RepoEndpoint) -> Option<String> {
+    // 多个token时使用第一个
+    let token = endpoint.token.as_deref()?.split(',').next()?.trim();
+    let username = match endpoint.forge {
+        Forge::GitHub => "x-access-token",
+        Forge::GitLab => "oauth2",
+        _ => &endpoint.owner,
+    };
+    let credentials = STANDARD.encode(format!("{username}:{token}"));
+    Some(format!("Authorization: Basic {credentials}"))
+}
+
+fn git(dir: &Path, endpoint: Option<&RepoEndpoint>, args: &[&str]) -> AnyResult<()> {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        // 不提示输入用户名密码
+        .env("GIT_TERMINAL_PROMPT", "0");
+    if let Some(header) = endpoint.and_then(auth_header) {
+        command
+            .env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", "http.extraHeader")
+            .env("GIT_CONFIG_VALUE_0", header);
+    }
+    let output = command
+        .output()
+        .context("git command start error, is git installed?")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed ({}): {}",
+            args[0],
+            output.status,
+            crate::redact::redact(String::from_utf8_lossy(&output.stderr).trim())
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::CancellationToken;
+    use std::env;
+
+    #[test]
+    fn test_sync_code() -> AnyResult<()> {
+        // 本地仓库模拟源仓库和目标仓库: git地址为{host}/{owner}/{repo}.git
+        let dir = env::temp_dir().join(format!("release2gitee-gitsync-{}", std::process::id()));
+        let endpoint = |repo: &str| RepoEndpoint {
+            forge: Forge::Gitea,
+            host: format!("file://{}", dir.display()),
+            owner: "o".to_string(),
+            repo: repo.to_string(),
+            token: None,
+        };
+        let (source, target) = (dir.join("o/r.git"), dir.join("o/m.git"));
+        fs::create_dir_all(&source)?;
+        fs::create_dir_all(&target)?;
+        git(&source, None, &["init", "--quiet"])?;
+        let commit = [
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@example.com",
+            "commit",
+            "--allow-empty",
+            "--quiet",
+            "-m",
+        ];
+        git(&source, None, &[&commit[..], &["init"]].concat())?;
+        git(&source, None, &["tag", "v1.0.0"])?;
+        git(&source, None, &["branch", "dev"])?;
+        git(&target, None, &["init", "--bare", "--quiet"])?;
+
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        let mirror_dir = dir.join("mirror.git");
+        mirror(&mirror_dir, &endpoint("r"), &endpoint("m"), &cancellation)?;
+        // 再次同步时增量拉取
+        git(&source, None, &[&commit[..], &["second"]].concat())?;
+        git(&source, None, &["tag", "v1.0.1"])?;
+        mirror(&mirror_dir, &endpoint("r"), &endpoint("m"), &cancellation)?;
+
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&target)
+            .args(["for-each-ref", "--format=%(refname)"])
+            .output()?;
+        let refs = String::from_utf8(output.stdout)?;
+        for name in ["refs/heads/dev", "refs/tags/v1.0.0", "refs/tags/v1.0.1"] {
+            assert!(refs.contains(name), "{refs}");
+        }
+        fs::remove_dir_all(&dir)?;
+
+        let endpoint = RepoEndpoint {
+            forge: Forge::GitHub,
+            token: Some("ghp_1,ghp_2".to_string()),
+            ..endpoint("r")
+        };
+        assert_eq!(
+            auth_header(&endpoint).as_deref(),
+            Some(
+                format!(
+                    "Authorization: Basic {}",
+                    STANDARD.encode("x-access-token:ghp_1")
+                )
+                .as_str()
+            )
+        );
+        Ok(())
+    }
+}
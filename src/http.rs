@@ -1,201 +1,448 @@
 use crate::AnyResult;
-use anyhow::bail;
-use indicatif::{ProgressBar, ProgressStyle};
-use log::{debug, info};
-use multipart::Part;
-use reqwest::blocking::{Client, RequestBuilder, Response, multipart};
+use crate::error::ApiError;
+use crate::model::{Cli, RedirectPolicy};
+use crate::trace::{self, SendTraced};
+use anyhow::Context;
+use log::{debug, info, warn};
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
+use reqwest::redirect::Policy;
+use reqwest::{Certificate, Identity};
+use reqwest::{NoProxy, Proxy};
+use reqwest::{StatusCode, Url};
 use serde::Serialize;
-use std::fs::File;
+use std::fs;
 use std::io;
-use std::io::{Read, Write};
-use std::path::PathBuf;
-use std::time::Duration;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-const USER_AGENT: &str = "reqwest";
+pub(crate) const USER_AGENT: &str = "reqwest";
 
-pub fn init_client() -> AnyResult<Client> {
-    let client = Client::builder()
+pub fn init_client(cli: &Cli) -> AnyResult<Client> {
+    let mut builder = Client::builder()
         .retry(reqwest::retry::for_host("api.github.com")) // github的查询和下载进行重试
-        .timeout(Duration::from_secs(60))
-        .build()?;
-    Ok(client)
+        .timeout(cli.http_timeout.as_duration())
+        .redirect(redirect_policy(cli.redirect_policy));
+    builder = tls(builder, cli)?;
+    crate::transfer::limit_rate(cli.limit_rate);
+    if let Some(path) = &cli.trace_http {
+        trace::open(path)?;
+    }
+    if cli.proxy.is_some() || cli.gitee_proxy.is_some() {
+        // 设置代理后reqwest不再读取环境变量, 未指定的部分按环境变量补充
+        builder = builder.no_proxy();
+        for proxy in proxies(cli)? {
+            builder = builder.proxy(proxy);
+        }
+    }
+    Ok(builder.build()?)
 }
 
-pub fn get(client: &Client, url: &str, token: Option<String>) -> AnyResult<String> {
-    info!("GET: {url}");
-    let mut builder = client.get(url).header("User-Agent", USER_AGENT);
-    if token.is_some() {
-        // 可选设置github_token. 速率: 50 次/小时  ==> 3000 次/小时
-        builder = builder.header("Authorization", format!("token {}", token.unwrap()));
+/// TLS配置: 额外信任的CA证书, 客户端证书(mTLS), 不校验服务端证书
+fn tls(mut builder: ClientBuilder, cli: &Cli) -> AnyResult<ClientBuilder> {
+    if let Some(path) = &cli.ca_cert {
+        let pem = fs::read(path).with_context(|| format!("read ca cert: {path}"))?;
+        for cert in Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(cert);
+        }
+        info!("ca cert added: {path}");
+    }
+    if let (Some(cert), Some(key)) = (&cli.client_cert, &cli.client_key) {
+        let cert_pem = fs::read(cert).with_context(|| format!("read client cert: {cert}"))?;
+        let key_pem = fs::read(key).with_context(|| format!("read client key: {key}"))?;
+        builder = builder.identity(identity(cert_pem, key_pem)?);
+        info!("client cert used: {cert}");
+    }
+    if cli.insecure {
+        warn!("tls certificate verification disabled (--insecure)");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    Ok(builder)
+}
+
+/// 客户端证书: rustls要求证书和私钥在同一个PEM中, native-tls要求私钥为PKCS#8格式
+#[cfg(feature = "rustls")]
+fn identity(mut cert_pem: Vec<u8>, key_pem: Vec<u8>) -> AnyResult<Identity> {
+    cert_pem.push(b'\n');
+    cert_pem.extend(key_pem);
+    Ok(Identity::from_pem(&cert_pem)?)
+}
+
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+fn identity(cert_pem: Vec<u8>, key_pem: Vec<u8>) -> AnyResult<Identity> {
+    Ok(Identity::from_pkcs8_pem(&cert_pem, &key_pem)?)
+}
+
+/// 代理不使用时的取值
+const DIRECT: &str = "direct";
+
+/// 代理配置: gitee的域名使用--gitee-proxy, 其他使用--proxy或环境变量
+fn proxies(cli: &Cli) -> AnyResult<Vec<Proxy>> {
+    let gitee_host = Url::parse(&cli.gitee_api_url)?
+        .host_str()
+        .unwrap_or_default()
+        .to_string();
+    let mut proxies = Vec::new();
+    // 其他代理不作用于gitee的域名
+    let mut no_proxy = NoProxy::from_env();
+    if let Some(gitee_proxy) = cli.gitee_proxy.as_deref() {
+        if gitee_proxy != DIRECT {
+            let proxy_url = Url::parse(gitee_proxy)?;
+            let host = gitee_host.clone();
+            proxies.push(Proxy::custom(move |url| {
+                is_same_site(url, &host).then(|| proxy_url.clone())
+            }));
+        }
+        let mut hosts = vec![gitee_host.clone(), format!(".{gitee_host}")];
+        hosts.extend(env_var(&["NO_PROXY", "no_proxy"]));
+        no_proxy = NoProxy::from_string(&hosts.join(","));
+    }
+
+    match cli.proxy.as_deref() {
+        Some(DIRECT) => {}
+        Some(proxy) => proxies.push(Proxy::all(proxy)?.no_proxy(no_proxy)),
+        None => {
+            // 与reqwest读取环境变量的方式一致: 协议对应的代理优先, 然后是ALL_PROXY
+            for (scheme, names) in [
+                ("https", ["HTTPS_PROXY", "https_proxy"]),
+                ("http", ["HTTP_PROXY", "http_proxy"]),
+            ] {
+                if let Some(proxy) = env_var(&names) {
+                    let proxy = match scheme {
+                        "https" => Proxy::https(proxy)?,
+                        _ => Proxy::http(proxy)?,
+                    };
+                    proxies.push(proxy.no_proxy(no_proxy.clone()));
+                }
+            }
+            if let Some(proxy) = env_var(&["ALL_PROXY", "all_proxy"]) {
+                proxies.push(Proxy::all(proxy)?.no_proxy(no_proxy));
+            }
+        }
+    }
+    Ok(proxies)
+}
+
+/// 域名相同或为其子域名(如gitee附件下载重定向到的子域名)
+fn is_same_site(url: &Url, host: &str) -> bool {
+    url.host_str().is_some_and(|url_host| {
+        url_host == host
+            || url_host
+                .strip_suffix(host)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+const MAX_REDIRECTS: usize = 10;
+
+/// 重定向策略: 跨域名/端口/协议的重定向由reqwest移除Authorization、Cookie等请求头, token不会泄露给第三方
+fn redirect_policy(policy: RedirectPolicy) -> Policy {
+    match policy {
+        RedirectPolicy::Follow => Policy::limited(MAX_REDIRECTS),
+        RedirectPolicy::None => Policy::none(),
+        RedirectPolicy::SameOrigin => Policy::custom(|attempt| {
+            if attempt.previous().len() > MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            let origin = attempt.previous().first().map(Url::origin);
+            if origin == Some(attempt.url().origin()) {
+                attempt.follow()
+            } else {
+                warn!("cross-origin redirect stopped: {}", attempt.url());
+                attempt.stop()
+            }
+        }),
     }
-    let res = builder.send()?;
+}
+
+/// authorization: Authorization请求头, 如: token xxx (由RepoEndpoint::authorization生成)
+pub fn get(client: &Client, url: &str, authorization: Option<String>) -> AnyResult<String> {
+    info!("GET: {url}");
+    let res = get_builder(client, url, authorization).send_traced()?;
     let text = extract_response_text(res)?;
     debug!("response: {}", text);
     Ok(text)
 }
 
+/// 条件请求的结果
+#[derive(Debug)]
+pub enum Conditional {
+    /// 304: 与上次的ETag一致
+    NotModified,
+    Modified {
+        text: String,
+        etag: Option<String>,
+    },
+}
+
+/// 携带If-None-Match的GET请求(github返回304时不消耗速率限制)
+pub fn get_if_none_match(
+    client: &Client,
+    url: &str,
+    authorization: Option<String>,
+    etag: Option<&str>,
+) -> AnyResult<Conditional> {
+    info!("GET: {url}, if-none-match: {}", etag.unwrap_or("None"));
+    let mut builder = get_builder(client, url, authorization);
+    if let Some(etag) = etag {
+        builder = builder.header("If-None-Match", etag);
+    }
+    let res = builder.send_traced()?;
+    if res.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Conditional::NotModified);
+    }
+
+    let etag = res
+        .headers()
+        .get("etag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let text = extract_response_text(res)?;
+    debug!("response: {}", text);
+    Ok(Conditional::Modified { text, etag })
+}
+
+fn get_builder(client: &Client, url: &str, authorization: Option<String>) -> RequestBuilder {
+    let builder = client.get(url).header("User-Agent", USER_AGENT);
+    match authorization {
+        // 可选设置github_token. 速率: 50 次/小时  ==> 3000 次/小时
+        Some(authorization) => builder.header("Authorization", authorization),
+        None => builder,
+    }
+}
+
 pub fn post<T: Serialize + ?Sized>(
     client: &Client,
     url: &str,
-    token: &str,
+    authorization: &str,
     json: &T,
 ) -> AnyResult<String> {
     info!("POST: {url}");
-    post_or_patch(client.post(url), token, json)
+    send_json(client.post(url), authorization, json)
 }
 
 pub fn patch<T: Serialize + ?Sized>(
     client: &Client,
     url: &str,
-    token: &str,
+    authorization: &str,
     json: &T,
 ) -> AnyResult<String> {
     info!("PATCH: {url}");
-    post_or_patch(client.patch(url), token, json)
+    send_json(client.patch(url), authorization, json)
+}
+
+pub fn put<T: Serialize + ?Sized>(
+    client: &Client,
+    url: &str,
+    authorization: &str,
+    json: &T,
+) -> AnyResult<String> {
+    info!("PUT: {url}");
+    send_json(client.put(url), authorization, json)
 }
 
-fn post_or_patch<T: Serialize + ?Sized>(
+fn send_json<T: Serialize + ?Sized>(
     builder: RequestBuilder,
-    token: &str,
+    authorization: &str,
     json: &T,
 ) -> AnyResult<String> {
     let res = builder
-        .header("Authorization", format!("token {}", token))
+        .header("Authorization", authorization)
         .header("User-Agent", USER_AGENT)
         .header("Content-Type", "application/json")
         .json(json)
-        .send()?;
+        .send_traced()?;
     debug!("param: {}", serde_json::to_string(json)?);
     let text = extract_response_text(res)?;
     debug!("response: {text}");
     Ok(text)
 }
 
-pub fn delete(client: &Client, url: &str, token: &str) -> AnyResult<()> {
+pub fn delete(client: &Client, url: &str, authorization: &str) -> AnyResult<()> {
     info!("DELETE: {url}");
     let res = client
         .delete(url)
-        .header("Authorization", format!("token {}", token))
+        .header("Authorization", authorization)
         .header("User-Agent", USER_AGENT)
-        .send()?;
+        .send_traced()?;
     let text = extract_response_text(res)?;
     debug!("response: {text}");
     Ok(())
 }
 
+/// 失败的响应转换为ApiError
+pub fn check_status(res: Response) -> AnyResult<Response> {
+    if res.status().is_success() {
+        Ok(res)
+    } else {
+        Err(api_error(res).into())
+    }
+}
+
 fn extract_response_text(res: Response) -> AnyResult<String> {
     if res.status().is_success() {
+        let url = res.url().clone();
         let text = res.text()?;
+        trace::response_body(&url, &text);
         Ok(text)
     } else {
-        bail!("response err: {:?}", res)
+        Err(api_error(res).into())
     }
 }
 
-pub fn download(client: &Client, url: &str, file_path: &PathBuf) -> AnyResult<()> {
-    info!("downloading: {}", url);
+/// 失败响应转换为ApiError: 区分认证失败和速率限制
+pub(crate) fn api_error(res: Response) -> ApiError {
+    let status = res.status().as_u16();
+    let url = res.url().to_string();
+    let rate_limit_remaining = res
+        .headers()
+        .get("x-ratelimit-remaining")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string());
+    let rate_limited =
+        status == 429 || (status == 403 && rate_limit_remaining.as_deref() == Some("0"));
+    // 速率限制的重置时间(unix秒)
+    let rate_limit_reset = res
+        .headers()
+        .get("x-ratelimit-reset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok());
+    let message = res.text().unwrap_or_default();
+    if let Ok(url) = Url::parse(&url) {
+        trace::response_body(&url, &message);
+    }
+    ApiError {
+        status,
+        url,
+        message,
+        rate_limited,
+        rate_limit_reset,
+    }
+}
 
-    let mut res = client
-        .get(url)
-        .header("User-Agent", reqwest::header::USER_AGENT)
-        .send()?;
+/// 服务不可访问: 可重试的错误或速率限制耗尽(认证失败等不属于)
+pub fn is_unreachable(e: &anyhow::Error) -> bool {
+    is_retryable(e) || e.downcast_ref::<ApiError>().is_some_and(|e| e.rate_limited)
+}
 
-    if res.status().is_success() {
-        // 获取内容长度用于进度条
-        let total_size = res.content_length().unwrap_or(0);
-        let pb = get_progress_bar(total_size)?;
+/// 多个token轮换(如github的多个PAT分摊每小时5000次的速率限制)
+/// - 每个token单独记录速率限制是否耗尽, 当前token耗尽时切换到下一个未耗尽的token重试
+#[derive(Debug, Default)]
+pub struct TokenRotation {
+    tokens: Vec<String>,
+    current: AtomicUsize,
+    exhausted: Mutex<Vec<bool>>,
+}
 
-        // 创建文件
-        let mut file = File::create(&file_path)?;
+impl TokenRotation {
+    /// 多个token以逗号分隔: t1,t2
+    pub fn parse(tokens: Option<&str>) -> Self {
+        let tokens = tokens
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        TokenRotation {
+            exhausted: Mutex::new(vec![false; tokens.len()]),
+            tokens,
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.tokens
+            .get(self.current.load(Ordering::SeqCst))
+            .map(String::as_str)
+    }
 
-        // 下载并更新进度
-        // 分块读取、写入并更新进度
-        let mut buffer = [0u8; 8192]; // 8KB 缓冲区
+    /// 使用当前token执行请求, 速率限制耗尽时切换到下一个token重试, 全部耗尽时返回最后的错误
+    pub fn call<T>(&self, mut request: impl FnMut(Option<&str>) -> AnyResult<T>) -> AnyResult<T> {
         loop {
-            let n = res.read(&mut buffer)?;
-            if n == 0 {
-                break;
+            let index = self.current.load(Ordering::SeqCst);
+            match request(self.tokens.get(index).map(String::as_str)) {
+                Err(e) if is_rate_limited(&e) && self.rotate(index) => {
+                    warn!(
+                        "token #{} rate limited, rotate to token #{}",
+                        index + 1,
+                        self.current.load(Ordering::SeqCst) + 1
+                    );
+                }
+                result => return result,
             }
-            file.write_all(&buffer[..n])?;
-            pb.inc(n as u64);
         }
-        pb.finish_with_message("");
-        Ok(())
-    } else {
-        bail!(
-            "download file error: {}",
-            file_path.file_name().unwrap().display()
-        );
     }
-}
 
-pub fn upload(client: &Client, url: &str, token: &str, file_path: &PathBuf) -> AnyResult<()> {
-    let name = file_path.file_name().unwrap().display();
-    info!("uploading: {}, file: {}", url, name);
-
-    let file = File::open(file_path)?;
-    let pb = get_progress_bar(file.metadata().unwrap().len())?;
-
-    // 使用自定义的 ProgressRead 包裹文件读取
-    let progress_reader = ProgressRead {
-        inner: file,
-        progress: pb.clone(),
-    };
-
-    // 创建 multipart 表单数据
-    let full_name = file_path.display().to_string();
-    let form =
-        multipart::Form::new().part("file", Part::reader(progress_reader).file_name(full_name));
-    // 上传文件到Gitee
-    let upload_response = client
-        .post(url)
-        .header("Authorization", format!("token {}", token))
-        .multipart(form)
-        .send()?;
-    pb.finish_with_message("");
-
-    if !upload_response.status().is_success() {
-        bail!(
-            "upload file error: {}",
-            file_path.file_name().unwrap().display()
-        );
+    // 标记token耗尽, 切换到下一个未耗尽的token, 没有可用的token时返回false
+    fn rotate(&self, index: usize) -> bool {
+        let mut exhausted = self.exhausted.lock().expect("token rotation lock");
+        if let Some(flag) = exhausted.get_mut(index) {
+            *flag = true;
+        }
+        let len = exhausted.len();
+        let next = (1..len).map(|i| (index + i) % len).find(|&i| !exhausted[i]);
+        match next {
+            Some(next) => {
+                self.current.store(next, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
     }
-    Ok(())
-}
-
-fn get_progress_bar(size: u64) -> AnyResult<ProgressBar> {
-    let pb = ProgressBar::new(size);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{elapsed_precise:.white.dim} [{wide_bar:.cyan}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")?
-            .progress_chars("#>-"),
-    );
-    Ok(pb)
 }
 
-// 自定义实现 Read 来更新进度条
-struct ProgressRead<R> {
-    inner: R,
-    progress: ProgressBar,
+/// 速率限制耗尽的错误
+fn is_rate_limited(e: &anyhow::Error) -> bool {
+    e.chain()
+        .filter_map(|cause| cause.downcast_ref::<ApiError>())
+        .any(|e| e.rate_limited)
 }
 
-impl<R: Read> Read for ProgressRead<R> {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let n = self.inner.read(buf)?;
-        if n > 0 {
-            self.progress.inc(n as u64);
+/// 可重试的错误: 网络错误(连接、超时等)或服务端5xx错误
+pub(crate) fn is_retryable(e: &anyhow::Error) -> bool {
+    if let Some(api_error) = e.downcast_ref::<ApiError>() {
+        return api_error.status >= 500;
+    }
+    if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+        return is_retryable_reqwest(e);
+    }
+    // 下载过程中连接中断: 读取响应体的错误包装在io::Error中
+    if let Some(e) = e.downcast_ref::<io::Error>() {
+        if let Some(e) = e.get_ref().and_then(|e| e.downcast_ref::<reqwest::Error>()) {
+            return is_retryable_reqwest(e);
         }
-        Ok(n)
+        return matches!(
+            e.kind(),
+            io::ErrorKind::TimedOut
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::UnexpectedEof
+        );
     }
+    false
+}
+
+fn is_retryable_reqwest(e: &reqwest::Error) -> bool {
+    e.is_timeout() || e.is_connect() || e.is_request() || e.is_body()
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "cli"))]
 mod tests {
     use super::*;
     use crate::model::Release;
+    use std::io::Write;
+    use std::thread;
     use version_compare::Version;
 
     #[test]
+    #[allow(clippy::needless_borrow)]
     fn test_get() -> AnyResult<()> {
         // 测试反序列化失败（body为null, 需要定义为Option<T>）
         let result = r#"[{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/272775542","assets_url":"https://api.github.com/repos/hepengju/release2gitee/releases/272775542/assets","upload_url":"https://uploads.github.com/repos/hepengju/release2gitee/releases/272775542/assets{?name,label}","html_url":"https://github.com/hepengju/release2gitee/releases/tag/v0.9.4","id":272775542,"author":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"node_id":"RE_kwDOQr-QxM4QQjl2","tag_name":"v0.9.4","target_commitish":"master","name":"v0.9.4","draft":false,"immutable":false,"prerelease":false,"created_at":"2025-12-25T08:18:09Z","updated_at":"2025-12-25T08:22:42Z","published_at":"2025-12-25T08:18:58Z","assets":[{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332775978","id":332775978,"node_id":"RA_kwDOQr-QxM4T1cIq","name":"release2gitee-universal-apple-darwin.tar.gz","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/x-gtar","state":"uploaded","size":5785442,"digest":"sha256:86e7a244bfb7e8ff95eb7f9cc741c5c964062f727758a6abb0c09df3c11486b2","download_count":0,"created_at":"2025-12-25T08:22:41Z","updated_at":"2025-12-25T08:22:42Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.4/release2gitee-universal-apple-darwin.tar.gz"},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332775768","id":332775768,"node_id":"RA_kwDOQr-QxM4T1cFY","name":"release2gitee-x86_64-pc-windows-msvc.zip","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/zip","state":"uploaded","size":2402653,"digest":"sha256:df68af431fe3ab73460c30c0117754a9222a921bde1412b99be6431264ef9ccf","download_count":0,"created_at":"2025-12-25T08:21:56Z","updated_at":"2025-12-25T08:21:57Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.4/release2gitee-x86_64-pc-windows-msvc.zip"},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332775270","id":332775270,"node_id":"RA_kwDOQr-QxM4T1b9m","name":"release2gitee-x86_64-unknown-linux-gnu.tar.gz","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/x-gtar","state":"uploaded","size":3070120,"digest":"sha256:94fca392f3244bcdbebbd0d17566c09f541d7e425cd7d7f7e897f7fda6551a40","download_count":0,"created_at":"2025-12-25T08:20:23Z","updated_at":"2025-12-25T08:20:24Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.4/release2gitee-x86_64-unknown-linux-gnu.tar.gz"}],"tarball_url":"https://api.github.com/repos/hepengju/release2gitee/tarball/v0.9.4","zipball_url":"https://api.github.com/repos/hepengju/release2gitee/zipball/v0.9.4","body":null},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/272772969","assets_url":"https://api.github.com/repos/hepengju/release2gitee/releases/272772969/assets","upload_url":"https://uploads.github.com/repos/hepengju/release2gitee/releases/272772969/assets{?name,label}","html_url":"https://github.com/hepengju/release2gitee/releases/tag/v0.9.3","id":272772969,"author":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"node_id":"RE_kwDOQr-QxM4QQi9p","tag_name":"v0.9.3","target_commitish":"master","name":"v0.9.3","draft":false,"immutable":false,"prerelease":false,"created_at":"2025-12-25T07:36:20Z","updated_at":"2025-12-25T07:40:04Z","published_at":"2025-12-25T07:37:07Z","assets":[{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332767841","id":332767841,"node_id":"RA_kwDOQr-QxM4T1aJh","name":"release2gitee-universal-apple-darwin.tar.gz","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/x-gtar","state":"uploaded","size":5783698,"digest":"sha256:9e7cc0f7098fa60dfd346ed9012e5b1af2bb8572eeff6d422db9559220e8c882","download_count":1,"created_at":"2025-12-25T07:39:40Z","updated_at":"2025-12-25T07:39:41Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.3/release2gitee-universal-apple-darwin.tar.gz"},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332767891","id":332767891,"node_id":"RA_kwDOQr-QxM4T1aKT","name":"release2gitee-x86_64-pc-windows-msvc.zip","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/zip","state":"uploaded","size":2402029,"digest":"sha256:f3352e886eb1df9f6bb4d4ffd1427ba6a0bada8c3367cdff005229d07097b475","download_count":1,"created_at":"2025-12-25T07:40:03Z","updated_at":"2025-12-25T07:40:04Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.3/release2gitee-x86_64-pc-windows-msvc.zip"},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332767657","id":332767657,"node_id":"RA_kwDOQr-QxM4T1aGp","name":"release2gitee-x86_64-unknown-linux-gnu.tar.gz","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/x-gtar","state":"uploaded","size":3069000,"digest":"sha256:5e219d1ff714597fd681ce9ee68c935ed7825ac2fc0dd08ff9cd428e1f859cc1","download_count":1,"created_at":"2025-12-25T07:38:28Z","updated_at":"2025-12-25T07:38:28Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.3/release2gitee-x86_64-unknown-linux-gnu.tar.gz"}],"tarball_url":"https://api.github.com/repos/hepengju/release2gitee/tarball/v0.9.3","zipball_url":"https://api.github.com/repos/hepengju/release2gitee/zipball/v0.9.3","body":null},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/272765438","assets_url":"https://api.github.com/repos/hepengju/release2gitee/releases/272765438/assets","upload_url":"https://uploads.github.com/repos/hepengju/release2gitee/releases/272765438/assets{?name,label}","html_url":"https://github.com/hepengju/release2gitee/releases/tag/v0.9.2","id":272765438,"author":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"node_id":"RE_kwDOQr-QxM4QQhH-","tag_name":"v0.9.2","target_commitish":"master","name":"v0.9.2","draft":false,"immutable":false,"prerelease":false,"created_at":"2025-12-25T05:38:16Z","updated_at":"2025-12-25T07:07:39Z","published_at":"2025-12-25T05:39:19Z","assets":[{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332748813","id":332748813,"node_id":"RA_kwDOQr-QxM4T1VgN","name":"release2gitee-universal-apple-darwin.tar.gz","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/x-gtar","state":"uploaded","size":5783864,"digest":"sha256:e5a00e9f5b33bcc9d676cea45c9047fae6c7a7fbe81800e3cd24e6d2f678fcac","download_count":2,"created_at":"2025-12-25T05:41:45Z","updated_at":"2025-12-25T05:41:46Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.2/release2gitee-universal-apple-darwin.tar.gz"},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332748897","id":332748897,"node_id":"RA_kwDOQr-QxM4T1Vhh","name":"release2gitee-x86_64-pc-windows-msvc.zip","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/zip","state":"uploaded","size":2402243,"digest":"sha256:c68f4f680a152906a292bc1bfc7ba787235f0f5eb71a629541266d0eabd8fae9","download_count":3,"created_at":"2025-12-25T05:42:21Z","updated_at":"2025-12-25T05:42:21Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.2/release2gitee-x86_64-pc-windows-msvc.zip"},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332748639","id":332748639,"node_id":"RA_kwDOQr-QxM4T1Vdf","name":"release2gitee-x86_64-unknown-linux-gnu.tar.gz","label":"","uploader":{"login":"github-actions[bot]","id":41898282,"node_id":"MDM6Qm90NDE4OTgyODI=","avatar_url":"https://avatars.githubusercontent.com/in/15368?v=4","gravatar_id":"","url":"https://api.github.com/users/github-actions%5Bbot%5D","html_url":"https://github.com/apps/github-actions","followers_url":"https://api.github.com/users/github-actions%5Bbot%5D/followers","following_url":"https://api.github.com/users/github-actions%5Bbot%5D/following{/other_user}","gists_url":"https://api.github.com/users/github-actions%5Bbot%5D/gists{/gist_id}","starred_url":"https://api.github.com/users/github-actions%5Bbot%5D/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/github-actions%5Bbot%5D/subscriptions","organizations_url":"https://api.github.com/users/github-actions%5Bbot%5D/orgs","repos_url":"https://api.github.com/users/github-actions%5Bbot%5D/repos","events_url":"https://api.github.com/users/github-actions%5Bbot%5D/events{/privacy}","received_events_url":"https://api.github.com/users/github-actions%5Bbot%5D/received_events","type":"Bot","user_view_type":"public","site_admin":false},"content_type":"application/x-gtar","state":"uploaded","size":3069156,"digest":"sha256:b29a23c7526a58a73d68f9b1b7210acf5495c8072fe0d5bae9ff0a75117a699d","download_count":2,"created_at":"2025-12-25T05:40:35Z","updated_at":"2025-12-25T05:40:36Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.2/release2gitee-x86_64-unknown-linux-gnu.tar.gz"}],"tarball_url":"https://api.github.com/repos/hepengju/release2gitee/tarball/v0.9.2","zipball_url":"https://api.github.com/repos/hepengju/release2gitee/zipball/v0.9.2","body":"优化清理gitee的旧release逻辑（考虑新增的同步个数）"},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/272641536","assets_url":"https://api.github.com/repos/hepengju/release2gitee/releases/272641536/assets","upload_url":"https://uploads.github.com/repos/hepengju/release2gitee/releases/272641536/assets{?name,label}","html_url":"https://github.com/hepengju/release2gitee/releases/tag/v0.9.0","id":272641536,"author":{"login":"hepengju","id":26279882,"node_id":"MDQ6VXNlcjI2Mjc5ODgy","avatar_url":"https://avatars.githubusercontent.com/u/26279882?v=4","gravatar_id":"","url":"https://api.github.com/users/hepengju","html_url":"https://github.com/hepengju","followers_url":"https://api.github.com/users/hepengju/followers","following_url":"https://api.github.com/users/hepengju/following{/other_user}","gists_url":"https://api.github.com/users/hepengju/gists{/gist_id}","starred_url":"https://api.github.com/users/hepengju/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/hepengju/subscriptions","organizations_url":"https://api.github.com/users/hepengju/orgs","repos_url":"https://api.github.com/users/hepengju/repos","events_url":"https://api.github.com/users/hepengju/events{/privacy}","received_events_url":"https://api.github.com/users/hepengju/received_events","type":"User","user_view_type":"public","site_admin":false},"node_id":"RE_kwDOQr-QxM4QQC4A","tag_name":"v0.9.0","target_commitish":"master","name":"v0.9.0","draft":false,"immutable":false,"prerelease":false,"created_at":"2025-12-24T08:15:54Z","updated_at":"2025-12-24T08:23:50Z","published_at":"2025-12-24T08:18:35Z","assets":[{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/332445226","id":332445226,"node_id":"RA_kwDOQr-QxM4T0LYq","name":"release2gitee.exe","label":null,"uploader":{"login":"hepengju","id":26279882,"node_id":"MDQ6VXNlcjI2Mjc5ODgy","avatar_url":"https://avatars.githubusercontent.com/u/26279882?v=4","gravatar_id":"","url":"https://api.github.com/users/hepengju","html_url":"https://github.com/hepengju","followers_url":"https://api.github.com/users/hepengju/followers","following_url":"https://api.github.com/users/hepengju/following{/other_user}","gists_url":"https://api.github.com/users/hepengju/gists{/gist_id}","starred_url":"https://api.github.com/users/hepengju/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/hepengju/subscriptions","organizations_url":"https://api.github.com/users/hepengju/orgs","repos_url":"https://api.github.com/users/hepengju/repos","events_url":"https://api.github.com/users/hepengju/events{/privacy}","received_events_url":"https://api.github.com/users/hepengju/received_events","type":"User","user_view_type":"public","site_admin":false},"content_type":"application/x-msdownload","state":"uploaded","size":5863936,"digest":"sha256:a2a485b3fd73f761ebc587494e06cdd9cbdbbd11d0065e000e52d9368b4a8c5e","download_count":3,"created_at":"2025-12-24T08:18:14Z","updated_at":"2025-12-24T08:18:33Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.9.0/release2gitee.exe"}],"tarball_url":"https://api.github.com/repos/hepengju/release2gitee/tarball/v0.9.0","zipball_url":"https://api.github.com/repos/hepengju/release2gitee/zipball/v0.9.0","body":"- reqwest的http请求支持重试\r\n- 支持配置仅保留N个gitee的release版本\r\n- 上传下载均支持进度条显示\r\n- 命令行日志输出支持verbosity"},{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/271999059","assets_url":"https://api.github.com/repos/hepengju/release2gitee/releases/271999059/assets","upload_url":"https://uploads.github.com/repos/hepengju/release2gitee/releases/271999059/assets{?name,label}","html_url":"https://github.com/hepengju/release2gitee/releases/tag/v0.1.0","id":271999059,"author":{"login":"hepengju","id":26279882,"node_id":"MDQ6VXNlcjI2Mjc5ODgy","avatar_url":"https://avatars.githubusercontent.com/u/26279882?v=4","gravatar_id":"","url":"https://api.github.com/users/hepengju","html_url":"https://github.com/hepengju","followers_url":"https://api.github.com/users/hepengju/followers","following_url":"https://api.github.com/users/hepengju/following{/other_user}","gists_url":"https://api.github.com/users/hepengju/gists{/gist_id}","starred_url":"https://api.github.com/users/hepengju/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/hepengju/subscriptions","organizations_url":"https://api.github.com/users/hepengju/orgs","repos_url":"https://api.github.com/users/hepengju/repos","events_url":"https://api.github.com/users/hepengju/events{/privacy}","received_events_url":"https://api.github.com/users/hepengju/received_events","type":"User","user_view_type":"public","site_admin":false},"node_id":"RE_kwDOQr-QxM4QNmBT","tag_name":"v0.1.0","target_commitish":"master","name":"v0.1.0","draft":false,"immutable":false,"prerelease":false,"created_at":"2025-12-21T06:19:37Z","updated_at":"2025-12-21T06:20:36Z","published_at":"2025-12-21T06:20:36Z","assets":[{"url":"https://api.github.com/repos/hepengju/release2gitee/releases/assets/331276764","id":331276764,"node_id":"RA_kwDOQr-QxM4TvuHc","name":"release2gitee.exe","label":null,"uploader":{"login":"hepengju","id":26279882,"node_id":"MDQ6VXNlcjI2Mjc5ODgy","avatar_url":"https://avatars.githubusercontent.com/u/26279882?v=4","gravatar_id":"","url":"https://api.github.com/users/hepengju","html_url":"https://github.com/hepengju","followers_url":"https://api.github.com/users/hepengju/followers","following_url":"https://api.github.com/users/hepengju/following{/other_user}","gists_url":"https://api.github.com/users/hepengju/gists{/gist_id}","starred_url":"https://api.github.com/users/hepengju/starred{/owner}{/repo}","subscriptions_url":"https://api.github.com/users/hepengju/subscriptions","organizations_url":"https://api.github.com/users/hepengju/orgs","repos_url":"https://api.github.com/users/hepengju/repos","events_url":"https://api.github.com/users/hepengju/events{/privacy}","received_events_url":"https://api.github.com/users/hepengju/received_events","type":"User","user_view_type":"public","site_admin":false},"content_type":"application/x-msdownload","state":"uploaded","size":7053312,"digest":"sha256:afdc03b71e9f4ddabf1ee39a3e236a1ef9c863334ee3696c1a4c4f54669f8dd5","download_count":3,"created_at":"2025-12-21T06:20:03Z","updated_at":"2025-12-21T06:20:32Z","browser_download_url":"https://github.com/hepengju/release2gitee/releases/download/v0.1.0/release2gitee.exe"}],"tarball_url":"https://api.github.com/repos/hepengju/release2gitee/tarball/v0.1.0","zipball_url":"https://api.github.com/repos/hepengju/release2gitee/zipball/v0.1.0","body":"- 完成Github的Release同步到Gitee的功能\r\n- 支持release_body 和 lastest_json里面的download_url自动替换\r\n- 支持多次重试，可复用下载的附件及进行release的body和asserts的对比功能"}]"#;
@@ -205,6 +452,188 @@ mod tests {
     }
 
     #[test]
+    fn test_token_rotation() -> AnyResult<()> {
+        let rate_limited = || {
+            anyhow::Error::new(ApiError {
+                status: 403,
+                url: String::new(),
+                message: String::new(),
+                rate_limited: true,
+                rate_limit_reset: None,
+            })
+        };
+        let tokens = TokenRotation::parse(Some("t1, t2,t3"));
+        assert_eq!(tokens.current(), Some("t1"));
+
+        // t1耗尽后切换到t2, 之后的请求继续使用t2
+        let mut used = Vec::new();
+        let result = tokens.call(|token| {
+            used.push(token.unwrap().to_string());
+            match token {
+                Some("t1") => Err(rate_limited()),
+                _ => Ok(()),
+            }
+        });
+        assert!(result.is_ok());
+        assert_eq!(used, ["t1", "t2"]);
+        assert_eq!(tokens.current(), Some("t2"));
+
+        // 全部耗尽时返回错误
+        let result: AnyResult<()> = tokens.call(|_| Err(rate_limited()));
+        assert!(is_rate_limited(&result.unwrap_err()));
+        assert_eq!(TokenRotation::parse(None).current(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let api_error = |status| {
+            anyhow::Error::new(ApiError {
+                status,
+                url: String::new(),
+                message: String::new(),
+                rate_limited: false,
+                rate_limit_reset: None,
+            })
+        };
+        assert!(is_retryable(&api_error(502)));
+        assert!(!is_retryable(&api_error(401)));
+        assert!(!is_retryable(&anyhow::anyhow!("local file error")));
+    }
+
+    // 本地http服务: 接收一个请求后返回指定的响应, 返回收到的请求头
+    fn serve_once(response: String) -> (u16, thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                request.push_str(&line);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            request
+        });
+        (port, handle)
+    }
+
+    fn redirect_to(location: &str) -> String {
+        format!(
+            "HTTP/1.1 302 Found\r\nLocation: {location}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )
+    }
+
+    fn test_cli(redirect_policy: &str) -> Cli {
+        use clap::Parser;
+        Cli::parse_from([
+            "release2gitee",
+            "--github-owner=o",
+            "--github-repo=r",
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+            &format!("--redirect-policy={redirect_policy}"),
+        ])
+    }
+
+    #[test]
+    fn test_redirect_not_forward_token() -> AnyResult<()> {
+        let ok = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+        // 127.0.0.1 -> localhost: 不同的域名(模拟第三方)
+        let (third_port, third) = serve_once(ok.to_string());
+        let (port, origin) =
+            serve_once(redirect_to(&format!("http://localhost:{third_port}/file")));
+
+        let client = init_client(&test_cli("follow"))?;
+        let url = format!("http://127.0.0.1:{port}/releases");
+        assert_eq!(get(&client, &url, Some("token secret".to_string()))?, "ok");
+        assert!(origin.join().unwrap().contains("secret"));
+        let third_request = third.join().unwrap().to_lowercase();
+        assert!(third_request.starts_with("get /file"));
+        assert!(!third_request.contains("authorization") && !third_request.contains("secret"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_redirect_same_origin_policy() -> AnyResult<()> {
+        // 跨域的重定向不跟随, 直接返回302
+        let (port, origin) = serve_once(redirect_to("http://localhost:1/file"));
+        let client = init_client(&test_cli("same-origin"))?;
+        let url = format!("http://127.0.0.1:{port}/releases");
+        let err = get(&client, &url, Some("token secret".to_string())).unwrap_err();
+        assert_eq!(err.downcast_ref::<ApiError>().map(|e| e.status), Some(302));
+        origin.join().unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_gitee_proxy() -> AnyResult<()> {
+        use clap::Parser;
+        let ok = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+        let (port, proxy) = serve_once(ok.to_string());
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=o",
+            "--github-repo=r",
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+            "--gitee-api-url=http://gitee.test/api/v5",
+            &format!("--gitee-proxy=http://127.0.0.1:{port}"),
+            "--proxy=direct",
+        ]);
+        // gitee的请求发送到代理(请求行为完整地址)
+        let client = init_client(&cli)?;
+        assert_eq!(get(&client, "http://gitee.test/api/v5/repos", None)?, "ok");
+        assert!(
+            proxy
+                .join()
+                .unwrap()
+                .starts_with("GET http://gitee.test/api/v5/repos")
+        );
+
+        let gitee = Url::parse("https://foruda.gitee.test/a.zip")?;
+        assert!(is_same_site(&gitee, "gitee.test"));
+        assert!(!is_same_site(
+            &Url::parse("https://notgitee.test")?,
+            "gitee.test"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tls_options() {
+        use clap::Parser;
+        let args = [
+            "release2gitee",
+            "--github-owner=o",
+            "--github-repo=r",
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+        ];
+        let cli = Cli::parse_from(args.iter().chain(&["--insecure"]));
+        assert!(init_client(&cli).is_ok());
+
+        // 证书文件不存在时报错(而不是忽略后连接失败)
+        let cli = Cli::parse_from(args.iter().chain(&["--ca-cert=/nonexistent/ca.pem"]));
+        let err = init_client(&cli).unwrap_err();
+        assert!(format!("{err:#}").contains("/nonexistent/ca.pem"));
+
+        // 客户端证书和私钥必须同时指定
+        assert!(Cli::try_parse_from(args.iter().chain(&["--client-cert=cert.pem"])).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_version() {
         assert_eq!(Version::from("1.2.3"), Version::from("v1.2.3"));
         assert_eq!(Version::from("v0.9.1") > Version::from("v0.9.0"), true);
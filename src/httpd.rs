@@ -0,0 +1,123 @@
+//! 简单的http服务端: 每个连接处理一个请求(Connection: close), 用于性能测试的模拟服务和webhook服务
+
+use crate::AnyResult;
+use anyhow::bail;
+use std::io::{BufRead, Write};
+
+/// http请求
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    // 请求头名称为小写
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 读取请求: 请求体支持Content-Length和chunked(multipart上传), 超过max_body时失败
+pub fn read_request(reader: &mut impl BufRead, max_body: usize) -> AnyResult<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        bail!("invalid request line: {line}");
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    let chunked = request
+        .header("transfer-encoding")
+        .is_some_and(|value| value.eq_ignore_ascii_case("chunked"));
+    if chunked {
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let size = usize::from_str_radix(line.trim(), 16)?;
+            if size == 0 {
+                // 结尾的空行
+                line.clear();
+                reader.read_line(&mut line)?;
+                break;
+            }
+            let start = request.body.len();
+            if start + size > max_body {
+                bail!("request body too large: > {max_body}");
+            }
+            request.body.resize(start + size, 0);
+            reader.read_exact(&mut request.body[start..])?;
+            let mut crlf = [0u8; 2];
+            reader.read_exact(&mut crlf)?;
+        }
+    } else {
+        let content_length = match request.header("content-length") {
+            Some(value) => value.parse()?,
+            None => 0,
+        };
+        if content_length > max_body {
+            bail!("request body too large: {content_length} > {max_body}");
+        }
+        request.body.resize(content_length, 0);
+        reader.read_exact(&mut request.body)?;
+    }
+    Ok(Some(request))
+}
+
+/// 写入响应: 204没有响应体
+pub fn write_response(
+    writer: &mut impl Write,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> AnyResult<()> {
+    let body = if status == 204 { &[][..] } else { body };
+    write!(
+        writer,
+        "HTTP/1.1 {status} {}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        reason(status),
+        body.len()
+    )?;
+    writer.write_all(body)?;
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Error",
+    }
+}
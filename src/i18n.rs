@@ -0,0 +1,70 @@
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use std::env;
+use std::sync::OnceLock;
+
+/// 日志和同步结果的语言(--lang), 默认按系统区域设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum Lang {
+    /// 中文
+    Zh,
+    /// English
+    En,
+}
+
+impl Lang {
+    /// 按LC_ALL, LC_MESSAGES, LANG判断: zh开头为中文, 其他(包括未设置)为英文
+    pub fn from_locale() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty());
+        Self::parse_locale(locale.as_deref())
+    }
+
+    fn parse_locale(locale: Option<&str>) -> Self {
+        match locale {
+            Some(locale) if locale.to_ascii_lowercase().starts_with("zh") => Lang::Zh,
+            _ => Lang::En,
+        }
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// 设置语言, 只在第一次设置时生效(解析参数后调用)
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+/// 当前语言: 未设置时(如作为库使用)为英文
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or(Lang::En)
+}
+
+/// 按当前语言选择文本: tr("nothing to sync", "没有需要同步的内容")
+pub fn tr(en: &'static str, zh: &'static str) -> &'static str {
+    tr_in(lang(), en, zh)
+}
+
+pub fn tr_in(lang: Lang, en: &'static str, zh: &'static str) -> &'static str {
+    match lang {
+        Lang::En => en,
+        Lang::Zh => zh,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Lang::parse_locale(Some("zh_CN.UTF-8")), Lang::Zh);
+        assert_eq!(Lang::parse_locale(Some("en_US.UTF-8")), Lang::En);
+        assert_eq!(Lang::parse_locale(Some("C")), Lang::En);
+        assert_eq!(Lang::parse_locale(None), Lang::En);
+        assert_eq!(tr_in(Lang::Zh, "synced", "已同步"), "已同步");
+    }
+}
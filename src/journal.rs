@@ -0,0 +1,161 @@
+use crate::model::Cli;
+use crate::{AnyResult, fsutil};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// 同步日志: 记录本次运行已完成的release和已上传的附件
+/// - 每完成一步就写入临时目录, 程序崩溃或Ctrl-C后下次运行可从中断处继续
+/// - 全部同步成功后删除
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    state: JournalState,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JournalState {
+    github: String,
+    gitee: String,
+    completed_releases: BTreeSet<String>,
+    uploaded_assets: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Journal {
+    /// 打开同步日志: 存在上次未完成的日志则继续使用
+    pub fn open(cli: &Cli) -> AnyResult<Self> {
+        let mut dir = cli.work_dir();
+        dir.push(&cli.github_repo);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(".journal-{}.json", cli.mirror_key()));
+
+        let github = format!("{}/{}", cli.github_owner, cli.github_repo);
+        let gitee = cli.mirror().path();
+        let mut state = JournalState {
+            github: github.clone(),
+            gitee: gitee.clone(),
+            ..Default::default()
+        };
+
+        if cli.no_resume {
+            let _ = fs::remove_file(&path);
+        } else if path.exists() {
+            match fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<JournalState>(&content)?))
+            {
+                Ok(last) if last.github == github && last.gitee == gitee => {
+                    info!(
+                        "resume from journal: {}, completed releases: {}",
+                        path.display(),
+                        last.completed_releases.len()
+                    );
+                    state = last;
+                }
+                Ok(_) => warn!("journal repo mismatch, ignore: {}", path.display()),
+                Err(e) => warn!("journal read error, ignore: {}, {e:#}", path.display()),
+            }
+        }
+        Ok(Journal { path, state })
+    }
+
+    pub fn is_release_completed(&self, tag_name: &str) -> bool {
+        self.state.completed_releases.contains(tag_name)
+    }
+
+    pub fn is_asset_uploaded(&self, tag_name: &str, asset_name: &str) -> bool {
+        self.state
+            .uploaded_assets
+            .get(tag_name)
+            .is_some_and(|assets| assets.contains(asset_name))
+    }
+
+    /// 开始同步release的附件: 中断后即使不在下次的同步计划中(如已是目标仓库的最大版本)也继续同步
+    pub fn release_started(&mut self, tag_name: &str) -> AnyResult<()> {
+        if self.state.uploaded_assets.contains_key(tag_name) {
+            return Ok(());
+        }
+        self.state
+            .uploaded_assets
+            .insert(tag_name.to_string(), BTreeSet::new());
+        self.save()
+    }
+
+    /// 上次运行中断(取消或失败)时正在同步附件的release
+    pub fn is_release_interrupted(&self, tag_name: &str) -> bool {
+        self.state.uploaded_assets.contains_key(tag_name)
+    }
+
+    pub fn asset_uploaded(&mut self, tag_name: &str, asset_name: &str) -> AnyResult<()> {
+        self.state
+            .uploaded_assets
+            .entry(tag_name.to_string())
+            .or_default()
+            .insert(asset_name.to_string());
+        self.save()
+    }
+
+    pub fn release_completed(&mut self, tag_name: &str) -> AnyResult<()> {
+        self.state.uploaded_assets.remove(tag_name);
+        self.state.completed_releases.insert(tag_name.to_string());
+        self.save()
+    }
+
+    /// release被回滚(删除)后, 已上传的附件记录失效
+    pub fn release_reset(&mut self, tag_name: &str) -> AnyResult<()> {
+        self.state.uploaded_assets.remove(tag_name);
+        self.state.completed_releases.remove(tag_name);
+        self.save()
+    }
+
+    /// 全部同步成功: 删除同步日志
+    pub fn finish(self) -> AnyResult<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> AnyResult<()> {
+        let content = serde_json::to_string_pretty(&self.state)?;
+        fsutil::write_file(&self.path, content, true)
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::env;
+
+    #[test]
+    fn test_journal_resume() -> AnyResult<()> {
+        let repo = format!("journal-test-{}", std::process::id());
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=o",
+            &format!("--github-repo={repo}"),
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+        ]);
+
+        let mut journal = Journal::open(&cli)?;
+        journal.asset_uploaded("v0.9.4", "a.zip")?;
+        journal.release_completed("v0.9.3")?;
+
+        // 模拟中断后再次运行
+        let journal = Journal::open(&cli)?;
+        assert!(journal.is_release_completed("v0.9.3"));
+        assert!(journal.is_asset_uploaded("v0.9.4", "a.zip"));
+        assert!(!journal.is_release_completed("v0.9.4"));
+        journal.finish()?;
+
+        let journal = Journal::open(&cli)?;
+        assert!(!journal.is_release_completed("v0.9.3"));
+        fs::remove_dir_all(env::temp_dir().join(&repo))?;
+        Ok(())
+    }
+}
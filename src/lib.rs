@@ -1,54 +1,496 @@
 extern crate core;
 
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+compile_error!("either the rustls or native-tls feature is required");
+
+mod appcast;
+mod archive;
+mod assets;
+pub mod auth;
+pub mod bench;
+mod body;
+pub mod bundle;
+mod cache;
+pub mod cancel;
+mod cos;
+pub mod error;
+pub mod events;
+mod fsutil;
+mod github_app;
+mod gitlab;
+mod gitsync;
 mod http;
+mod httpd;
+pub mod i18n;
+pub mod journal;
+mod listing;
+mod local;
+mod lock;
+pub mod logfile;
 pub mod model;
+mod notes;
+mod notify;
+mod object;
+mod options;
+mod oss;
+mod otel;
+pub mod progress;
+mod provenance;
+pub mod provider;
+pub mod redact;
+pub mod report;
+pub mod rewrite;
+mod s3;
+pub mod schedule;
+pub mod serve;
+mod sftp;
+pub mod stats;
+pub mod status;
+mod store;
+mod trace;
+mod transfer;
+pub mod transport;
+pub mod tui;
+pub mod units;
 
-use crate::model::{Assert, Cli, Release};
+use crate::assets::AssetCache;
+use crate::cache::HttpCache;
+use crate::cancel::{Cancellation, CancellationToken};
+use crate::error::{AssetError, Diverged, SyncInProgress};
+use crate::events::{Events, SyncEvent};
+use crate::http::Conditional;
+use crate::journal::Journal;
+use crate::lock::SyncLock;
+use crate::model::{
+    Assert, AssetConflict, Cli, Forge, IdRange, QuotaExceeded, Release, ReleaseSelector,
+    RollbackMode, SyncConfig,
+};
+use crate::provenance::Provenance;
+use crate::provider::{ReleaseSource, ReleaseTarget};
+use crate::report::{ReleaseAction, SyncReport, SyncedRelease};
+use crate::store::CacheStore;
+use crate::tui::ReleaseState;
+use crate::units::ByteSize;
+use anyhow::{Context, bail};
 use log::{error, info, warn};
-use reqwest::blocking::Client;
 use std::cmp::Ordering::Equal;
+use std::collections::BTreeSet;
+use std::fs;
 use std::path::{Path, PathBuf};
-use std::{env, fs};
+use std::time::{Duration, Instant};
 use version_compare::{Cmp, compare};
 
 const GITHUB_API_URL: &str = "https://api.github.com/repos";
-const GITEE_API_URL: &str = "https://gitee.com/api/v5/repos";
+const GITHUB_UPLOADS_URL: &str = "https://uploads.github.com/repos";
+// gitee接口路径(企业版/私有化部署相同), 完整地址见--gitee-api-url
+const GITEE_API_PATH: &str = "/api/v5";
+// 超过该时间没有变化的临时目录视为之前崩溃的运行遗留
+const STALE_TMP_DIR_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+// 本工具创建的临时目录中的标记文件, 清理遗留目录时只删除带标记的目录
+const TMP_DIR_MARKER: &str = ".release2gitee";
 pub type AnyResult<T> = anyhow::Result<T>;
+pub use crate::error::Error;
+pub use crate::options::{SyncOptions, SyncOptionsBuilder};
+
+/// 同步Github的Releases到Gitee(或按--direction反向同步), 返回同步结果
+/// - options: SyncOptions::builder构建, 命令行程序由Cli转换
+/// - cancel: 可在其他线程取消, 在release之间、附件分块下载上传之间检查
+/// - 失败时按类别返回Error(如速率限制、认证失败), 可匹配处理
+pub fn sync_github_releases_to_gitee(
+    options: &SyncOptions,
+    cancel: &CancellationToken,
+) -> Result<SyncReport, Error> {
+    sync_with_report(&options.cli, &options.config, cancel).map_err(Error::from)
+}
+
+/// 同步并在结束后通知: webhook服务和并行同步多个目标平台时使用
+pub(crate) fn sync_with_report(
+    cli: &Cli,
+    config: &SyncConfig,
+    cancel: &CancellationToken,
+) -> AnyResult<SyncReport> {
+    // 库的调用方不经过resolve_tokens: 同步前登记token和密钥
+    cli.register_secrets();
+    cli.validate()?;
+    // 链路追踪: 每次同步一个span, 结束后导出
+    otel::init(cli);
+    let started = Instant::now();
+    let mut span = otel::span("sync");
+    span.set("source", cli.source());
+    span.set("target", cli.target());
+    let mut report = SyncReport {
+        events: Events::new(config.events.clone()),
+        ..Default::default()
+    };
+    let result = sync_repo(cli, config, cancel, &mut report);
+    // 释放事件的发送端: 接收端的迭代随同步结束
+    report.events = Events::default();
+    span.record(&result);
+    drop(span);
+    otel::flush();
+    // 同步结束后通知(并行同步多个目标平台时只在汇总后通知一次)
+    if !cli.fan_out {
+        notify::notify(cli, &report, result.as_ref().err(), started.elapsed());
+    }
+    result.map(|()| report)
+}
+
+fn sync_repo(
+    cli: &Cli,
+    config: &SyncConfig,
+    cancel: &CancellationToken,
+    report: &mut SyncReport,
+) -> AnyResult<()> {
+    let cli = &github_app::authenticate(cli)?;
+    if cli.target.len() > 1 {
+        return sync_fan_out(cli, config, cancel, report);
+    }
+
+    // 同一个gitee仓库同时只允许一个同步进程(如定时任务与手动执行重叠)
+    let Some(_lock) = SyncLock::acquire(cli)? else {
+        return Err(SyncInProgress {
+            gitee_repo: cli.mirror().path(),
+        }
+        .into());
+    };
+
+    let cancellation = Cancellation::new(cancel, config.overall_timeout);
+    let result = sync(cli, config, &cancellation, report);
+    if let Err(e) = &result
+        && report.failed.is_empty()
+    {
+        // 非release维度的失败(如查询releases信息失败)
+        report.failed("-", e);
+        report.events.emit(SyncEvent::Error {
+            target: cli.target().to_string(),
+            error: report.failed[0].error.clone(),
+        });
+    }
+
+    // GitHub Actions中运行时输出Step Summary
+    let title = format!("release2gitee: {} -> {}", cli.source(), cli.target());
+    report.write_github_step_summary(&title);
+    result
+}
 
-pub fn sync_github_releases_to_gitee(cli: &Cli) -> AnyResult<()> {
+fn sync(
+    cli: &Cli,
+    config: &SyncConfig,
+    cancellation: &Cancellation,
+    report: &mut SyncReport,
+) -> AnyResult<()> {
     // http请求较多，复用client
-    let client = &http::init_client()?;
+    let client = &http::init_client(cli)?;
+    // 先同步代码: release引用的tag已存在于目标仓库
+    if cli.sync_code {
+        gitsync::sync_code(cli, cancellation)?;
+    }
+    let source = provider::source(cli, client, config);
+    let target = provider::target(cli, client, config)?;
+    let store = store::open(cli, client)?;
+    let result = sync_releases(
+        cli,
+        source.as_ref(),
+        target.as_ref(),
+        store.as_deref(),
+        cancellation,
+        report,
+    );
+    report.mirrors = source.mirror_stats();
+    result
+}
 
-    // 1. 获取github的releases信息: 新的在前面
-    let github_releases = &github_releases(client, cli)?;
+/// 并行同步到多个目标平台: 每个目标平台单独加锁、记录同步日志和处理失败
+/// - 一个目标平台失败(如容量耗尽)不影响其他目标平台, 全部结束后返回第一个失败的错误
+fn sync_fan_out(
+    cli: &Cli,
+    config: &SyncConfig,
+    cancel: &CancellationToken,
+    report: &mut SyncReport,
+) -> AnyResult<()> {
+    let clis = cli.fan_out();
+    let context = otel::current();
+    let results = std::thread::scope(|scope| {
+        let handles = clis
+            .iter()
+            .map(|cli| {
+                scope.spawn(move || {
+                    let _attached = otel::attach(context);
+                    sync_with_report(cli, config, cancel)
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| bail!("sync thread panicked"))
+            })
+            .collect::<Vec<_>>()
+    });
 
-    // 2. 获取gitee的releases信息: 新的在前面
-    let gitee_releases = &gitee_releases(client, cli)?;
+    let mut first_error = None;
+    for (cli, result) in clis.iter().zip(results) {
+        let target = cli.target().to_string();
+        match result {
+            Ok(target_report) => {
+                info!("target sync success: {target}, {target_report}");
+                report.merge(&target, target_report);
+            }
+            Err(e) => {
+                error!("target sync failed: {target}, {e:#}");
+                report.failed(&format!("{target}: -"), &e);
+                first_error.get_or_insert(e.context(format!("target sync failed: {target}")));
+            }
+        }
+    }
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// 同步流程: 源仓库和目标仓库通过ReleaseSource/ReleaseTarget访问
+pub fn sync_releases(
+    cli: &Cli,
+    source: &dyn ReleaseSource,
+    target: &dyn ReleaseTarget,
+    store: Option<&dyn CacheStore>,
+    cancellation: &Cancellation,
+    report: &mut SyncReport,
+) -> AnyResult<()> {
+    // 1. 获取源仓库(github)的releases信息: 新的在前面 (与上次同步成功时相比没有变化则直接结束)
+    let mut http_cache = HttpCache::open(cli);
+    let mut provenance = Provenance::open(cli);
+    let Some(all_source_releases) = &source_releases(source, cli, &mut http_cache, report)? else {
+        info!("source releases not modified since last sync, skip");
+        return propagate_deletes(source, target, cli, &mut provenance, report);
+    };
 
-    // 3. 计算哪些版本需要同步: ①保留前几个 ②比gitee最新版本小的忽略同步
-    let github_releases = filter_github_releases(cli, &gitee_releases, github_releases);
+    // 2. 获取目标仓库(gitee)的releases信息: 新的在前面
+    let target_releases = &target_releases(target)?;
 
-    // 4. 循环release进行对比并同步: 倒序处理, 先同步旧的版本
-    for github_release in github_releases.iter().rev() {
-        let gitee_release = gitee_releases
+    // 3. 计算哪些版本需要同步: ①保留前几个 ②比gitee最新版本小的忽略同步 (或按指定的id范围)
+    // 上次中断时正在同步附件的release继续同步(已在目标仓库中创建, 不会再被计划)
+    let mut journal = Journal::open(cli)?;
+    let planned = plan_source_releases(cli, target_releases, all_source_releases);
+    let source_releases = all_source_releases
+        .iter()
+        .filter(|release| {
+            journal.is_release_interrupted(&release.tag_name)
+                || planned.iter().any(|r| r.tag_name == release.tag_name)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let target_name = cli.target().to_string();
+    for release in all_source_releases {
+        if !source_releases
             .iter()
-            .find(|gr| gr.tag_name == github_release.tag_name);
-        sync_release(client, cli, github_release, gitee_release)?;
+            .any(|r| r.tag_name == release.tag_name)
+        {
+            report.skipped(&release.tag_name, "not in sync plan");
+            report.events.emit(SyncEvent::ReleaseSkipped {
+                target: target_name.clone(),
+                tag_name: release.tag_name.clone(),
+                reason: "not in sync plan".to_string(),
+            });
+        }
+    }
+    let source_releases = fit_target_quota(cli, target_releases, source_releases, report);
+
+    // 4. 循环release进行对比并同步: 倒序处理, 先同步旧的版本 (跳过上次中断前已完成的release)
+    clean_stale_tmp_dirs(cli);
+    let mut first_error = None;
+    for source_release in source_releases.iter().rev() {
+        let tag_name = &source_release.tag_name;
+        cancellation.check()?;
+        if journal.is_release_completed(tag_name) {
+            info!("release completed in last run, skip: {tag_name}");
+            report.skipped(tag_name, "completed in last run");
+            report.events.emit(SyncEvent::ReleaseSkipped {
+                target: target_name.clone(),
+                tag_name: tag_name.clone(),
+                reason: "completed in last run".to_string(),
+            });
+            continue;
+        }
+
+        let target_release = target_releases.iter().find(|gr| &gr.tag_name == tag_name);
+        // 上次同步成功后github没有变化, 且gitee上仍然存在: 无需再对比
+        if !cli.no_cache && target_release.is_some() && provenance.is_unchanged(source_release) {
+            info!("source release unchanged since last sync, skip: {tag_name}");
+            tui::release_state(
+                &target_name,
+                tag_name,
+                ReleaseState::Synced(ReleaseAction::Unchanged),
+            );
+            report.synced(SyncedRelease {
+                tag_name: tag_name.clone(),
+                action: ReleaseAction::Unchanged,
+                uploaded_assets: Vec::new(),
+                uploaded_bytes: 0,
+                missing_assets: Vec::new(),
+                warnings: Vec::new(),
+            });
+            report.events.emit(SyncEvent::ReleaseSynced {
+                target: target_name.clone(),
+                tag_name: tag_name.clone(),
+                action: ReleaseAction::Unchanged,
+                uploaded_assets: 0,
+            });
+            continue;
+        }
+        tui::release_state(&target_name, tag_name, ReleaseState::Syncing);
+        report.events.emit(SyncEvent::ReleaseStarted {
+            target: target_name.clone(),
+            tag_name: tag_name.clone(),
+        });
+        let mut span = otel::span("release");
+        span.set("release.tag", tag_name);
+        let result = sync_release(
+            cli,
+            source,
+            target,
+            source_release,
+            target_release,
+            &mut journal,
+            store,
+            cancellation,
+            &report.events,
+        );
+        span.record(&result);
+        if let Ok(synced) = &result {
+            span.set("release.action", synced.action);
+            span.set_int(
+                "release.uploaded_assets",
+                synced.uploaded_assets.len() as u64,
+            );
+        }
+        drop(span);
+        match result {
+            Ok(synced) => {
+                journal.release_completed(tag_name)?;
+                // 部分同步的release不记录, 下次运行时重新对比
+                if synced.missing_assets.is_empty() {
+                    provenance.record(source_release);
+                    clean_tmp_dir(cli, source_release);
+                }
+                tui::release_state(&target_name, tag_name, ReleaseState::Synced(synced.action));
+                report.events.emit(SyncEvent::ReleaseSynced {
+                    target: target_name.clone(),
+                    tag_name: tag_name.clone(),
+                    action: synced.action,
+                    uploaded_assets: synced.uploaded_assets.len(),
+                });
+                report.synced(synced)
+            }
+            Err(e) => {
+                tui::release_state(&target_name, tag_name, ReleaseState::Failed);
+                report.failed(&source_release.tag_name, &e);
+                report.events.emit(SyncEvent::ReleaseFailed {
+                    target: target_name.clone(),
+                    tag_name: tag_name.clone(),
+                    error: report
+                        .failed
+                        .last()
+                        .map(|f| f.error.clone())
+                        .unwrap_or_default(),
+                });
+                if !cli.continue_on_error || cancellation.check().is_err() {
+                    return Err(e);
+                }
+                // 记录错误后继续同步其他release
+                error!("sync release error: {}, {e:#}", source_release.tag_name);
+                first_error.get_or_insert(e);
+            }
+        }
     }
 
-    // 5. 清理gitee中旧的release(免费的容量空间有限)
-    clean_oldest_gitee_releases(client, cli)?;
+    provenance.save()?;
+    if cli.fix_latest_release && first_error.is_none() {
+        fix_latest_release(
+            cli,
+            source,
+            target,
+            all_source_releases,
+            &mut journal,
+            store,
+            cancellation,
+            report,
+        )?;
+    }
+
+    // 5. 清理gitee中旧的release(免费的容量空间有限), 删除github上已删除的release
+    clean_oldest_target_releases(target, cli, report)?;
+    if http_cache.is_stale() {
+        // 源仓库的releases列表不可信, 不同步删除
+        warn!("source releases are stale, skip propagate deletes");
+        record_warning(
+            report,
+            &target_name,
+            "source releases are stale, deletes not propagated".to_string(),
+        );
+    } else {
+        propagate_deletes(source, target, cli, &mut provenance, report)?;
+    }
+
+    // 6. 存在失败的release时, 汇总失败的tag后返回错误(退出码以第一个错误为准)
+    if let Some(e) = first_error {
+        let tag_names = report
+            .failed
+            .iter()
+            .map(|failed| failed.tag_name.clone())
+            .collect::<Vec<_>>();
+        error!("sync failed tags: {}", tag_names.join(", "));
+        return Err(e.context(format!("sync failed tags: {}", tag_names.join(", "))));
+    }
+
+    // 7. 全部同步成功, 删除同步日志, 记录github releases的ETag
+    journal.finish()?;
+    http_cache.commit()?;
     Ok(())
 }
 
-/// 获取Github仓库Releases信息
-pub fn github_releases(client: &Client, cli: &Cli) -> AnyResult<Vec<Release>> {
-    let url = format!(
-        "{}/{}/{}/releases?per_page={}&page=1",
-        GITHUB_API_URL, cli.github_owner, cli.github_repo, cli.github_latest_release_count
-    );
-    let result = http::get(client, &url, cli.github_token.clone())?;
-    let mut releases: Vec<Release> = serde_json::from_str(&result)?;
+/// 获取源仓库Releases信息: 最新N个的方式使用ETag条件请求, 没有变化时返回None
+fn source_releases(
+    source: &dyn ReleaseSource,
+    cli: &Cli,
+    http_cache: &mut HttpCache,
+    report: &mut SyncReport,
+) -> AnyResult<Option<Vec<Release>>> {
+    let per_page = cli.github_latest_release_count;
+    let mut releases: Vec<Release> = match &cli.release_id_range {
+        None => {
+            let url = provider::releases_url(source.endpoint(), per_page, 1);
+            let etag = http_cache.etag(&url);
+            match source.fetch_releases(per_page, 1, etag) {
+                Ok(Conditional::NotModified) => {
+                    http_cache.stage_not_modified();
+                    return Ok(None);
+                }
+                Ok(Conditional::Modified { text, etag }) => {
+                    http_cache.stage(&url, etag, &text);
+                    source.parse_releases(&text)?
+                }
+                // 源仓库暂时不可访问: 使用最近缓存的releases列表继续同步(已下载到本地的附件仍可上传)
+                Err(e) => match cli.allow_stale_source {
+                    Some(max_age) if http::is_unreachable(&e) => {
+                        let Some(text) = http_cache.stale_text(&url, max_age.as_duration()) else {
+                            return Err(e);
+                        };
+                        let warning =
+                            format!("source releases fetch error, cached releases used: {e:#}");
+                        warn!("{warning}");
+                        record_warning(report, &cli.target().to_string(), warning);
+                        source.parse_releases(&text)?
+                    }
+                    _ => return Err(e),
+                },
+            }
+        }
+        Some(range) => source_releases_by_id_range(source, range)?,
+    };
     releases.sort_by_key(|r| r.id);
     releases.reverse(); // 倒序, 这样保证同步到gitee时，先处理旧的，再处理新的
 
@@ -62,182 +504,699 @@ pub fn github_releases(client: &Client, cli: &Cli) -> AnyResult<Vec<Release>> {
     // 记录日志
     let tag_names = get_tags(&releases);
     info!(
-        "github releases fetch {}: {}",
+        "{} releases fetch {}: {}",
+        source.endpoint().forge,
         releases.len(),
         tag_names.join(", ")
     );
-    Ok(releases)
+    Ok(Some(releases))
 }
 
-/// 获取Gitee仓库Releases信息
-pub fn gitee_releases(client: &Client, cli: &Cli) -> AnyResult<Vec<Release>> {
-    let url = format!(
-        "{}/{}/{}/releases?per_page=100&page=1", // 最近100个
-        GITEE_API_URL, cli.gitee_owner, cli.gitee_repo
-    );
-    let result = http::get(client, &url, Some(cli.gitee_token.clone()))?;
-    let mut releases: Vec<Release> = serde_json::from_str(&result)?;
+/// 按id范围获取源仓库Releases: 分页查询直到超出范围的起始id
+fn source_releases_by_id_range(
+    source: &dyn ReleaseSource,
+    range: &IdRange,
+) -> AnyResult<Vec<Release>> {
+    const PER_PAGE: usize = 100;
+    let mut releases = Vec::new();
+    for page in 1.. {
+        let page_releases = match source.fetch_releases(PER_PAGE, page, None)? {
+            Conditional::Modified { text, .. } => source.parse_releases(&text)?,
+            Conditional::NotModified => bail!("unexpected 304 without etag"),
+        };
+        let page_len = page_releases.len();
+        let min_id = page_releases.iter().map(|r| r.id).min();
+        releases.extend(page_releases);
+
+        // 最后一页, 或者已经查询到比起始id更早的release
+        let reach_start =
+            matches!((range.start, min_id), (Some(start), Some(min_id)) if min_id < start);
+        if page_len < PER_PAGE || reach_start {
+            break;
+        }
+    }
+    Ok(select_releases_by_id_range(range, &releases))
+}
+
+/// 获取目标仓库Releases信息
+fn target_releases(target: &dyn ReleaseTarget) -> AnyResult<Vec<Release>> {
+    let mut releases = target.releases()?;
     releases.sort_by_key(|r| r.id);
     releases.reverse();
 
     // 记录日志
     let tag_names = get_tags(&releases);
     info!(
-        "gitee releases fetch {}: {}",
+        "{} releases fetch {}: {}",
+        target.endpoint().forge,
         releases.len(),
         tag_names.join(", ")
     );
     Ok(releases)
 }
 
+/// 修正最新版本(--fix-latest-release): 本次新建了release时, 版本号最大的release应作为最新版本
+/// - 支持设置最新版本的目标(github)直接设置, 不删除release
+/// - 按创建顺序展示最新版本的目标(gitee, gitea)没有设置的接口: --allow-recreate-latest时删除后重新创建版本号最大的release
+/// - 同一个tag不能有两个release, 无法先创建再删除: 删除前先下载全部附件, 缩短没有该release的时间
+#[allow(clippy::too_many_arguments)]
+fn fix_latest_release(
+    cli: &Cli,
+    source: &dyn ReleaseSource,
+    target: &dyn ReleaseTarget,
+    source_releases: &[Release],
+    journal: &mut Journal,
+    store: Option<&dyn CacheStore>,
+    cancellation: &Cancellation,
+    report: &mut SyncReport,
+) -> AnyResult<()> {
+    let created = report
+        .synced
+        .iter()
+        .any(|synced| synced.action == ReleaseAction::Created);
+    if !created {
+        return Ok(());
+    }
+    let releases = target_releases(target)?;
+    let Some(highest) = latest_mismatch(&releases) else {
+        return Ok(());
+    };
+    let tag_name = &highest.tag_name;
+    if target.mark_latest(highest)? {
+        info!("target latest release marked: {tag_name}");
+        return Ok(());
+    }
+    if !target.latest_by_creation() {
+        return Ok(());
+    }
+    if !cli.allow_recreate_latest {
+        warn!(
+            "target latest release not fixed, recreate requires --allow-recreate-latest: {tag_name}"
+        );
+        record_warning(
+            report,
+            &target.endpoint().to_string(),
+            format!("latest release not recreated without --allow-recreate-latest: {tag_name}"),
+        );
+        return Ok(());
+    }
+    let Some(source_release) = source_releases.iter().find(|r| &r.tag_name == tag_name) else {
+        warn!("latest release not found in source releases, skip recreate: {tag_name}");
+        record_warning(
+            report,
+            &target.endpoint().to_string(),
+            format!("latest release not recreated: {tag_name}"),
+        );
+        return Ok(());
+    };
+
+    // 删除前先下载附件: 源仓库不可用时不删除
+    download_release_asserts(
+        source,
+        cli,
+        source_release,
+        &source_release.assets,
+        store,
+        cancellation,
+        &report.events,
+    )
+    .with_context(|| format!("latest release assets download error, not recreated: {tag_name}"))?;
+
+    // 重新创建后成为最新创建的release
+    cancellation.check()?;
+    warn!("target latest release delete and recreate: {tag_name}");
+    target.delete_release(highest)?;
+    journal.release_reset(tag_name)?;
+    sync_release(
+        cli,
+        source,
+        target,
+        source_release,
+        None,
+        journal,
+        store,
+        cancellation,
+        &report.events,
+    )
+    .with_context(|| {
+        format!("target latest release recreate error, the next run creates it again: {tag_name}")
+    })?;
+    journal.release_completed(tag_name)?;
+
+    if latest_mismatch(&target_releases(target)?).is_some() {
+        warn!("target still presents an older release as latest: {tag_name}");
+        record_warning(
+            report,
+            &target.endpoint().to_string(),
+            format!("target presents an older release as latest: {tag_name}"),
+        );
+    }
+    Ok(())
+}
+
+/// 记录警告并发送警告事件
+fn record_warning(report: &mut SyncReport, target: &str, message: String) {
+    report.events.emit(SyncEvent::Warning {
+        target: target.to_string(),
+        message: message.clone(),
+    });
+    report.warning(message);
+}
+
+/// 目标仓库展示的最新版本(最新创建的正式版本)不是版本号最大的正式版本时, 返回版本号最大的release
+fn latest_mismatch(target_releases: &[Release]) -> Option<&Release> {
+    let presented = target_releases.iter().find(|r| !r.prerelease)?;
+    let highest = target_releases
+        .iter()
+        .filter(|r| !r.prerelease)
+        .max_by(|a, b| {
+            compare(&a.tag_name, &b.tag_name)
+                .unwrap_or(Cmp::Eq)
+                .ord()
+                .unwrap_or(Equal)
+        })?;
+    (compare(&highest.tag_name, &presented.tag_name) == Ok(Cmp::Gt)).then_some(highest)
+}
+
 /// 日志显示tag名称列表
-fn get_tags(releases: &Vec<Release>) -> Vec<String> {
+fn get_tags(releases: &[Release]) -> Vec<String> {
     releases
         .iter()
         .map(|release| release.tag_name.clone())
         .collect::<Vec<_>>()
 }
 
-/// 清理Gitee仓库最老的Releases: 查询最近100个，仅保留最新的N个
-fn clean_oldest_gitee_releases(client: &Client, cli: &Cli) -> AnyResult<()> {
-    info!("clean gitee releases");
+/// 清理目标仓库最老的Releases: 查询最近100个，仅保留最新的N个
+fn clean_oldest_target_releases(
+    target: &dyn ReleaseTarget,
+    cli: &Cli,
+    report: &mut SyncReport,
+) -> AnyResult<()> {
+    info!("clean {} releases", target.endpoint().forge);
     // 重新查询后清理
-    let gitee_releases = gitee_releases(client, cli)?;
+    let target_releases = target_releases(target)?;
 
     // 新同步的个数: github有，gitee没有的tag
     let count = cli.gitee_retain_release_count;
-    if count >= gitee_releases.len() {
-        info!("gitee releases retain count: {count}, no need to clean");
+    if count >= target_releases.len() {
+        info!("target releases retain count: {count}, no need to clean");
     } else {
-        let clean_count = gitee_releases.len() - count;
+        let clean_count = target_releases.len() - count;
         info!(
-            "gitee releases: {}, need clean count: {}",
-            gitee_releases.len(),
+            "target releases: {}, need clean count: {}",
+            target_releases.len(),
             clean_count
         );
 
         let skip_count = cli.gitee_retain_release_count;
-        for release in gitee_releases.iter().skip(skip_count) {
-            gitee_release_delete(client, cli, release.id)?;
-            info!("gitee release delete success: {}", release.tag_name);
+        for release in target_releases.iter().skip(skip_count) {
+            target.delete_release(release)?;
+            info!("target release delete success: {}", release.tag_name);
+            report.cleaned(&release.tag_name);
+            report.events.emit(SyncEvent::ReleaseCleaned {
+                target: target.endpoint().to_string(),
+                tag_name: release.tag_name.clone(),
+            });
         }
     }
 
     Ok(())
 }
 
+/// 同步删除: 曾经同步过但github上已删除的release, 超过宽限期后从gitee删除
+fn propagate_deletes(
+    source: &dyn ReleaseSource,
+    target: &dyn ReleaseTarget,
+    cli: &Cli,
+    provenance: &mut Provenance,
+    report: &mut SyncReport,
+) -> AnyResult<()> {
+    if !cli.propagate_deletes || provenance.is_empty() {
+        return Ok(());
+    }
+
+    // 查询github所有的release
+    info!("propagate source release deletions");
+    let all = IdRange {
+        start: None,
+        end: None,
+    };
+    let source_ids = source_releases_by_id_range(source, &all)?
+        .iter()
+        .map(|release| release.id)
+        .collect::<BTreeSet<_>>();
+    if source_ids.is_empty() {
+        // 防止接口异常时误删所有release
+        warn!("source releases is empty, skip propagate deletes");
+        return Ok(());
+    }
+
+    let grace = cli.propagate_deletes_grace.as_duration();
+    let expired = provenance.expired_deletions(&source_ids, provenance::now_secs(), grace);
+    if !expired.is_empty() {
+        let target_releases = target_releases(target)?;
+        for (tag_name, entry) in expired {
+            match target_releases.iter().find(|gr| gr.tag_name == tag_name) {
+                Some(target_release) => {
+                    target.delete_release(target_release)?;
+                    provenance.audit_deletion(&tag_name, &entry, target_release.id);
+                    warn!("target release delete success (deleted on source): {tag_name}");
+                    report.deleted(&tag_name);
+                    report.events.emit(SyncEvent::ReleaseDeleted {
+                        target: target.endpoint().to_string(),
+                        tag_name: tag_name.clone(),
+                    });
+                }
+                None => info!("target release not exists, skip delete: {tag_name}"),
+            }
+            provenance.forget(&tag_name);
+        }
+    }
+    provenance.save()
+}
+
+/// 计算需要同步的Github Releases(同步计划): 按选择方式筛选
+pub fn plan_source_releases(
+    cli: &Cli,
+    target_releases: &[Release],
+    source_releases: &[Release],
+) -> Vec<Release> {
+    match cli.release_selector() {
+        ReleaseSelector::Latest => filter_source_releases(cli, target_releases, source_releases),
+        ReleaseSelector::IdRange(range) => {
+            let releases = select_releases_by_id_range(&range, source_releases);
+            info!(
+                "github releases in id range {range}: {}",
+                get_tags(&releases).join(", ")
+            );
+            releases
+        }
+    }
+}
+
+/// 目标仓库的附件容量(--target-quota): 已用容量为目标仓库releases的附件大小之和
+/// 总容量无法查询(Gitee没有查询附件容量的接口), 只能由--target-quota指定
+/// 待上传的附件(目标仓库中没有的同名附件)超过剩余容量时警告, 或从最旧的release开始移出同步计划
+fn fit_target_quota(
+    cli: &Cli,
+    target_releases: &[Release],
+    mut source_releases: Vec<Release>,
+    report: &mut SyncReport,
+) -> Vec<Release> {
+    let Some(quota) = cli.target_quota else {
+        return source_releases;
+    };
+    let used: u64 = target_releases
+        .iter()
+        .flat_map(|release| &release.assets)
+        .filter_map(|asset| asset.size)
+        .sum();
+    let remaining = quota.as_u64().saturating_sub(used);
+    let pending = |release: &Release| -> u64 {
+        let target_release = target_releases
+            .iter()
+            .find(|target_release| target_release.tag_name == release.tag_name);
+        release
+            .assets
+            .iter()
+            .filter(|asset| {
+                target_release.is_none_or(|target_release| {
+                    !target_release.assets.iter().any(|a| a.name == asset.name)
+                })
+            })
+            .filter_map(|asset| asset.size)
+            .sum()
+    };
+    let mut total: u64 = source_releases.iter().map(pending).sum();
+    if total <= remaining {
+        return source_releases;
+    }
+    let warning = format!(
+        "pending uploads exceed target quota: {} > {} remaining (used {} of {quota})",
+        ByteSize(total),
+        ByteSize(remaining),
+        ByteSize(used)
+    );
+    warn!("{warning}");
+    record_warning(report, &cli.target().to_string(), warning);
+    if cli.on_quota_exceeded == QuotaExceeded::Trim {
+        // 同步计划中新的在前面, 先移出旧的版本
+        while total > remaining
+            && let Some(release) = source_releases.pop()
+        {
+            total -= pending(&release);
+            warn!("release trimmed for target quota: {}", release.tag_name);
+            report.skipped(&release.tag_name, "trimmed for target quota");
+            report.events.emit(SyncEvent::ReleaseSkipped {
+                target: cli.target().to_string(),
+                tag_name: release.tag_name.clone(),
+                reason: "trimmed for target quota".to_string(),
+            });
+        }
+    }
+    source_releases
+}
+
+/// 按id范围选择Release(包含两端), 用于精确补齐历史版本
+pub fn select_releases_by_id_range(range: &IdRange, releases: &[Release]) -> Vec<Release> {
+    releases
+        .iter()
+        .filter(|release| range.contains(release.id))
+        .cloned()
+        .collect()
+}
+
 /// 过滤Github仓库Release: 仅保留最新的N个, 且过滤掉版本小的
-fn filter_github_releases(
+fn filter_source_releases(
     cli: &Cli,
-    gitee_releases: &Vec<Release>,
-    github_releases: &Vec<Release>,
+    target_releases: &[Release],
+    source_releases: &[Release],
 ) -> Vec<Release> {
-    let mut retain_github_releases = github_releases.clone();
+    let mut retain_source_releases = source_releases.to_vec();
 
     // 仅保留最新的N个用于同步
-    if cli.gitee_retain_release_count > retain_github_releases.len() {
-        retain_github_releases = retain_github_releases
+    if cli.gitee_retain_release_count > retain_source_releases.len() {
+        retain_source_releases = retain_source_releases
             .into_iter()
             .take(cli.gitee_retain_release_count)
             .collect();
     }
 
     // 计算gitee中最大的版本并输出（以tag_name为依据, version-compare的方法）
-    if cli.ignore_lt_gitee_max_version && !gitee_releases.is_empty() {
+    if cli.ignore_lt_gitee_max_version && !target_releases.is_empty() {
         // 找到Gitee中版本最大的tag
-        if let Some(max_gitee_tag) = gitee_releases
+        if let Some(max_target_tag) = target_releases
             .iter()
             .map(|release| &release.tag_name)
-            .max_by(|a, b| compare(&a, &b).unwrap_or(Cmp::Eq).ord().unwrap_or(Equal))
+            .max_by(|a, b| compare(a, b).unwrap_or(Cmp::Eq).ord().unwrap_or(Equal))
         {
-            info!("gitee max_tag_name: {}", max_gitee_tag);
+            info!("target max_tag_name: {}", max_target_tag);
 
             // 过滤github中版本小的，并打印日志
-            retain_github_releases = retain_github_releases
-                .into_iter()
-                .filter(|release| {
-                    match compare(&max_gitee_tag, &release.tag_name) {
-                        Ok(ord) => {
-                            if ord == Cmp::Gt || ord == Cmp::Eq {
-                                info!(
-                                    "github tag_name: {} <= {}, ignore sync",
-                                    release.tag_name, max_gitee_tag
-                                );
-                                false
-                            } else {
-                                true
-                            }
-                        }
-                        Err(_) => {
-                            // 如果版本号比较失败，保留该发布（以防无法比较的情况）
-                            warn!(
-                                "compare version error: {} and {}",
-                                release.tag_name, max_gitee_tag
+            retain_source_releases.retain(|release| {
+                match compare(max_target_tag, &release.tag_name) {
+                    Ok(ord) => {
+                        if ord == Cmp::Gt || ord == Cmp::Eq {
+                            info!(
+                                "source tag_name: {} <= {}, ignore sync",
+                                release.tag_name, max_target_tag
                             );
+                            false
+                        } else {
                             true
                         }
                     }
-                })
-                .collect();
+                    Err(_) => {
+                        // 如果版本号比较失败，保留该发布（以防无法比较的情况）
+                        warn!(
+                            "compare version error: {} and {}",
+                            release.tag_name, max_target_tag
+                        );
+                        true
+                    }
+                }
+            });
         }
     }
 
     info!(
-        "github releases retain count: {}",
-        retain_github_releases.len()
+        "source releases retain count: {}",
+        retain_source_releases.len()
     );
-    retain_github_releases
+    retain_source_releases
 }
 
-/// 同步Gitee仓库Release
+/// 同步目标仓库Release
+#[allow(clippy::too_many_arguments)]
 pub fn sync_release(
-    client: &Client,
     cli: &Cli,
+    source: &dyn ReleaseSource,
+    target: &dyn ReleaseTarget,
     release: &Release,
     er: Option<&Release>,
-) -> AnyResult<()> {
+    journal: &mut Journal,
+    store: Option<&dyn CacheStore>,
+    cancellation: &Cancellation,
+    events: &Events,
+) -> AnyResult<SyncedRelease> {
+    // 本地化的release notes追加到body或作为附件
+    let release = notes::localize_release(source, cli, release)?;
+    let release = with_source_archives(source, cli, release);
+    let release = &with_label_names(target, cli, release);
+    if er.is_none() && cli.ensure_tag {
+        ensure_target_tag(source, target, cli, &release.tag_name)?;
+    }
+
     // 如果gitee的release不存在则创建, 存在且内容不一致则更新, 否则无需处理
-    let gitee_release = &gitee_release_create_or_update(client, cli, release, er)?;
+    let (mut target_release, action) = target_release_create_or_update(target, cli, release, er)?;
+    // 镜像与源仓库不一致的原因(严格模式下作为错误)
+    let mut divergences = Vec::new();
+    let renamed_asserts =
+        resolve_asset_conflicts(target, cli, release, &mut target_release, &mut divergences)?;
+    let mut synced = SyncedRelease {
+        tag_name: release.tag_name.clone(),
+        action,
+        uploaded_assets: Vec::new(),
+        uploaded_bytes: 0,
+        missing_assets: Vec::new(),
+        warnings: Vec::new(),
+    };
 
-    // 如果gitee的release 和 github的release的附件完全一致，则无需处理
-    let diff_asserts = &release_asserts_diff(release, gitee_release);
-    if diff_asserts.is_empty() {
+    // 如果gitee的release 和 github的release的附件完全一致，则无需处理 (上次中断前已上传的附件也无需处理)
+    let mut diff_asserts = release_asserts_diff(release, &target_release);
+    diff_asserts.extend(renamed_asserts);
+    diff_asserts.retain(|asset| !journal.is_asset_uploaded(&release.tag_name, &asset.name));
+    let diff_asserts = &diff_asserts;
+    let result = if diff_asserts.is_empty() {
         let tag_name = &release.tag_name;
-        info!("gitee/github release asserts is some: {tag_name}!",);
+        info!("source/target release asserts is some: {tag_name}!",);
+        Ok(Vec::new())
+    } else {
+        journal.release_started(&release.tag_name)?;
+        // 下载github附件到本地, 上传附件到gitee
+        download_release_asserts(
+            source,
+            cli,
+            release,
+            diff_asserts,
+            store,
+            cancellation,
+            events,
+        )
+        .and_then(|_| {
+            upload_release_asserts(
+                target,
+                cli,
+                release,
+                &target_release,
+                diff_asserts,
+                journal,
+                cancellation,
+                events,
+            )
+        })
+    };
+
+    // 未同步的附件标注在release的body末尾, 全部同步后移除标注
+    let missing_assets = diff_asserts
+        .iter()
+        .filter(|asset| !journal.is_asset_uploaded(&release.tag_name, &asset.name))
+        .map(|asset| asset.name.clone())
+        .collect::<Vec<_>>();
+    if let Err(e) = annotate_partial_mirror(target, &mut target_release, &missing_assets) {
+        warn!("partial mirror annotate error: {}, {e:#}", release.tag_name);
+    }
+
+    match result {
+        Ok(uploaded_assets) => {
+            synced.warnings = divergences.clone();
+            if !missing_assets.is_empty() {
+                divergences.push(format!("partial mirror: {}", missing_assets.join(", ")));
+            }
+            if cli.strict && !divergences.is_empty() {
+                return Err(Diverged {
+                    tag_name: release.tag_name.clone(),
+                    reasons: divergences,
+                }
+                .into());
+            }
+            synced.uploaded_bytes = diff_asserts
+                .iter()
+                .filter(|asset| uploaded_assets.contains(&asset.name))
+                .filter_map(|asset| asset.size)
+                .sum();
+            synced.uploaded_assets = uploaded_assets;
+            synced.missing_assets = missing_assets;
+            Ok(synced)
+        }
+        Err(e) => {
+            // 本次新建的release附件同步失败时回滚, 避免镜像中留下没有附件的release
+            // 取消时不回滚, 保留已上传的附件, 下次运行从中断处继续
+            if action == ReleaseAction::Created
+                && cancellation.check().is_ok()
+                && rollback_created_release(target, cli, &target_release)
+            {
+                journal.release_reset(&release.tag_name)?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// 部分同步的release: body末尾标注未同步的附件, 没有缺失时移除标注
+fn annotate_partial_mirror(
+    target: &dyn ReleaseTarget,
+    target_release: &mut Release,
+    missing_assets: &[String],
+) -> AnyResult<()> {
+    let body = target_release.body.clone().unwrap_or_default();
+    let new_body = body::partial_mirror_body(&body, missing_assets);
+    if new_body == body {
         return Ok(());
     }
 
-    // 下载github附件到本地
-    download_release_asserts(client, cli, release, diff_asserts)?;
+    target_release.body = Some(new_body);
+    target_release_update(target, target_release)?;
+    let tag_name = &target_release.tag_name;
+    if missing_assets.is_empty() {
+        info!("partial mirror annotation removed: {tag_name}");
+    } else {
+        warn!(
+            "partial mirror annotated: {tag_name}, missing: {}",
+            missing_assets.join(", ")
+        );
+    }
+    Ok(())
+}
 
-    // 上传附件到gitee
-    upload_release_asserts(client, cli, release, gitee_release, diff_asserts)?;
+/// 回滚新建的gitee release: 删除或标记为预发布(Gitee不支持草稿), 返回是否已删除
+fn rollback_created_release(
+    target: &dyn ReleaseTarget,
+    cli: &Cli,
+    target_release: &Release,
+) -> bool {
+    let tag_name = &target_release.tag_name;
+    let result = match cli.rollback_created_release {
+        RollbackMode::None => return false,
+        RollbackMode::Delete => target.delete_release(target_release),
+        RollbackMode::Prerelease => {
+            let mut er = target_release.clone();
+            er.prerelease = true;
+            target_release_update(target, &er)
+        }
+    };
+    match result {
+        Ok(()) => {
+            warn!(
+                "gitee release rollback success ({}): {tag_name}",
+                cli.rollback_created_release
+            );
+            cli.rollback_created_release == RollbackMode::Delete
+        }
+        Err(e) => {
+            error!("target release rollback error: {tag_name}, {e:#}");
+            false
+        }
+    }
+}
+
+/// 创建release之前确保目标仓库存在tag(--ensure-tag): 以--ensure-tag-ref或源仓库tag的commit创建
+fn ensure_target_tag(
+    source: &dyn ReleaseSource,
+    target: &dyn ReleaseTarget,
+    cli: &Cli,
+    tag_name: &str,
+) -> AnyResult<()> {
+    let git_ref = match &cli.ensure_tag_ref {
+        Some(git_ref) => git_ref.clone(),
+        None => source
+            .tag_commit(tag_name)
+            .with_context(|| format!("source tag commit query error: {tag_name}"))?
+            .with_context(|| format!("source tag commit not found: {tag_name}"))?,
+    };
+    target
+        .ensure_tag(tag_name, &git_ref)
+        .with_context(|| format!("target tag create error: {tag_name}, {git_ref}"))?;
     Ok(())
 }
 
-fn gitee_release_delete(client: &Client, cli: &Cli, id: u64) -> AnyResult<()> {
-    let url = format!(
-        "{}/{}/{}/releases/{}",
-        GITEE_API_URL, cli.gitee_owner, cli.gitee_repo, id
-    );
-    http::delete(client, &url, &cli.gitee_token)
+/// 源码压缩包(--source-archives): github release页面的Source code, 作为附件source-{tag}.tar.gz、source-{tag}.zip
+fn with_source_archives(source: &dyn ReleaseSource, cli: &Cli, mut release: Release) -> Release {
+    let endpoint = source.endpoint();
+    if !cli.source_archives || endpoint.forge != Forge::GitHub {
+        return release;
+    }
+    let tag_name = &release.tag_name;
+    // tag中的/不能作为文件名
+    let name = tag_name.replace('/', "-");
+    for ext in ["tar.gz", "zip"] {
+        release.assets.push(Assert {
+            id: None,
+            name: format!("source-{name}.{ext}"),
+            size: None,
+            browser_download_url: format!(
+                "{}/archive/refs/tags/{tag_name}.{ext}",
+                endpoint.web_url()
+            ),
+            digest: None,
+            label: None,
+            download_count: None,
+            content_type: None,
+            api_url: None,
+        });
+    }
+    release
+}
+
+/// 附件的显示名称加入附件名(--asset-label-in-name): 目标仓库不支持label时, 如: app_macOS_Apple_Silicon.dmg
+fn with_label_names(target: &dyn ReleaseTarget, cli: &Cli, mut release: Release) -> Release {
+    if !cli.asset_label_in_name || target.supports_asset_label() {
+        return release;
+    }
+    for asset in release.assets.iter_mut() {
+        if let Some(label) = asset.display_label() {
+            asset.name = label_name(&asset.name, label);
+            asset.label = None;
+        }
+    }
+    release
+}
+
+/// 在扩展名之前加入label(非字母数字的字符替换为_), 如: app.tar.gz → app_linux_x64.tar.gz
+fn label_name(name: &str, label: &str) -> String {
+    let label = label
+        .split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '.'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if label.is_empty() {
+        return name.to_string();
+    }
+    let ext_start = name
+        .find(".tar.")
+        .or_else(|| name.rfind('.').filter(|&i| i > 0))
+        .unwrap_or(name.len());
+    format!("{}_{label}{}", &name[..ext_start], &name[ext_start..])
 }
 
-fn gitee_release_create_or_update(
-    client: &Client,
+fn target_release_create_or_update(
+    target: &dyn ReleaseTarget,
     cli: &Cli,
     release: &Release,
-    gitee_release: Option<&Release>,
-) -> AnyResult<Release> {
-    if gitee_release.is_none() {
-        Ok(gitee_release_create(client, cli, &release)?)
-    } else {
-        let er = gitee_release.unwrap();
-        let new_body = replace_release_body_url(cli, release.body.clone().unwrap_or_default());
+    target_release: Option<&Release>,
+) -> AnyResult<(Release, ReleaseAction)> {
+    if let Some(er) = target_release {
+        let new_body = target_release_body(target, cli, release, Some(er))?;
+        // gitlab没有预发布标记, 不参与对比
+        let prerelease_changed =
+            release.prerelease != er.prerelease && target.supports_prerelease();
 
         if release.name != er.name
-            || new_body != er.body.clone().unwrap_or_default()
-            || release.prerelease != er.prerelease
+            || new_body
+                != body::strip_footer(&body::strip_partial_mirror(
+                    er.body.as_deref().unwrap_or_default(),
+                ))
+            || prerelease_changed
         //|| release.target_commitish != er.target_commitish
         //  ==> 某些场景下github返回的releases中target_commitish为master, 而gitee返回的为具体哈希值导致永远不一致，因此注释掉
         {
@@ -247,49 +1206,91 @@ fn gitee_release_create_or_update(
                 tag_name: er.tag_name.clone(),
                 assets: er.assets.clone(),
                 name: release.name.clone(),
-                body: release.body.clone(),
-                prerelease: release.prerelease.clone(),
+                body: Some(body::append_footer(cli, &release.tag_name, new_body)),
+                prerelease: release.prerelease,
+                draft: false,
                 target_commitish: release.target_commitish.clone(),
+                updated_at: er.updated_at.clone(),
+                created_at: er.created_at.clone(),
+                published_at: er.published_at.clone(),
             };
-            gitee_release_update(client, cli, &new_er)?;
-            Ok(new_er)
+            target_release_update(target, &new_er)?;
+            Ok((new_er, ReleaseAction::Updated))
         } else {
             info!(
-                "gitee/github release name/body/prerelease is some: {}!",
+                "source/target release name/body/prerelease is some: {}!",
                 &release.tag_name
             );
-            Ok(er.clone())
+            Ok((er.clone(), ReleaseAction::Unchanged))
         }
+    } else {
+        let mut new_release = release.clone();
+        let new_body = target_release_body(target, cli, release, None)?;
+        new_release.body = Some(body::append_footer(cli, &release.tag_name, new_body));
+        let er = target.create_release(&new_release)?;
+        info!(
+            "{} release create success: {}!",
+            target.endpoint().forge,
+            &er.tag_name
+        );
+        Ok((er, ReleaseAction::Created))
     }
 }
 
-fn gitee_release_update(client: &Client, cli: &Cli, er: &Release) -> AnyResult<()> {
-    let url = format!(
-        "{}/{}/{}/releases/{}",
-        GITEE_API_URL, cli.gitee_owner, cli.gitee_repo, er.id
-    );
-    let result = http::patch(client, &url, &cli.gitee_token, er)?;
-    let release: Release = serde_json::from_str(&result)?;
-    info!("gitee release update success: {}!", &release.tag_name);
-    Ok(())
+/// 目标仓库的release body: 外部命令转换、地址等替换后按模板组合, 超过目标平台的长度限制时截断
+fn target_release_body(
+    target: &dyn ReleaseTarget,
+    cli: &Cli,
+    release: &Release,
+    target_release: Option<&Release>,
+) -> AnyResult<String> {
+    let tag_name = &release.tag_name;
+    let source_body = release.body.clone().unwrap_or_default();
+    // 源body和转换命令未变化时沿用目标仓库的body
+    let marker = body::transform_marker(cli, &source_body);
+    if let (Some(marker), Some(er)) = (&marker, target_release) {
+        let body = body::strip_footer(&body::strip_partial_mirror(
+            er.body.as_deref().unwrap_or_default(),
+        ));
+        if body.ends_with(marker.as_str()) {
+            return Ok(body);
+        }
+    }
+
+    let body = body::transform_body(cli, tag_name, source_body)
+        .with_context(|| format!("release body transform error: {tag_name}"))?;
+    let body = body::convert_release_body(cli, tag_name, body);
+    let body = body::render_template(cli, release, body)?;
+    let body = body::prepend_published_date(cli, release, body);
+    let body = match cli.release_body_max_chars.or(target.body_max_chars()) {
+        Some(max_chars) if body.chars().count() > max_chars => {
+            warn!("release body exceeds {max_chars} chars, truncated: {tag_name}");
+            let notes_url = format!("{}/releases/tag/{tag_name}", cli.source().web_url());
+            body::truncate_body(body, max_chars, &notes_url)
+        }
+        _ => body,
+    };
+    Ok(match marker {
+        Some(marker) => format!("{}\n\n{marker}", body.trim_end()),
+        None => body,
+    })
 }
 
-fn gitee_release_create(client: &Client, cli: &Cli, release: &Release) -> AnyResult<Release> {
-    let url = format!(
-        "{}/{}/{}/releases",
-        GITEE_API_URL, cli.gitee_owner, cli.gitee_repo
+fn target_release_update(target: &dyn ReleaseTarget, er: &Release) -> AnyResult<()> {
+    target.update_release(er)?;
+    info!(
+        "{} release update success: {}!",
+        target.endpoint().forge,
+        &er.tag_name
     );
-    let result = http::post(client, &url, &cli.gitee_token, release)?;
-    let release: Release = serde_json::from_str(&result)?;
-    info!("gitee release create success: {}!", &release.tag_name);
-    Ok(release)
+    Ok(())
 }
 
 /// 寻找附件差异: Github附件有，但Gitee没有的
-fn release_asserts_diff(release: &Release, gitee_release: &Release) -> Vec<Assert> {
+fn release_asserts_diff(release: &Release, target_release: &Release) -> Vec<Assert> {
     let mut diff_assets = Vec::new();
     for asset in &release.assets {
-        if !gitee_release
+        if !target_release
             .assets
             .iter()
             .any(|gitee_asset| gitee_asset.name == asset.name)
@@ -300,57 +1301,292 @@ fn release_asserts_diff(release: &Release, gitee_release: &Release) -> Vec<Asser
     diff_assets
 }
 
+/// 同名附件冲突: 大小都已知且不一致(内容不同), 按--on-asset-conflict处理
+/// - skip: 跳过, 记录警告日志
+/// - replace: 删除目标仓库的附件, 由附件差异重新上传
+/// - rename: 返回以新名称上传的附件
+fn resolve_asset_conflicts(
+    target: &dyn ReleaseTarget,
+    cli: &Cli,
+    release: &Release,
+    target_release: &mut Release,
+    divergences: &mut Vec<String>,
+) -> AnyResult<Vec<Assert>> {
+    let mut renamed = Vec::new();
+    for asset in &release.assets {
+        let Some(er_asset) = target_release
+            .assets
+            .iter()
+            .find(|er_asset| er_asset.name == asset.name)
+        else {
+            continue;
+        };
+        let (Some(size), Some(er_size)) = (asset.size, er_asset.size) else {
+            continue;
+        };
+        // 替换了下载地址的附件大小与源仓库不同
+        if size == er_size || cli.is_transformed_asset(&asset.name) {
+            continue;
+        }
+
+        let tag_name = &release.tag_name;
+        let name = &asset.name;
+        match cli.on_asset_conflict {
+            AssetConflict::Skip => {
+                warn!("asset conflict, skip: {tag_name}, {name}, size {size} != {er_size}");
+                divergences.push(format!("asset conflict skipped: {name}"));
+            }
+            AssetConflict::Replace => {
+                let er_asset = er_asset.clone();
+                target
+                    .delete_asset(target_release, &er_asset)
+                    .with_context(|| asset_error(release, asset))?;
+                target_release.assets.retain(|a| a.name != er_asset.name);
+                warn!("asset conflict, replace: {tag_name}, {name}, size {size} != {er_size}");
+            }
+            AssetConflict::Rename => {
+                if let Some(new_name) = mirror_asset_name(asset, &target_release.assets) {
+                    warn!("asset conflict, rename: {tag_name}, {name} -> {new_name}");
+                    divergences.push(format!("asset renamed: {name} -> {new_name}"));
+                    let mut asset = asset.clone();
+                    asset.name = new_name;
+                    renamed.push(asset);
+                }
+            }
+        }
+    }
+    Ok(renamed)
+}
+
+/// 冲突附件的新名称: app.tar.gz -> app-mirror-1.tar.gz
+/// - 已经以新名称上传过(大小一致)时返回None, 避免每次同步重复上传
+fn mirror_asset_name(asset: &Assert, target_assets: &[Assert]) -> Option<String> {
+    let (stem, ext) = split_extension(&asset.name);
+    for n in 1.. {
+        let name = format!("{stem}-mirror-{n}{ext}");
+        match target_assets.iter().find(|a| a.name == name) {
+            None => return Some(name),
+            Some(a) if a.size.is_none() || a.size == asset.size => return None,
+            Some(_) => {}
+        }
+    }
+    unreachable!()
+}
+
+/// 拆分文件名和扩展名, 支持.tar.gz等双扩展名
+fn split_extension(name: &str) -> (&str, &str) {
+    for ext in [".tar.gz", ".tar.xz", ".tar.bz2", ".tar.zst"] {
+        if let Some(stem) = name.strip_suffix(ext)
+            && !stem.is_empty()
+        {
+            return (stem, &name[stem.len()..]);
+        }
+    }
+    match name.rfind('.') {
+        Some(i) if i > 0 => name.split_at(i),
+        _ => (name, ""),
+    }
+}
+
 /// 下载附件
 fn download_release_asserts(
-    client: &Client,
+    source: &dyn ReleaseSource,
     cli: &Cli,
     release: &Release,
-    diff_asserts: &Vec<Assert>,
+    diff_asserts: &[Assert],
+    store: Option<&dyn CacheStore>,
+    cancellation: &Cancellation,
+    events: &Events,
 ) -> AnyResult<()> {
     let tmp_dir = tmp_dir_repo_tag(cli, release)?;
+    let asset_cache = AssetCache::open(cli);
+
+    // 网络文件系统(NFS/SMB)上使用 写临时文件->fsync->重命名 的方式, 避免缓存的附件损坏
+    let durable = cli.fsync || fsutil::is_remote_fs(&tmp_dir);
+    if durable {
+        info!("tmp dir write with fsync and rename: {}", tmp_dir.display());
+    }
 
+    // 重新打包的压缩包: (原sha256, 新sha256)
+    let mut repacked = Vec::new();
     for asset in diff_asserts {
+        cancellation.check()?;
         // 先判断文件是否存在，存在且大小一致则忽略下载
         let file_path = tmp_dir.join(&asset.name);
-        if Path::new(&file_path).exists() {
-            // 如果文件存在，检查大小是否一致
-            if let Some(asset_size) = asset.size {
-                if let Ok(metadata) = fs::metadata(&file_path) {
-                    if metadata.len() == asset_size {
-                        info!(
-                            "file exists and size is some, skip download: {}",
-                            &asset.name
-                        );
-                        continue;
-                    }
+        // 如果文件存在，检查大小是否一致
+        if Path::new(&file_path).exists()
+            && let Some(asset_size) = asset.size
+            && let Ok(metadata) = fs::metadata(&file_path)
+            && metadata.len() == asset_size
+        {
+            info!(
+                "file exists and size is some, skip download: {}",
+                &asset.name
+            );
+            continue;
+        }
+
+        // 优先从缓存存储中取出, 否则下载后保存到缓存存储
+        let key = format!(
+            "{}/{}/{}",
+            source.endpoint().path(),
+            release.tag_name,
+            asset.name
+        );
+        if let Some(cache) = &asset_cache {
+            match cache.get(asset, &file_path) {
+                Ok(true) => {
+                    transform_asset(cli, asset, &file_path, durable, &mut repacked)?;
+                    continue;
                 }
+                Ok(false) => {}
+                Err(e) => warn!("cache dir get error, download again: {}, {e:#}", asset.name),
             }
         }
+        if !restore_cached_asset(store, &key, asset, &file_path) {
+            let mut span = otel::span("download");
+            span.set("asset.name", &asset.name);
+            span.set_int("asset.size", asset.size.unwrap_or_default());
+            let result = source.download_asset(asset, &file_path, durable, cancellation);
+            span.record(&result);
+            drop(span);
+            result.with_context(|| asset_error(release, asset))?;
+            events.emit(SyncEvent::AssetDownloaded {
+                target: cli.target().to_string(),
+                tag_name: release.tag_name.clone(),
+                asset_name: asset.name.clone(),
+                size: asset.size.unwrap_or_default(),
+            });
+            if let Some(store) = store
+                && let Err(e) = store.put(&key, &file_path)
+            {
+                warn!("cache store put error: {key}, {e:#}");
+            }
+        }
+        if let Some(cache) = &asset_cache
+            && let Err(e) = cache.put(asset, &file_path)
+        {
+            warn!("cache dir put error: {}, {e:#}", asset.name);
+        }
+        transform_asset(cli, asset, &file_path, durable, &mut repacked)?;
+    }
 
-        http::download(client, &asset.browser_download_url, &file_path)?;
+    // 校验和附件中的sha256更新为重新打包后的值
+    if !repacked.is_empty() {
+        for asset in diff_asserts {
+            if !cli.is_checksum_asset(&asset.name) {
+                continue;
+            }
+            let file_path = tmp_dir.join(&asset.name);
+            let Ok(content) = String::from_utf8(fs::read(&file_path)?) else {
+                warn!(
+                    "checksum asset is not utf-8 text, skip update: {}",
+                    asset.name
+                );
+                continue;
+            };
+            if let Some(content) = archive::update_checksums(&content, &repacked) {
+                fsutil::write_file(&file_path, content, durable)?;
+                info!("{}'s checksums are updated (repacked archives)", asset.name);
+            }
+        }
+    }
+    Ok(())
+}
 
-        // 如果是latest.json, 则替换其中的下载地址
-        if cli.latest_json_url_replace && asset.name == "latest.json" {
-            let content = fs::read_to_string(&file_path)?;
-            let content = replace_download_url(cli, content);
-            fs::write(&file_path, content)?;
-            info!("latest.json's content is replaced (download url)");
+/// 下载后修改附件的内容: 替换latest.json等文本附件、appcast、压缩包中的下载地址
+fn transform_asset(
+    cli: &Cli,
+    asset: &Assert,
+    file_path: &Path,
+    durable: bool,
+    repacked: &mut Vec<(String, String)>,
+) -> AnyResult<()> {
+    if cli.is_appcast_asset(&asset.name) {
+        let Ok(content) = String::from_utf8(fs::read(file_path)?) else {
+            warn!("appcast is not utf-8 text, skip replace: {}", asset.name);
+            return Ok(());
+        };
+        fsutil::write_file(file_path, appcast::rewrite(cli, &content), durable)?;
+        info!("{}'s enclosure urls are replaced", asset.name);
+    } else if cli.is_rewritable_asset(&asset.name) {
+        let Ok(content) = String::from_utf8(fs::read(file_path)?) else {
+            warn!("asset is not utf-8 text, skip replace: {}", asset.name);
+            return Ok(());
+        };
+        let content = replace_download_url(cli, content);
+        fsutil::write_file(file_path, content, durable)?;
+        info!("{}'s content is replaced (download url)", asset.name);
+    } else if cli.is_rewritable_archive(&asset.name) {
+        let old_sha256 = fsutil::sha256_file(file_path)?;
+        let rewrite = |content| replace_download_url(cli, content);
+        match archive::rewrite(file_path, &cli.rewrite_archive_entry, rewrite)
+            .with_context(|| format!("repack archive error: {}", asset.name))?
+        {
+            Some(true) => {
+                repacked.push((old_sha256, fsutil::sha256_file(file_path)?));
+                info!("{}'s entries are replaced (download url)", asset.name);
+            }
+            Some(false) => info!("{}'s entries have nothing to replace", asset.name),
+            None => warn!("unsupported archive format, skip repack: {}", asset.name),
         }
     }
     Ok(())
 }
 
-/// 上传附件
+/// 从缓存存储中取出附件: 大小和摘要与源仓库一致才使用, 出错时重新下载
+fn restore_cached_asset(
+    store: Option<&dyn CacheStore>,
+    key: &str,
+    asset: &Assert,
+    file_path: &Path,
+) -> bool {
+    let Some(store) = store else {
+        return false;
+    };
+    let result = store.get(key, file_path).and_then(|found| {
+        if !found {
+            return Ok(false);
+        }
+        if let Some(size) = asset.size
+            && fs::metadata(file_path)?.len() != size
+        {
+            let _ = fs::remove_file(file_path);
+            bail!("asset size mismatch: {}, expected {size}", asset.name);
+        }
+        provider::verify_asset_digest(asset, file_path)?;
+        Ok(true)
+    });
+    match result {
+        Ok(true) => {
+            info!("asset restored from cache store: {key}");
+            true
+        }
+        Ok(false) => false,
+        Err(e) => {
+            warn!("cache store get error, download again: {key}, {e:#}");
+            false
+        }
+    }
+}
+
+/// 上传附件, 返回上传成功的附件名称
+#[allow(clippy::too_many_arguments)]
 fn upload_release_asserts(
-    client: &Client,
+    target: &dyn ReleaseTarget,
     cli: &Cli,
     release: &Release,
-    gitee_release: &Release,
-    diff_asserts: &Vec<Assert>,
-) -> AnyResult<()> {
+    target_release: &Release,
+    diff_asserts: &[Assert],
+    journal: &mut Journal,
+    cancellation: &Cancellation,
+    events: &Events,
+) -> AnyResult<Vec<String>> {
     let tmp_dir = tmp_dir_repo_tag(cli, release)?;
 
+    let mut uploaded = Vec::new();
     for asset in diff_asserts {
+        cancellation.check()?;
         //let file_path = &format!("{}/{}", &release.tag_name, &asset.name);
         let file_path = tmp_dir.join(&asset.name);
 
@@ -360,24 +1596,61 @@ fn upload_release_asserts(
             continue;
         }
 
-        // 构造上传URL
-        let upload_url = format!(
-            "{}/{}/{}/releases/{}/attach_files",
-            GITEE_API_URL, cli.gitee_owner, cli.gitee_repo, gitee_release.id,
-        );
-        http::upload(client, &upload_url, &cli.gitee_token, &file_path)?;
+        let mut span = otel::span("upload");
+        span.set("asset.name", &asset.name);
+        span.set_int("asset.size", asset.size.unwrap_or_default());
+        let result = target.upload_asset(target_release, &asset.name, &file_path, cancellation);
+        span.record(&result);
+        drop(span);
+        result.with_context(|| asset_error(release, asset))?;
+        events.emit(SyncEvent::AssetUploaded {
+            target: target.endpoint().to_string(),
+            tag_name: release.tag_name.clone(),
+            asset_name: asset.name.clone(),
+            size: asset.size.unwrap_or_default(),
+        });
+        if let Some(label) = asset.display_label() {
+            if !target.supports_asset_label() {
+                warn!(
+                    "asset label not supported by {}, dropped: {} ({label})",
+                    target.endpoint().forge,
+                    asset.name
+                );
+            } else if let Err(e) = target.label_asset(target_release, &asset.name, label) {
+                warn!("asset label update error: {}, {e:#}", asset.name);
+            }
+        }
+        journal.asset_uploaded(&release.tag_name, &asset.name)?;
+        uploaded.push(asset.name.clone());
     }
-    Ok(())
+    Ok(uploaded)
 }
 
-/// 创建临时目录: ~/tmp/github_repo/tag_name
-fn tmp_dir_repo_tag(cli: &Cli, release: &Release) -> AnyResult<PathBuf> {
-    let mut tmp_dir = env::temp_dir();
+fn asset_error(release: &Release, asset: &Assert) -> AssetError {
+    AssetError {
+        tag_name: release.tag_name.clone(),
+        asset_name: asset.name.clone(),
+    }
+}
+
+/// 临时目录的上级目录: {工作目录}/github_repo
+fn tmp_dir_repo(cli: &Cli) -> PathBuf {
+    let mut tmp_dir = cli.work_dir();
     tmp_dir.push(cli.github_repo.clone());
-    tmp_dir.push(release.tag_name.clone());
+    // 并行同步多个目标平台时各自下载, 避免同时写入同一个文件
+    if cli.fan_out {
+        tmp_dir.push(cli.mirror_key());
+    }
+    tmp_dir
+}
+
+/// 创建临时目录: {工作目录}/github_repo/tag_name
+fn tmp_dir_repo_tag(cli: &Cli, release: &Release) -> AnyResult<PathBuf> {
+    let tmp_dir = tmp_dir_repo(cli).join(&release.tag_name);
 
     if !tmp_dir.exists() {
         fs::create_dir_all(&tmp_dir)?;
+        fs::write(tmp_dir.join(TMP_DIR_MARKER), "")?;
         info!("tmp dir create: {}", &tmp_dir.display())
     } else {
         info!("tmp dir exits: {}", &tmp_dir.display());
@@ -385,23 +1658,931 @@ fn tmp_dir_repo_tag(cli: &Cli, release: &Release) -> AnyResult<PathBuf> {
     Ok(tmp_dir)
 }
 
-// 替换下载地址
+/// 附件全部同步到目标仓库后删除该release的临时目录(--keep-temp时保留)
+fn clean_tmp_dir(cli: &Cli, release: &Release) {
+    if !cli.clean_local() {
+        return;
+    }
+    let tmp_dir = tmp_dir_repo(cli).join(&release.tag_name);
+    if !tmp_dir.exists() {
+        return;
+    }
+    match fs::remove_dir_all(&tmp_dir) {
+        Ok(()) => info!("tmp dir removed: {}", tmp_dir.display()),
+        Err(e) => warn!("tmp dir remove error: {}, {e}", tmp_dir.display()),
+    }
+}
+
+/// 清理之前崩溃或中断的运行遗留的临时目录: 带TMP_DIR_MARKER且超过STALE_TMP_DIR_AGE没有变化的目录
+fn clean_stale_tmp_dirs(cli: &Cli) {
+    if !cli.clean_local() {
+        return;
+    }
+    let tmp_dir = tmp_dir_repo(cli);
+    match fsutil::remove_stale_dirs(&tmp_dir, TMP_DIR_MARKER, STALE_TMP_DIR_AGE) {
+        Ok(0) => {}
+        Ok(count) => info!("stale tmp dirs removed: {}, {count}", tmp_dir.display()),
+        Err(e) => warn!("stale tmp dirs clean error: {}, {e:#}", tmp_dir.display()),
+    }
+}
+
+// 替换下载地址, 然后执行--rewrite自定义的替换规则
 fn replace_download_url(cli: &Cli, content: String) -> String {
     // https://github.com/hepengju/redis-me
     // https://gitee.com/hepengju/redis-me
-    let src = format!(
-        "https://github.com/{}/{}",
-        cli.github_owner, cli.github_repo
-    );
-    let tar = format!("https://gitee.com/{}/{}", cli.gitee_owner, cli.gitee_repo);
-    let content = content.replace(&src, &tar);
-    content
+    let (source, target) = (cli.source(), cli.target());
+    let content = content
+        .replace(&source.download_url(), &target.download_url())
+        .replace(&source.web_url(), &target.web_url());
+    rewrite::apply_rules(&cli.rewrite, content)
 }
 
-fn replace_release_body_url(cli: &Cli, content: String) -> String {
-    if cli.release_body_url_replace {
-        replace_download_url(cli, content)
-    } else {
-        content
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use crate::model::{Forge, RepoEndpoint};
+    use clap::Parser;
+    use std::cell::RefCell;
+    use std::env;
+
+    fn release(id: u64, tag_name: &str, assets: &[&str]) -> Release {
+        Release {
+            id,
+            tag_name: tag_name.to_string(),
+            name: tag_name.to_string(),
+            body: Some(format!("release {tag_name}")),
+            prerelease: false,
+            draft: false,
+            target_commitish: "main".to_string(),
+            updated_at: Some("2025-12-25T08:22:42Z".to_string()),
+            created_at: None,
+            published_at: None,
+            assets: assets
+                .iter()
+                .map(|name| Assert {
+                    id: None,
+                    name: name.to_string(),
+                    size: None,
+                    browser_download_url: format!("https://example.com/{tag_name}/{name}"),
+                    digest: None,
+                    label: None,
+                    download_count: None,
+                    content_type: None,
+                    api_url: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn endpoint(forge: Forge, repo: &str) -> RepoEndpoint {
+        RepoEndpoint {
+            forge,
+            host: "https://example.com".to_string(),
+            owner: "o".to_string(),
+            repo: repo.to_string(),
+            token: Some("t".to_string()),
+        }
+    }
+
+    struct FakeSource {
+        endpoint: RepoEndpoint,
+        releases: Vec<Release>,
+    }
+
+    impl ReleaseSource for FakeSource {
+        fn endpoint(&self) -> &RepoEndpoint {
+            &self.endpoint
+        }
+
+        fn fetch_releases(&self, _: usize, _: usize, _: Option<&str>) -> AnyResult<Conditional> {
+            // 响应为逗号分隔的tag(缓存的响应只包含当时的release)
+            Ok(Conditional::Modified {
+                text: get_tags(&self.releases).join(","),
+                etag: None,
+            })
+        }
+
+        fn parse_releases(&self, text: &str) -> AnyResult<Vec<Release>> {
+            let tags = text.split(',').collect::<Vec<_>>();
+            Ok(self
+                .releases
+                .iter()
+                .filter(|release| tags.contains(&release.tag_name.as_str()))
+                .cloned()
+                .collect())
+        }
+
+        fn download_asset(
+            &self,
+            asset: &Assert,
+            path: &Path,
+            _: bool,
+            _: &Cancellation,
+        ) -> AnyResult<()> {
+            fs::write(path, &asset.browser_download_url)?;
+            Ok(())
+        }
+    }
+
+    /// 源仓库的releases接口不可访问(网络超时)
+    struct UnreachableSource(FakeSource);
+
+    impl ReleaseSource for UnreachableSource {
+        fn endpoint(&self) -> &RepoEndpoint {
+            self.0.endpoint()
+        }
+
+        fn fetch_releases(&self, _: usize, _: usize, _: Option<&str>) -> AnyResult<Conditional> {
+            Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into())
+        }
+
+        fn parse_releases(&self, text: &str) -> AnyResult<Vec<Release>> {
+            self.0.parse_releases(text)
+        }
+
+        fn download_asset(
+            &self,
+            asset: &Assert,
+            path: &Path,
+            durable: bool,
+            cancellation: &Cancellation,
+        ) -> AnyResult<()> {
+            self.0.download_asset(asset, path, durable, cancellation)
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeTarget {
+        endpoint: Option<RepoEndpoint>,
+        releases: RefCell<Vec<Release>>,
+        // 按创建顺序展示最新版本(gitee, gitea)
+        latest_by_creation: bool,
+        // 上传失败的附件名
+        fail_uploads: Vec<String>,
+        // 上传该附件时取消同步(调用方在传输过程中取消)
+        cancel_upload: Option<(String, CancellationToken)>,
+    }
+
+    impl ReleaseTarget for FakeTarget {
+        fn endpoint(&self) -> &RepoEndpoint {
+            self.endpoint.as_ref().unwrap()
+        }
+
+        fn releases(&self) -> AnyResult<Vec<Release>> {
+            Ok(self.releases.borrow().clone())
+        }
+
+        fn create_release(&self, release: &Release) -> AnyResult<Release> {
+            let mut created = release.clone();
+            created.id = self
+                .releases
+                .borrow()
+                .iter()
+                .map(|r| r.id)
+                .max()
+                .unwrap_or(0)
+                + 1;
+            created.assets.clear();
+            self.releases.borrow_mut().push(created.clone());
+            Ok(created)
+        }
+
+        fn update_release(&self, release: &Release) -> AnyResult<()> {
+            let mut releases = self.releases.borrow_mut();
+            let er = releases.iter_mut().find(|er| er.id == release.id).unwrap();
+            er.body = release.body.clone();
+            er.prerelease = release.prerelease;
+            Ok(())
+        }
+
+        fn delete_release(&self, release: &Release) -> AnyResult<()> {
+            self.releases.borrow_mut().retain(|er| er.id != release.id);
+            Ok(())
+        }
+
+        fn upload_asset(
+            &self,
+            release: &Release,
+            name: &str,
+            path: &Path,
+            cancellation: &Cancellation,
+        ) -> AnyResult<()> {
+            if let Some((asset_name, token)) = &self.cancel_upload
+                && asset_name == name
+            {
+                token.cancel();
+                cancellation.check()?;
+            }
+            if self
+                .fail_uploads
+                .iter()
+                .any(|asset_name| asset_name == name)
+            {
+                bail!("upload error: {name}");
+            }
+            let mut releases = self.releases.borrow_mut();
+            let er = releases.iter_mut().find(|er| er.id == release.id).unwrap();
+            er.assets.push(Assert {
+                id: Some(er.assets.len() as u64 + 1),
+                name: name.to_string(),
+                size: Some(fs::metadata(path)?.len()),
+                browser_download_url: format!("https://mirror.example.com/{name}"),
+                digest: None,
+                label: None,
+                download_count: None,
+                content_type: None,
+                api_url: None,
+            });
+            Ok(())
+        }
+
+        fn delete_asset(&self, release: &Release, asset: &Assert) -> AnyResult<()> {
+            let mut releases = self.releases.borrow_mut();
+            let er = releases.iter_mut().find(|er| er.id == release.id).unwrap();
+            er.assets.retain(|a| a.id != asset.id);
+            Ok(())
+        }
+
+        fn latest_by_creation(&self) -> bool {
+            self.latest_by_creation
+        }
+    }
+
+    #[test]
+    fn test_sync_releases_with_fakes() -> AnyResult<()> {
+        let repo = format!("sync-fake-test-{}", std::process::id());
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=o",
+            &format!("--github-repo={repo}"),
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+        ]);
+        let source = FakeSource {
+            endpoint: endpoint(Forge::GitHub, &repo),
+            releases: vec![
+                release(2, "v0.2.0", &["a.zip", "b.zip"]),
+                release(1, "v0.1.0", &["a.zip"]),
+            ],
+        };
+        let target = FakeTarget {
+            endpoint: Some(endpoint(Forge::Gitee, "r")),
+            ..Default::default()
+        };
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+
+        let (sender, receiver) = std::sync::mpsc::sync_channel(100);
+        let mut report = SyncReport {
+            events: Events::new(Some(sender)),
+            ..Default::default()
+        };
+        sync_releases(&cli, &source, &target, None, &cancellation, &mut report)?;
+        report.events = Events::default();
+        let events = receiver.iter().collect::<Vec<_>>();
+        assert_eq!(
+            events[0],
+            SyncEvent::ReleaseStarted {
+                target: "gitee:o/r".to_string(),
+                tag_name: "v0.1.0".to_string(),
+            }
+        );
+        assert!(events.contains(&SyncEvent::AssetDownloaded {
+            target: "gitee:o/r".to_string(),
+            tag_name: "v0.2.0".to_string(),
+            asset_name: "b.zip".to_string(),
+            size: 0,
+        }));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            SyncEvent::AssetUploaded { tag_name, asset_name, .. }
+                if tag_name == "v0.2.0" && asset_name == "b.zip"
+        )));
+        // 旧的版本先同步
+        let tags = get_tags(&target.releases.borrow());
+        assert_eq!(tags, ["v0.1.0", "v0.2.0"]);
+        assert_eq!(target.releases.borrow()[1].assets.len(), 2);
+        assert!(
+            report
+                .synced
+                .iter()
+                .all(|r| r.action == ReleaseAction::Created)
+        );
+
+        // 再次同步: 没有变化
+        let mut report = SyncReport::default();
+        sync_releases(&cli, &source, &target, None, &cancellation, &mut report)?;
+        assert_eq!(target.releases.borrow().len(), 2);
+        assert!(report.synced.iter().all(|r| r.uploaded_assets.is_empty()));
+
+        fs::remove_dir_all(env::temp_dir().join(&repo))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback_created_release() -> AnyResult<()> {
+        // 新建的release附件上传失败: b.zip上传失败时a.zip已上传
+        let sync = |mode: &str, cancel: bool| {
+            let repo = format!("rollback-{mode}-{cancel}-test-{}", std::process::id());
+            let cli = Cli::parse_from([
+                "release2gitee",
+                "--github-owner=o",
+                &format!("--github-repo={repo}"),
+                "--gitee-owner=o",
+                "--gitee-repo=r",
+                "--gitee-token=t",
+                &format!("--rollback-created-release={mode}"),
+            ]);
+            let source = FakeSource {
+                endpoint: endpoint(Forge::GitHub, &repo),
+                releases: vec![release(1, "v0.1.0", &["a.zip", "b.zip"])],
+            };
+            let token = CancellationToken::new();
+            let target = FakeTarget {
+                endpoint: Some(endpoint(Forge::Gitee, "r")),
+                fail_uploads: vec!["b.zip".to_string()],
+                cancel_upload: cancel.then(|| ("b.zip".to_string(), token.clone())),
+                ..Default::default()
+            };
+            let cancellation = Cancellation::new(&token, None);
+            let mut report = SyncReport::default();
+            let result = sync_releases(&cli, &source, &target, None, &cancellation, &mut report);
+            let uploaded = Journal::open(&cli)?.is_asset_uploaded("v0.1.0", "a.zip");
+            fs::remove_dir_all(env::temp_dir().join(&repo))?;
+            AnyResult::Ok((result, target.releases.take(), uploaded))
+        };
+
+        // delete: 删除新建的release, 重置同步日志(下次运行重新上传全部附件)
+        let (result, releases, uploaded) = sync("delete", false)?;
+        assert!(result.is_err());
+        assert!(releases.is_empty());
+        assert!(!uploaded);
+
+        // prerelease: 标记为预发布, 保留已上传的附件
+        let (result, releases, uploaded) = sync("prerelease", false)?;
+        assert!(result.is_err());
+        assert!(releases[0].prerelease);
+        assert_eq!(releases[0].assets.len(), 1);
+        assert!(uploaded);
+
+        // none: 保留
+        let (result, releases, uploaded) = sync("none", false)?;
+        assert!(result.is_err());
+        assert!(!releases[0].prerelease);
+        assert_eq!(releases[0].assets.len(), 1);
+        assert!(uploaded);
+
+        // 取消时不回滚: 下次运行从中断处继续
+        let (result, releases, uploaded) = sync("delete", true)?;
+        assert!(matches!(
+            Error::from(result.unwrap_err()),
+            Error::Cancelled { .. }
+        ));
+        assert_eq!(get_tags(&releases), ["v0.1.0"]);
+        assert_eq!(releases[0].assets.len(), 1);
+        assert!(uploaded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_continue_on_error() -> AnyResult<()> {
+        let repo = format!("continue-on-error-test-{}", std::process::id());
+        let source = FakeSource {
+            endpoint: endpoint(Forge::GitHub, &repo),
+            releases: vec![
+                release(3, "v0.3.0", &["c.zip"]),
+                release(2, "v0.2.0", &["bad.zip"]),
+                release(1, "v0.1.0", &["a.zip"]),
+            ],
+        };
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        let sync = |extra: &[&str]| {
+            let mut args = vec![
+                "release2gitee".to_string(),
+                "--github-owner=o".to_string(),
+                format!("--github-repo={repo}"),
+                "--gitee-owner=o".to_string(),
+                "--gitee-repo=r".to_string(),
+                "--gitee-token=t".to_string(),
+                "--no-resume".to_string(),
+            ];
+            args.extend(extra.iter().map(|arg| arg.to_string()));
+            let target = FakeTarget {
+                endpoint: Some(endpoint(Forge::Gitee, "r")),
+                fail_uploads: vec!["bad.zip".to_string()],
+                ..Default::default()
+            };
+            let mut report = SyncReport::default();
+            let result = sync_releases(
+                &Cli::parse_from(args),
+                &source,
+                &target,
+                None,
+                &cancellation,
+                &mut report,
+            );
+            (result, report, target.releases.take())
+        };
+
+        // 默认: 第一个失败的release中止同步
+        let (result, report, releases) = sync(&[]);
+        assert!(result.is_err());
+        assert_eq!(report.failed.len(), 1);
+        assert!(!get_tags(&releases).contains(&"v0.3.0".to_string()));
+
+        // 继续同步后面的release, 最后汇总失败的tag并返回错误
+        let (result, report, releases) = sync(&["--continue-on-error"]);
+        let e = result.unwrap_err();
+        assert_eq!(e.to_string(), "sync failed tags: v0.2.0");
+        assert_eq!(report.failed[0].tag_name, "v0.2.0");
+        assert_eq!(report.failed.len(), 1);
+        let synced = report
+            .synced
+            .iter()
+            .map(|r| r.tag_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(synced, ["v0.1.0", "v0.3.0"]);
+        let v3 = releases.iter().find(|r| r.tag_name == "v0.3.0").unwrap();
+        assert_eq!(v3.assets.len(), 1);
+
+        fs::remove_dir_all(env::temp_dir().join(&repo))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_strict() -> AnyResult<()> {
+        let repo = format!("strict-test-{}", std::process::id());
+        // 同名附件大小不一致: 以新名称上传
+        let mut source_release = release(1, "v0.1.0", &["a.zip"]);
+        source_release.assets[0].size = Some(2);
+        let source = FakeSource {
+            endpoint: endpoint(Forge::GitHub, &repo),
+            releases: vec![source_release],
+        };
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        let sync = |strict: bool| {
+            let mut target_release = release(1, "v0.1.0", &["a.zip"]);
+            target_release.assets[0].id = Some(7);
+            target_release.assets[0].size = Some(1);
+            let target = FakeTarget {
+                endpoint: Some(endpoint(Forge::Gitee, "r")),
+                releases: RefCell::new(vec![target_release]),
+                ..Default::default()
+            };
+            let mut cli = Cli::parse_from([
+                "release2gitee",
+                "--github-owner=o",
+                &format!("--github-repo={repo}"),
+                "--gitee-owner=o",
+                "--gitee-repo=r",
+                "--gitee-token=t",
+                "--on-asset-conflict=rename",
+                "--no-resume",
+                "--no-cache",
+            ]);
+            cli.strict = strict;
+            // 目标仓库已有该版本时仍然对比
+            cli.ignore_lt_gitee_max_version = false;
+            let mut report = SyncReport::default();
+            let result = sync_releases(&cli, &source, &target, None, &cancellation, &mut report);
+            (result, report)
+        };
+
+        // 默认: 只记录警告
+        let (result, report) = sync(false);
+        assert!(result.is_ok());
+        assert_eq!(
+            report.synced[0].warnings,
+            ["asset renamed: a.zip -> a-mirror-1.zip"]
+        );
+
+        // 严格模式: 该release同步失败, 错误中包含不一致的原因
+        let (result, report) = sync(true);
+        match Error::from(result.unwrap_err()) {
+            Error::Diverged {
+                tag_name, reasons, ..
+            } => {
+                assert_eq!(tag_name, "v0.1.0");
+                assert_eq!(reasons, ["asset renamed: a.zip -> a-mirror-1.zip"]);
+            }
+            e => panic!("unexpected error: {e:?}"),
+        }
+        assert_eq!(report.failed[0].tag_name, "v0.1.0");
+
+        fs::remove_dir_all(env::temp_dir().join(&repo))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_and_resume() -> AnyResult<()> {
+        let repo = format!("cancel-resume-test-{}", std::process::id());
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=o",
+            &format!("--github-repo={repo}"),
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+            "--rollback-created-release=delete",
+        ]);
+        let source = FakeSource {
+            endpoint: endpoint(Forge::GitHub, &repo),
+            releases: vec![
+                release(2, "v0.2.0", &["c.zip"]),
+                release(1, "v0.1.0", &["a.zip", "b.zip"]),
+            ],
+        };
+
+        // 上传b.zip时取消: 不回滚, 同步日志保留已上传的a.zip
+        let token = CancellationToken::new();
+        let target = FakeTarget {
+            endpoint: Some(endpoint(Forge::Gitee, "r")),
+            cancel_upload: Some(("b.zip".to_string(), token.clone())),
+            ..Default::default()
+        };
+        let mut report = SyncReport::default();
+        let cancellation = Cancellation::new(&token, None);
+        let result = sync_releases(&cli, &source, &target, None, &cancellation, &mut report);
+        assert!(matches!(
+            Error::from(result.unwrap_err()),
+            Error::Cancelled { .. }
+        ));
+        let releases = target.releases.take();
+        assert_eq!(get_tags(&releases), ["v0.1.0"]);
+        assert_eq!(releases[0].assets.len(), 1);
+        let id = releases[0].id;
+
+        // 下次运行从中断处继续: 只上传b.zip, 不重新创建release
+        let target = FakeTarget {
+            endpoint: Some(endpoint(Forge::Gitee, "r")),
+            releases: RefCell::new(releases),
+            ..Default::default()
+        };
+        let mut report = SyncReport::default();
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        sync_releases(&cli, &source, &target, None, &cancellation, &mut report)?;
+        assert_eq!(report.synced[0].tag_name, "v0.1.0");
+        assert_eq!(report.synced[0].uploaded_assets, ["b.zip"]);
+        assert_eq!(report.synced[1].action, ReleaseAction::Created);
+        let releases = target.releases.borrow();
+        assert_eq!(get_tags(&releases), ["v0.1.0", "v0.2.0"]);
+        assert_eq!((releases[0].id, releases[0].assets.len()), (id, 2));
+        // 全部同步成功后删除同步日志
+        assert!(!Journal::open(&cli)?.is_release_interrupted("v0.1.0"));
+
+        fs::remove_dir_all(env::temp_dir().join(&repo))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_allow_stale_source() -> AnyResult<()> {
+        let repo = format!("stale-source-test-{}", std::process::id());
+        let args = |extra: &[&str]| {
+            let mut args = vec![
+                "release2gitee".to_string(),
+                "--github-owner=o".to_string(),
+                format!("--github-repo={repo}"),
+                "--gitee-owner=o".to_string(),
+                "--gitee-repo=r".to_string(),
+                "--gitee-token=t".to_string(),
+            ];
+            args.extend(extra.iter().map(|arg| arg.to_string()));
+            Cli::parse_from(args)
+        };
+        let cli = args(&["--allow-stale-source=1h"]);
+        let target = || FakeTarget {
+            endpoint: Some(endpoint(Forge::Gitee, "r")),
+            ..Default::default()
+        };
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+
+        // 同步成功时缓存源仓库的releases列表
+        let source = FakeSource {
+            endpoint: endpoint(Forge::GitHub, &repo),
+            releases: vec![release(1, "v0.1.0", &["a.zip"])],
+        };
+        let mut report = SyncReport::default();
+        sync_releases(&cli, &source, &target(), None, &cancellation, &mut report)?;
+
+        // 源仓库不可访问: 使用缓存的列表(不包含之后发布的v0.2.0)继续同步, 并记录警告
+        let unreachable = UnreachableSource(FakeSource {
+            endpoint: endpoint(Forge::GitHub, &repo),
+            releases: vec![
+                release(2, "v0.2.0", &["a.zip"]),
+                release(1, "v0.1.0", &["a.zip"]),
+            ],
+        });
+        let stale_target = target();
+        let mut report = SyncReport::default();
+        sync_releases(
+            &cli,
+            &unreachable,
+            &stale_target,
+            None,
+            &cancellation,
+            &mut report,
+        )?;
+        assert_eq!(get_tags(&stale_target.releases.borrow()), ["v0.1.0"]);
+        assert!(
+            report
+                .warnings
+                .iter()
+                .any(|w| w.starts_with("source releases fetch error, cached releases used"))
+        );
+
+        // 未设置--allow-stale-source: 返回错误
+        let mut report = SyncReport::default();
+        let result = sync_releases(
+            &args(&[]),
+            &unreachable,
+            &target(),
+            None,
+            &cancellation,
+            &mut report,
+        );
+        assert!(http::is_unreachable(&result.unwrap_err()));
+
+        fs::remove_dir_all(env::temp_dir().join(&repo))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_fix_latest_release() -> AnyResult<()> {
+        let repo = format!("fix-latest-test-{}", std::process::id());
+        let args = |extra: &[&str]| {
+            let mut args = vec![
+                "release2gitee".to_string(),
+                "--github-owner=o".to_string(),
+                format!("--github-repo={repo}"),
+                "--gitee-owner=o".to_string(),
+                "--gitee-repo=r".to_string(),
+                "--gitee-token=t".to_string(),
+                "--fix-latest-release".to_string(),
+            ];
+            args.extend(extra.iter().map(|arg| arg.to_string()));
+            let mut cli = Cli::parse_from(args);
+            // 补同步小于目标仓库最大版本的旧版本
+            cli.ignore_lt_gitee_max_version = false;
+            cli
+        };
+        let target = FakeTarget {
+            endpoint: Some(endpoint(Forge::Gitee, "r")),
+            latest_by_creation: true,
+            ..Default::default()
+        };
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        let sync = |cli: &Cli, releases: Vec<Release>| {
+            let source = FakeSource {
+                endpoint: endpoint(Forge::GitHub, &repo),
+                releases,
+            };
+            let mut report = SyncReport::default();
+            sync_releases(cli, &source, &target, None, &cancellation, &mut report)?;
+            AnyResult::Ok(report)
+        };
+        sync(&args(&[]), vec![release(2, "v0.2.0", &["a.zip"])])?;
+
+        // 补同步旧版本: 未确认时不删除, 只记录警告
+        let old = release(1, "v0.1.0", &["a.zip"]);
+        let report = sync(
+            &args(&[]),
+            vec![release(2, "v0.2.0", &["a.zip"]), old.clone()],
+        )?;
+        assert_eq!(get_tags(&target.releases.borrow()), ["v0.2.0", "v0.1.0"]);
+        assert!(
+            report
+                .warnings
+                .iter()
+                .any(|w| w.contains("--allow-recreate-latest"))
+        );
+
+        // 确认后重新创建版本号最大的release, 附件重新上传
+        target
+            .releases
+            .borrow_mut()
+            .retain(|r| r.tag_name != "v0.1.0");
+        let cli = args(&["--allow-recreate-latest", "--no-resume"]);
+        sync(&cli, vec![release(2, "v0.2.0", &["a.zip"]), old])?;
+        let releases = target_releases(&target)?;
+        assert_eq!(get_tags(&releases), ["v0.2.0", "v0.1.0"]);
+        assert_eq!(releases[0].assets.len(), 1);
+        assert!(latest_mismatch(&releases).is_none());
+
+        fs::remove_dir_all(env::temp_dir().join(&repo))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_target_quota() {
+        let sized = |id, tag_name, sizes: &[u64]| {
+            let names = (0..sizes.len())
+                .map(|i| format!("{i}.zip"))
+                .collect::<Vec<_>>();
+            let mut release = release(
+                id,
+                tag_name,
+                &names.iter().map(String::as_str).collect::<Vec<_>>(),
+            );
+            for (asset, size) in release.assets.iter_mut().zip(sizes) {
+                asset.size = Some(*size);
+            }
+            release
+        };
+        // 已用600, 剩余400; 待上传: v3(300) v2(200, 其中100已上传) v1(200)
+        let target_releases = [sized(1, "v0", &[500]), sized(2, "v2", &[100])];
+        let plan = vec![
+            sized(3, "v3", &[300]),
+            sized(2, "v2", &[100, 100]),
+            sized(1, "v1", &[200]),
+        ];
+        let args = [
+            "release2gitee",
+            "--github-owner=o",
+            "--github-repo=r",
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+            "--target-quota=1000",
+        ];
+        let tags = |releases: Vec<Release>| get_tags(&releases).join(",");
+        let cli = Cli::parse_from(args);
+        let mut report = SyncReport::default();
+        assert_eq!(
+            tags(fit_target_quota(
+                &cli,
+                &target_releases,
+                plan.clone(),
+                &mut report
+            )),
+            "v3,v2,v1"
+        );
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.skipped.is_empty());
+        let cli = Cli::parse_from(args.iter().copied().chain(["--on-quota-exceeded=trim"]));
+        let mut report = SyncReport::default();
+        assert_eq!(
+            tags(fit_target_quota(&cli, &target_releases, plan, &mut report)),
+            "v3,v2"
+        );
+        assert_eq!(report.skipped[0].tag_name, "v1");
+        assert_eq!(report.skipped[0].reason, "trimmed for target quota");
+    }
+
+    #[test]
+    fn test_latest_mismatch() {
+        // 新的在前面: 补同步的v1.0.0最后创建
+        let mut releases = vec![
+            release(3, "v1.0.0", &[]),
+            release(2, "v2.0.0", &[]),
+            release(1, "v1.5.0", &[]),
+        ];
+        let tag = |release: Option<&Release>| release.map(|r| r.tag_name.clone());
+        assert_eq!(tag(latest_mismatch(&releases)), Some("v2.0.0".to_string()));
+        // 预发布不作为最新版本
+        releases[0].prerelease = true;
+        assert_eq!(tag(latest_mismatch(&releases)), None);
+        releases[1].prerelease = true;
+        assert_eq!(tag(latest_mismatch(&releases)), None);
+        assert_eq!(tag(latest_mismatch(&[])), None);
+    }
+
+    #[test]
+    fn test_label_name() {
+        assert_eq!(
+            label_name("app.dmg", "macOS (Apple Silicon)"),
+            "app_macOS_Apple_Silicon.dmg"
+        );
+        assert_eq!(
+            label_name("app-1.0.tar.gz", "Linux x64"),
+            "app-1.0_Linux_x64.tar.gz"
+        );
+        assert_eq!(label_name("LICENSE", "许可证"), "LICENSE_许可证");
+        assert_eq!(label_name(".env", "()"), ".env");
+    }
+
+    #[test]
+    fn test_source_archives() {
+        let args = [
+            "release2gitee",
+            "--github-owner=o",
+            "--github-repo=r",
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+        ];
+        let source = FakeSource {
+            endpoint: endpoint(Forge::GitHub, "r"),
+            releases: Vec::new(),
+        };
+        let archived = with_source_archives(
+            &source,
+            &Cli::parse_from(args.iter().copied().chain(["--source-archives"])),
+            release(1, "app/v1.0.0", &["a.zip"]),
+        );
+        let assets = archived
+            .assets
+            .iter()
+            .map(|asset| (asset.name.as_str(), asset.browser_download_url.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            assets[1..],
+            [
+                (
+                    "source-app-v1.0.0.tar.gz",
+                    "https://example.com/o/r/archive/refs/tags/app/v1.0.0.tar.gz"
+                ),
+                (
+                    "source-app-v1.0.0.zip",
+                    "https://example.com/o/r/archive/refs/tags/app/v1.0.0.zip"
+                ),
+            ]
+        );
+        let archived = with_source_archives(&source, &Cli::parse_from(args), release(1, "v1", &[]));
+        assert!(archived.assets.is_empty());
+    }
+
+    #[test]
+    fn test_asset_conflict() -> AnyResult<()> {
+        let asset = |name: &str, size| Assert {
+            id: None,
+            name: name.to_string(),
+            size: Some(size),
+            browser_download_url: String::new(),
+            digest: None,
+            label: None,
+            download_count: None,
+            content_type: None,
+            api_url: None,
+        };
+        assert_eq!(split_extension("app.tar.gz"), ("app", ".tar.gz"));
+        assert_eq!(split_extension("app.dmg"), ("app", ".dmg"));
+        assert_eq!(split_extension(".env"), (".env", ""));
+        assert_eq!(
+            mirror_asset_name(&asset("app.tar.gz", 2), &[asset("app.tar.gz", 1)]),
+            Some("app-mirror-1.tar.gz".to_string())
+        );
+        assert_eq!(
+            mirror_asset_name(&asset("app", 2), &[asset("app-mirror-1", 3)]),
+            Some("app-mirror-2".to_string())
+        );
+        // 已经以新名称上传过
+        assert_eq!(
+            mirror_asset_name(&asset("app", 2), &[asset("app-mirror-1", 2)]),
+            None
+        );
+
+        // 冲突附件的处理: 需要上传的附件, 目标仓库中保留的附件
+        for (policy, upload, remain) in [
+            ("skip", vec![], vec!["a.zip"]),
+            ("replace", vec!["a.zip"], vec![]),
+            ("rename", vec!["a-mirror-1.zip"], vec!["a.zip"]),
+        ] {
+            let cli = Cli::parse_from([
+                "release2gitee",
+                "--github-owner=o",
+                "--github-repo=r",
+                "--gitee-owner=o",
+                "--gitee-repo=r",
+                "--gitee-token=t",
+                &format!("--on-asset-conflict={policy}"),
+            ]);
+            let mut source_release = release(1, "v0.1.0", &["a.zip"]);
+            source_release.assets[0].size = Some(2);
+            let mut target_release = release(1, "v0.1.0", &[]);
+            target_release.assets.push(Assert {
+                id: Some(7),
+                ..asset("a.zip", 1)
+            });
+            let target = FakeTarget {
+                endpoint: Some(endpoint(Forge::Gitee, "r")),
+                releases: RefCell::new(vec![target_release.clone()]),
+                ..Default::default()
+            };
+
+            let mut divergences = Vec::new();
+            let renamed = resolve_asset_conflicts(
+                &target,
+                &cli,
+                &source_release,
+                &mut target_release,
+                &mut divergences,
+            )?;
+            assert_eq!(
+                divergences.is_empty(),
+                cli.on_asset_conflict == AssetConflict::Replace,
+                "{policy}"
+            );
+            let mut diff = release_asserts_diff(&source_release, &target_release);
+            diff.extend(renamed);
+            let names =
+                |assets: &[Assert]| assets.iter().map(|a| a.name.clone()).collect::<Vec<_>>();
+            assert_eq!(names(&diff), upload, "{policy}");
+            assert_eq!(
+                names(&target.releases.borrow()[0].assets),
+                remain,
+                "{policy}"
+            );
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,187 @@
+use crate::AnyResult;
+use crate::cancel::Cancellation;
+use crate::model::{Assert, Release, RepoEndpoint};
+use crate::provider::ReleaseTarget;
+use log::debug;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// 目标仓库release列表的进程内缓存: 按仓库接口地址保存
+/// - 常驻运行(定时同步)时多次同步之间共享, 频繁轮询不会耗尽gitee每小时的读接口限额
+static LISTINGS: Mutex<Option<HashMap<String, Listing>>> = Mutex::new(None);
+
+struct Listing {
+    fetched_at: Instant,
+    releases: Vec<Release>,
+}
+
+/// 带TTL缓存的目标仓库(--listing-cache-ttl)
+/// - 缓存未过期时直接返回release列表, 不访问接口
+/// - 任何写操作(创建、修改、删除release或附件)后立即失效, 同步后的变化在下一次读取时反映
+pub struct CachedTarget {
+    inner: Box<dyn ReleaseTarget>,
+    key: String,
+    ttl: Duration,
+}
+
+impl CachedTarget {
+    pub fn new(inner: Box<dyn ReleaseTarget>, ttl: Duration) -> Self {
+        let endpoint = inner.endpoint();
+        let key = format!("{}:{}", endpoint.forge, endpoint.api_url());
+        CachedTarget { inner, key, ttl }
+    }
+
+    fn cached(&self) -> Option<Vec<Release>> {
+        let listings = LISTINGS.lock().unwrap_or_else(PoisonError::into_inner);
+        let listing = listings.as_ref()?.get(&self.key)?;
+        (listing.fetched_at.elapsed() < self.ttl).then(|| listing.releases.clone())
+    }
+
+    fn store(&self, releases: &[Release]) {
+        let mut listings = LISTINGS.lock().unwrap_or_else(PoisonError::into_inner);
+        listings.get_or_insert_default().insert(
+            self.key.clone(),
+            Listing {
+                fetched_at: Instant::now(),
+                releases: releases.to_vec(),
+            },
+        );
+    }
+
+    fn invalidate(&self) {
+        let mut listings = LISTINGS.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(listings) = listings.as_mut() {
+            listings.remove(&self.key);
+        }
+    }
+
+    /// 写操作无论成功与否都使缓存失效(失败时可能已部分写入)
+    fn write<T>(&self, f: impl FnOnce(&dyn ReleaseTarget) -> AnyResult<T>) -> AnyResult<T> {
+        let result = f(self.inner.as_ref());
+        self.invalidate();
+        result
+    }
+}
+
+impl ReleaseTarget for CachedTarget {
+    fn endpoint(&self) -> &RepoEndpoint {
+        self.inner.endpoint()
+    }
+
+    fn releases(&self) -> AnyResult<Vec<Release>> {
+        if let Some(releases) = self.cached() {
+            debug!("releases listing cache hit: {}", self.key);
+            return Ok(releases);
+        }
+        let releases = self.inner.releases()?;
+        self.store(&releases);
+        Ok(releases)
+    }
+
+    fn create_release(&self, release: &Release) -> AnyResult<Release> {
+        self.write(|target| target.create_release(release))
+    }
+
+    fn update_release(&self, release: &Release) -> AnyResult<()> {
+        self.write(|target| target.update_release(release))
+    }
+
+    fn delete_release(&self, release: &Release) -> AnyResult<()> {
+        self.write(|target| target.delete_release(release))
+    }
+
+    fn upload_asset(
+        &self,
+        release: &Release,
+        asset_name: &str,
+        file_path: &Path,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()> {
+        self.write(|target| target.upload_asset(release, asset_name, file_path, cancellation))
+    }
+
+    fn delete_asset(&self, release: &Release, asset: &Assert) -> AnyResult<()> {
+        self.write(|target| target.delete_asset(release, asset))
+    }
+
+    fn supports_prerelease(&self) -> bool {
+        self.inner.supports_prerelease()
+    }
+
+    fn body_max_chars(&self) -> Option<usize> {
+        self.inner.body_max_chars()
+    }
+
+    fn ensure_tag(&self, tag_name: &str, git_ref: &str) -> AnyResult<bool> {
+        self.inner.ensure_tag(tag_name, git_ref)
+    }
+
+    fn mark_latest(&self, release: &Release) -> AnyResult<bool> {
+        self.write(|target| target.mark_latest(release))
+    }
+
+    fn supports_asset_label(&self) -> bool {
+        self.inner.supports_asset_label()
+    }
+
+    fn label_asset(&self, release: &Release, asset_name: &str, label: &str) -> AnyResult<()> {
+        self.write(|target| target.label_asset(release, asset_name, label))
+    }
+
+    fn latest_by_creation(&self) -> bool {
+        self.inner.latest_by_creation()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::LocalTarget;
+    use crate::model::Forge;
+    use std::{env, fs};
+
+    #[test]
+    fn test_cached_target() -> AnyResult<()> {
+        let dir = env::temp_dir().join(format!("listing-cache-test-{}", std::process::id()));
+        let endpoint = RepoEndpoint {
+            forge: Forge::Local,
+            host: dir.display().to_string(),
+            owner: String::new(),
+            repo: dir.display().to_string(),
+            token: None,
+        };
+        let local = || LocalTarget::open(endpoint.clone(), &dir, false);
+        let target = CachedTarget::new(Box::new(local()?), Duration::from_secs(60));
+        assert!(target.releases()?.is_empty());
+
+        let release = Release {
+            id: 100,
+            tag_name: "v1.0.0".to_string(),
+            name: "v1.0.0".to_string(),
+            body: None,
+            prerelease: false,
+            draft: false,
+            target_commitish: "main".to_string(),
+            updated_at: None,
+            created_at: None,
+            published_at: None,
+            assets: Vec::new(),
+        };
+        // 绕过缓存写入: 未过期时仍返回缓存的列表
+        local()?.create_release(&release)?;
+        assert!(target.releases()?.is_empty());
+
+        // 通过缓存写入后立即失效
+        let created = target.create_release(&Release {
+            tag_name: "v1.1.0".to_string(),
+            ..release
+        })?;
+        assert_eq!(target.releases()?.len(), 2);
+        target.delete_release(&created)?;
+        assert_eq!(target.releases()?.len(), 1);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+use crate::AnyResult;
+use crate::cancel::Cancellation;
+use crate::fsutil;
+use crate::model::{Assert, Release, RepoEndpoint};
+use crate::provider::ReleaseTarget;
+use anyhow::bail;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 每个release目录中的元数据文件
+const METADATA_FILE: &str = "metadata.json";
+
+/// release的元数据(metadata.json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseMetadata {
+    pub id: u64,
+    pub tag_name: String,
+    pub name: String,
+    pub body: Option<String>,
+    pub prerelease: bool,
+    pub target_commitish: String,
+    pub assets: Vec<MetadataAsset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataAsset {
+    pub name: String,
+    pub size: u64,
+    // 离线环境中校验附件
+    pub sha256: String,
+}
+
+/// 本地目录目标: 每个release一个目录 {output_dir}/{tag}/, 包含附件和metadata.json
+/// - 可直接用nginx提供下载, 或rsync到隔离网络
+pub struct LocalTarget {
+    endpoint: RepoEndpoint,
+    dir: PathBuf,
+    // 网络文件系统或--fsync: 先写临时文件, fsync后再重命名
+    durable: bool,
+}
+
+impl LocalTarget {
+    pub fn open(endpoint: RepoEndpoint, dir: &Path, fsync: bool) -> AnyResult<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(LocalTarget {
+            endpoint,
+            dir: dir.to_path_buf(),
+            durable: fsync || fsutil::is_remote_fs(dir),
+        })
+    }
+
+    fn release_dir(&self, tag_name: &str) -> PathBuf {
+        self.dir.join(tag_name)
+    }
+
+    fn read_metadata(&self, tag_name: &str) -> AnyResult<Option<ReleaseMetadata>> {
+        let path = self.release_dir(tag_name).join(METADATA_FILE);
+        match fs::read(&path) {
+            Ok(content) => Ok(Some(serde_json::from_slice(&content)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_metadata(&self, metadata: &ReleaseMetadata) -> AnyResult<()> {
+        let dir = self.release_dir(&metadata.tag_name);
+        fs::create_dir_all(&dir)?;
+        let content = serde_json::to_vec_pretty(metadata)?;
+        fsutil::write_file(&dir.join(METADATA_FILE), content, self.durable)
+    }
+
+    /// 修改release的元数据并写回
+    fn update_metadata(
+        &self,
+        tag_name: &str,
+        f: impl FnOnce(&mut ReleaseMetadata),
+    ) -> AnyResult<()> {
+        let Some(mut metadata) = self.read_metadata(tag_name)? else {
+            bail!("local release not found: {tag_name}");
+        };
+        f(&mut metadata);
+        self.write_metadata(&metadata)
+    }
+
+    /// 所有包含metadata.json的目录: 新的(id大的)在前面
+    pub fn metadata(&self) -> AnyResult<Vec<ReleaseMetadata>> {
+        let mut releases = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let tag_name = entry.file_name().to_string_lossy().to_string();
+            match self.read_metadata(&tag_name) {
+                Ok(Some(metadata)) => releases.push(metadata),
+                Ok(None) => {}
+                Err(e) => warn!("local release metadata invalid, skip: {tag_name}, {e:#}"),
+            }
+        }
+        releases.sort_by_key(|r| std::cmp::Reverse(r.id));
+        Ok(releases)
+    }
+
+    /// 附件的下载地址为 {host}/{tag}/{name}
+    pub fn to_release(&self, metadata: ReleaseMetadata) -> Release {
+        let assets = metadata
+            .assets
+            .iter()
+            .map(|asset| Assert {
+                id: None,
+                name: asset.name.clone(),
+                size: Some(asset.size),
+                browser_download_url: format!(
+                    "{}/{}/{}",
+                    self.endpoint.host, metadata.tag_name, asset.name
+                ),
+                digest: Some(format!("sha256:{}", asset.sha256)),
+                label: None,
+                download_count: None,
+                content_type: None,
+                api_url: None,
+            })
+            .collect();
+        Release {
+            id: metadata.id,
+            tag_name: metadata.tag_name,
+            name: metadata.name,
+            body: metadata.body,
+            prerelease: metadata.prerelease,
+            draft: false,
+            target_commitish: metadata.target_commitish,
+            updated_at: None,
+            created_at: None,
+            published_at: None,
+            assets,
+        }
+    }
+}
+
+impl ReleaseTarget for LocalTarget {
+    fn endpoint(&self) -> &RepoEndpoint {
+        &self.endpoint
+    }
+
+    fn releases(&self) -> AnyResult<Vec<Release>> {
+        let metadata = self.metadata()?;
+        Ok(metadata
+            .into_iter()
+            .map(|metadata| self.to_release(metadata))
+            .collect())
+    }
+
+    fn create_release(&self, release: &Release) -> AnyResult<Release> {
+        let id = self.releases()?.iter().map(|r| r.id).max().unwrap_or(0) + 1;
+        let metadata = ReleaseMetadata {
+            id,
+            tag_name: release.tag_name.clone(),
+            name: release.name.clone(),
+            body: release.body.clone(),
+            prerelease: release.prerelease,
+            target_commitish: release.target_commitish.clone(),
+            assets: Vec::new(),
+        };
+        self.write_metadata(&metadata)?;
+        Ok(self.to_release(metadata))
+    }
+
+    fn update_release(&self, release: &Release) -> AnyResult<()> {
+        self.update_metadata(&release.tag_name, |metadata| {
+            metadata.name = release.name.clone();
+            metadata.body = release.body.clone();
+            metadata.prerelease = release.prerelease;
+        })
+    }
+
+    /// 删除release目录(包括附件)
+    fn delete_release(&self, release: &Release) -> AnyResult<()> {
+        let dir = self.release_dir(&release.tag_name);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        Ok(())
+    }
+
+    fn upload_asset(
+        &self,
+        release: &Release,
+        asset_name: &str,
+        file_path: &Path,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()> {
+        cancellation.check()?;
+        let dir = self.release_dir(&release.tag_name);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(asset_name);
+        let mut writer = fsutil::FileWriter::create(&path, self.durable)?;
+        io::copy(&mut fs::File::open(file_path)?, &mut writer)?;
+        writer.commit()?;
+
+        let size = path.metadata()?.len();
+        let sha256 = fsutil::sha256_file(&path)?;
+        info!("local asset copy success: {}", path.display());
+        self.update_metadata(&release.tag_name, |metadata| {
+            metadata.assets.retain(|asset| asset.name != asset_name);
+            metadata.assets.push(MetadataAsset {
+                name: asset_name.to_string(),
+                size,
+                sha256,
+            });
+        })
+    }
+
+    fn delete_asset(&self, release: &Release, asset: &Assert) -> AnyResult<()> {
+        let path = self.release_dir(&release.tag_name).join(&asset.name);
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        self.update_metadata(&release.tag_name, |metadata| {
+            metadata.assets.retain(|a| a.name != asset.name);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::CancellationToken;
+    use crate::model::Forge;
+    use std::env;
+
+    #[test]
+    fn test_local_target() -> AnyResult<()> {
+        let dir = env::temp_dir().join(format!("local-target-test-{}", std::process::id()));
+        let endpoint = RepoEndpoint {
+            forge: Forge::Local,
+            host: "https://dl.example.com".to_string(),
+            owner: String::new(),
+            repo: dir.display().to_string(),
+            token: None,
+        };
+        let target = LocalTarget::open(endpoint, &dir, false)?;
+        assert!(target.releases()?.is_empty());
+
+        let release = Release {
+            id: 100,
+            tag_name: "v1.0.0".to_string(),
+            name: "v1.0.0".to_string(),
+            body: Some("notes".to_string()),
+            prerelease: false,
+            draft: false,
+            target_commitish: "main".to_string(),
+            updated_at: None,
+            created_at: None,
+            published_at: None,
+            assets: Vec::new(),
+        };
+        let created = target.create_release(&release)?;
+        assert_eq!(created.id, 1);
+
+        let file = env::temp_dir().join(format!("local-target-asset-{}", std::process::id()));
+        fs::write(&file, "asset")?;
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        target.upload_asset(&created, "a.zip", &file, &cancellation)?;
+        fs::remove_file(&file)?;
+
+        assert_eq!(fs::read(dir.join("v1.0.0/a.zip"))?, b"asset".to_vec());
+        let releases = target.releases()?;
+        assert_eq!(releases[0].assets[0].size, Some(5));
+        assert_eq!(
+            releases[0].assets[0].browser_download_url,
+            "https://dl.example.com/v1.0.0/a.zip"
+        );
+
+        target.delete_release(&releases[0])?;
+        assert!(target.releases()?.is_empty());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
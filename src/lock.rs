@@ -0,0 +1,62 @@
+use crate::AnyResult;
+use crate::model::Cli;
+use log::debug;
+use std::env;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+
+/// 同步锁: 同一个gitee仓库同时只允许一个同步进程(文件锁, 进程退出后自动释放)
+#[derive(Debug)]
+pub struct SyncLock {
+    _file: File,
+}
+
+impl SyncLock {
+    /// 获取同步锁, 已被其他进程持有时返回None
+    pub fn acquire(cli: &Cli) -> AnyResult<Option<SyncLock>> {
+        let path = env::temp_dir().join(format!("release2gitee-{}.lock", cli.mirror_key()));
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+
+        match file.try_lock() {
+            Ok(()) => {
+                // 记录持有锁的进程id, 方便排查
+                file.set_len(0)?;
+                writeln!(file, "{}", std::process::id())?;
+                debug!("sync lock acquired: {}", path.display());
+                Ok(Some(SyncLock { _file: file }))
+            }
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_sync_lock() -> AnyResult<()> {
+        let repo = format!("lock-test-{}", std::process::id());
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=o",
+            "--github-repo=r",
+            "--gitee-owner=o",
+            &format!("--gitee-repo={repo}"),
+            "--gitee-token=t",
+        ]);
+
+        let lock = SyncLock::acquire(&cli)?;
+        assert!(lock.is_some());
+        assert!(SyncLock::acquire(&cli)?.is_none());
+        drop(lock);
+        assert!(SyncLock::acquire(&cli)?.is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::AnyResult;
+use crate::provenance;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 按大小和日期轮转的日志文件(--log-file): 常驻运行(定时同步)时磁盘上只保留有限的历史
+/// - 超过max_size或跨天(UTC)时轮转: app.log -> app.log.1 -> app.log.2 ..., 最多保留keep个
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    keep: usize,
+    file: File,
+    size: u64,
+    day: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_size: u64, keep: usize) -> AnyResult<Self> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let metadata = file.metadata()?;
+        // 已有的日志文件按修改日期判断是否跨天
+        let day = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or_else(today, |elapsed| elapsed.as_secs() / 86400);
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            max_size,
+            keep,
+            file,
+            size: metadata.len(),
+            day,
+        })
+    }
+
+    /// 编号为n的历史文件: app.log.n
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.keep == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.keep));
+            for n in (1..self.keep).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn today() -> u64 {
+    provenance::now_secs() / 86400
+}
+
+impl Write for RotatingFile {
+    /// 每条日志整体写入: 写入前判断是否需要轮转, 一条日志不会被拆分到两个文件
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let day = today();
+        if self.size > 0 && (self.size + buf.len() as u64 > self.max_size || day != self.day) {
+            self.rotate()?;
+        }
+        self.day = day;
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// 日志同时输出到标准错误和日志文件
+pub struct Tee<W>(pub W);
+
+impl<W: Write> Write for Tee<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write_all(buf)?;
+        self.0.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()?;
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_rotating_file() -> AnyResult<()> {
+        let dir = env::temp_dir().join(format!("logfile-test-{}", std::process::id()));
+        let path = dir.join("sync.log");
+        let mut file = RotatingFile::open(&path, 10, 2)?;
+        for line in ["line-1\n", "line-2\n", "line-3\n", "line-4\n"] {
+            file.write_all(line.as_bytes())?;
+        }
+        file.flush()?;
+
+        // 超过10字节时轮转, 最多保留2个历史文件
+        assert_eq!(fs::read_to_string(&path)?, "line-4\n");
+        assert_eq!(fs::read_to_string(dir.join("sync.log.1"))?, "line-3\n");
+        assert_eq!(fs::read_to_string(dir.join("sync.log.2"))?, "line-2\n");
+        assert!(!dir.join("sync.log.3").exists());
+
+        // 重新打开时继续追加
+        let mut file = RotatingFile::open(&path, 100, 2)?;
+        file.write_all(b"line-5\n")?;
+        assert_eq!(fs::read_to_string(&path)?, "line-4\nline-5\n");
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
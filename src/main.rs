@@ -1,21 +1,195 @@
 use clap::Parser;
-use log::info;
-use release2gitee::model::Cli;
-use release2gitee::sync_github_releases_to_gitee;
+use log::{error, info, warn};
+use release2gitee::cancel::CancellationToken;
+use release2gitee::error::ExitStatus;
+use release2gitee::i18n::{self, Lang, tr};
+use release2gitee::logfile::{RotatingFile, Tee};
+use release2gitee::model::{Cli, Command, SyncConfig};
+use release2gitee::report::SyncReport;
+use release2gitee::schedule::{self, Schedule};
+use release2gitee::status::{self, StatusBoard};
+use release2gitee::tui::{self, LogPane};
+use release2gitee::{AnyResult, bench, bundle, redact, serve, stats};
+use release2gitee::{SyncOptions, sync_github_releases_to_gitee};
+use std::io::Write;
+use std::process::ExitCode;
+use std::sync::Arc;
 
 // [Rust 中的命令行应用程序](https://cli.rust-lang.net.cn/book/index.html)
-fn main() -> anyhow::Result<()> {
+fn main() -> ExitCode {
     // 参数解析和日志配置
-    let cli = &Cli::parse();
-    env_logger::Builder::new()
+    let mut cli = Cli::parse();
+    let mut logger = env_logger::Builder::new();
+    logger
         .filter_level(cli.verbosity.into())
-        .format_target(false)
-        .init();
+        .format_target(false);
+    // 日志同时写入轮转的日志文件
+    let mut log_file = None;
+    if let Some(path) = &cli.log_file {
+        match RotatingFile::open(path, cli.log_max_size.as_u64(), cli.log_keep) {
+            Ok(file) => log_file = Some(file),
+            Err(e) => {
+                eprintln!("open log file error: {}, {e:#}", path.display());
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    // 终端界面显示期间日志写入日志面板
+    if cli.tui {
+        let file = log_file.map(|file| Box::new(file) as Box<dyn Write + Send>);
+        logger.target(env_logger::Target::Pipe(Box::new(LogPane(file))));
+    } else if let Some(file) = log_file {
+        logger.target(env_logger::Target::Pipe(Box::new(Tee(file))));
+    }
+    redact::init_logger(logger);
+    i18n::set_lang(cli.lang.unwrap_or_else(Lang::from_locale));
+    if let Err(e) = cli.resolve_tokens() {
+        error!("{e:#}");
+        return ExitCode::FAILURE;
+    }
+    let cli = &cli;
 
     info!("params: {cli}");
 
-    // 同步程序
-    sync_github_releases_to_gitee(cli)?;
-    info!("sync success finish");
-    Ok(())
+    // token保存到系统密钥环
+    if let Some(Command::Auth(args)) = &cli.command {
+        return match release2gitee::auth::run(args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("auth failed: {e:#}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // 性能测试: 本地模拟服务, 输出吞吐量和请求次数
+    if let Some(Command::Bench(args)) = &cli.command {
+        return match bench::run(cli, args) {
+            Ok(report) => {
+                println!("{report}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                error!("bench failed: {e:?}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // webhook服务: 收到release事件后同步
+    if let Some(Command::Serve(args)) = &cli.command {
+        return match serve::run(cli, args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("serve failed: {e:?}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // 下载次数统计: 汇总github和gitee每个附件的下载次数
+    if let Some(Command::Stats(args)) = &cli.command {
+        return match stats::run(cli, args) {
+            Ok(output) => {
+                println!("{output}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                error!("stats failed: {e:?}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // 定时同步: 常驻运行, 每次同步的失败只记录日志
+    if let Some(schedule) = &cli.schedule {
+        return run_scheduled(cli, schedule);
+    }
+
+    let result = run_sync(cli);
+    ExitCode::from(exit_status(cli, result).code())
+}
+
+/// 同步程序: 同步、离线导出或导入
+fn run_sync(cli: &Cli) -> AnyResult<SyncReport> {
+    let config = SyncConfig::from(cli);
+    let cancel = &CancellationToken::new();
+    let sync = || {
+        match &cli.command {
+            // 离线同步: 导出tar包, 在另一台机器上导入
+            Some(Command::Export(args)) => bundle::export(cli, &config, cancel, args),
+            Some(Command::Import(args)) => bundle::import(cli, &config, cancel, args),
+            _ => sync_github_releases_to_gitee(&SyncOptions::from(cli), cancel),
+        }
+        .map_err(release2gitee::Error::into_anyhow)
+    };
+    if cli.tui {
+        tui::run(cli, cancel, sync).and_then(|result| result)
+    } else {
+        sync()
+    }
+}
+
+/// 根据失败类别返回不同的退出码
+fn exit_status(cli: &Cli, result: AnyResult<SyncReport>) -> ExitStatus {
+    match result {
+        Ok(report) if report.is_nothing_to_sync() => {
+            info!(
+                "{}",
+                tr(
+                    "sync success finish, nothing to sync",
+                    "同步完成, 没有需要同步的内容"
+                )
+            );
+            if cli.detailed_exit_codes {
+                ExitStatus::NothingToSync
+            } else {
+                ExitStatus::Success
+            }
+        }
+        Ok(report) => {
+            info!("{}: {report}", tr("sync success finish", "同步完成"));
+            ExitStatus::Success
+        }
+        Err(e) => match ExitStatus::from_error(&e) {
+            // 其他同步进程正在运行, 本次直接退出
+            ExitStatus::AlreadyRunning => {
+                warn!("{e}, exit");
+                if cli.detailed_exit_codes {
+                    ExitStatus::AlreadyRunning
+                } else {
+                    ExitStatus::Success
+                }
+            }
+            status => {
+                error!("{}: {e:?}", tr("sync failed", "同步失败"));
+                status
+            }
+        },
+    }
+}
+
+/// 按cron表达式定时同步, 设置--status-listen时提供/healthz和/status
+fn run_scheduled(cli: &Cli, schedule: &Schedule) -> ExitCode {
+    let offset = cli.schedule_utc_offset;
+    let board = Arc::new(StatusBoard::new());
+    if let Some(listen) = &cli.status_listen
+        && let Err(e) = status::spawn_server(listen, board.clone())
+    {
+        error!("status server failed: {e:#}");
+        return ExitCode::FAILURE;
+    }
+    let repo = format!("{}/{}", cli.github_owner, cli.github_repo);
+    loop {
+        let Some(next) = schedule.next(offset) else {
+            error!("schedule never matches: {schedule}");
+            return ExitCode::FAILURE;
+        };
+        info!("next sync at {}", schedule::format_time(next, offset));
+        schedule::sleep_until(next);
+        let started = board.start(&repo);
+        let result = run_sync(cli);
+        board.finish(&repo, started, &result);
+        exit_status(cli, result);
+    }
 }
@@ -0,0 +1,104 @@
+use crate::AnyResult;
+use crate::model::{Assert, Cli, LocalizedNotesMode, Release};
+use crate::provider::ReleaseSource;
+use log::info;
+use std::fs;
+
+/// 本地化的release notes: 读取源仓库tag下的CHANGELOG.{lang}.md中该版本的章节
+/// - append: 追加到body末尾
+/// - attach: 写入临时目录作为附件上传(RELEASE_NOTES.{lang}.md), 与其他附件一样对比差异
+pub fn localize_release(
+    source: &dyn ReleaseSource,
+    cli: &Cli,
+    release: &Release,
+) -> AnyResult<Release> {
+    let mut release = release.clone();
+    let Some(lang) = &cli.attach_localized_notes else {
+        return Ok(release);
+    };
+
+    let path = format!("CHANGELOG.{lang}.md");
+    let tag_name = &release.tag_name;
+    let Some(changelog) = source.file_content(&path, tag_name)? else {
+        info!("localized changelog not found: {path}, {tag_name}");
+        return Ok(release);
+    };
+    let Some(section) = changelog_section(&changelog, tag_name) else {
+        info!("localized changelog section not found: {path}, {tag_name}");
+        return Ok(release);
+    };
+
+    match cli.localized_notes_mode {
+        LocalizedNotesMode::Append => {
+            let body = release.body.unwrap_or_default();
+            release.body = Some(format!("{}\n\n---\n\n{section}", body.trim_end()));
+        }
+        LocalizedNotesMode::Attach => {
+            let name = format!("RELEASE_NOTES.{lang}.md");
+            let file_path = crate::tmp_dir_repo_tag(cli, &release)?.join(&name);
+            // 文件已存在且大小一致时跳过下载, 直接上传
+            fs::write(&file_path, &section)?;
+            release.assets.push(Assert {
+                id: None,
+                name,
+                size: Some(section.len() as u64),
+                browser_download_url: String::new(),
+                digest: None,
+                label: None,
+                download_count: None,
+                content_type: None,
+                api_url: None,
+            });
+        }
+    }
+    Ok(release)
+}
+
+/// changelog中版本对应的章节: 标题包含tag名称或版本号(忽略v前缀), 到下一个同级或更高级的标题为止
+/// - 如: ## [1.2.0] - 2024-01-01, ## v1.2.0
+pub fn changelog_section(changelog: &str, tag_name: &str) -> Option<String> {
+    let version = tag_name.trim_start_matches('v');
+    let mut level = None;
+    let mut lines = Vec::new();
+    for line in changelog.lines() {
+        let heading = heading_level(line);
+        match (level, heading) {
+            (None, Some(h)) if heading_matches(line, version) => level = Some(h),
+            (Some(l), Some(h)) if h <= l => break,
+            (Some(_), _) => lines.push(line),
+            _ => {}
+        }
+    }
+    let section = lines.join("\n").trim().to_string();
+    (!section.is_empty()).then_some(section)
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    (level > 0 && line[level..].starts_with(' ')).then_some(level)
+}
+
+// 标题按非版本号字符切分后, 存在与版本号一致的部分
+fn heading_matches(line: &str, version: &str) -> bool {
+    line.split(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '-' | '+')))
+        .any(|word| word.trim_start_matches('v') == version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changelog_section() {
+        let changelog = "# 更新日志\n\n## [1.2.0-beta] - 2024-02-01\n\n- 测试版\n\n## [1.2.0] - 2024-01-01\n\n### 新功能\n\n- 支持gitea\n\n## v1.1.0\n\n- 修复\n";
+        assert_eq!(
+            changelog_section(changelog, "v1.2.0").as_deref(),
+            Some("### 新功能\n\n- 支持gitea")
+        );
+        assert_eq!(
+            changelog_section(changelog, "1.1.0").as_deref(),
+            Some("- 修复")
+        );
+        assert_eq!(changelog_section(changelog, "v1.0.0"), None);
+    }
+}
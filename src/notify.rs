@@ -0,0 +1,896 @@
+//! 同步结束后的通知: 汇总同步的版本、失败和上传的字节数, 发送到钉钉、飞书、企业微信群机器人或邮件等(--notify-on设置发送的时机)
+
+use crate::AnyResult;
+use crate::http::{self, check_status};
+use crate::i18n::tr;
+use crate::model::{Cli, NotifyOn, SmtpTls};
+use crate::provenance;
+use crate::redact;
+use crate::report::{ReleaseAction, SyncReport};
+use crate::trace::SendTraced;
+use crate::units::{ByteSize, HumanDuration};
+use anyhow::{Context, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use reqwest::blocking::Client;
+use serde_json::{Map, Value, json};
+use sha2::Sha256;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 通知中列出的release个数上限, 超出部分只显示个数
+const MAX_LISTED: usize = 20;
+
+/// 通知渠道
+trait Notifier {
+    fn name(&self) -> &'static str;
+
+    /// 只在同步失败时通知(手机推送等)
+    fn failure_only(&self) -> bool {
+        false
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()>;
+}
+
+/// 同步结果的汇总
+struct Summary<'a> {
+    title: String,
+    report: &'a SyncReport,
+    // 非release维度的失败(如查询releases信息失败)
+    error: Option<String>,
+    elapsed: Duration,
+}
+
+impl Summary<'_> {
+    fn is_failed(&self) -> bool {
+        self.error.is_some() || !self.report.failed.is_empty()
+    }
+
+    /// 有同步的release: 新建、更新或上传了附件
+    fn synced_tags(&self) -> Vec<&str> {
+        self.report
+            .synced
+            .iter()
+            .filter(|release| {
+                release.action != ReleaseAction::Unchanged || !release.uploaded_assets.is_empty()
+            })
+            .map(|release| release.tag_name.as_str())
+            .collect()
+    }
+
+    /// 标题: ✅/❌ release2gitee: github:hepengju/redis-me -> gitee:hepengju/redis-me
+    fn heading(&self) -> String {
+        let icon = if self.is_failed() { "❌" } else { "✅" };
+        format!("{icon} {}", self.title)
+    }
+
+    /// markdown格式的内容: 标题和汇总
+    fn markdown(&self) -> String {
+        format!(
+            "### {}\n\n{}",
+            self.heading(),
+            self.details(Markup::Markdown)
+        )
+    }
+
+    /// 汇总: 同步的版本、失败的版本及原因、上传的字节数、耗时(钉钉等的markdown不支持表格, 使用列表)
+    fn details(&self, markup: Markup) -> String {
+        let mut text = String::new();
+        // - **label** (count): value
+        let mut item = |label: &str, count: Option<usize>, value: String| {
+            let count = count.map(|count| format!(" ({count})")).unwrap_or_default();
+            let value = markup.escape(&value);
+            let _ = writeln!(text, "- {}{count}: {value}", markup.bold(label));
+        };
+        let synced = self.synced_tags();
+        item(
+            tr("synced", "已同步"),
+            Some(synced.len()),
+            listed(synced.iter().map(|tag| tag.to_string())),
+        );
+        if !self.report.failed.is_empty() {
+            item(
+                tr("failed", "失败"),
+                Some(self.report.failed.len()),
+                listed(
+                    self.report.failed.iter().map(|release| {
+                        format!("{}: {}", release.tag_name, one_line(&release.error))
+                    }),
+                ),
+            );
+        }
+        item(
+            tr("uploaded", "上传"),
+            None,
+            ByteSize(self.report.uploaded_bytes()).to_string(),
+        );
+        item(
+            tr("elapsed", "耗时"),
+            None,
+            HumanDuration(Duration::from_secs(self.elapsed.as_secs())).to_string(),
+        );
+        if let Some(error) = &self.error {
+            item(tr("error", "错误"), None, one_line(error));
+        }
+        text
+    }
+}
+
+/// 各渠道的消息格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Markup {
+    // 钉钉、飞书、企业微信
+    Markdown,
+    // Slack的mrkdwn: *粗体*, 需要转义&<>
+    Slack,
+    // Telegram的HTML: <b>粗体</b>, 需要转义&<>
+    Html,
+    // 邮件的纯文本
+    Text,
+}
+
+impl Markup {
+    fn bold(self, text: &str) -> String {
+        match self {
+            Markup::Markdown => format!("**{text}**"),
+            Markup::Slack => format!("*{}*", self.escape(text)),
+            Markup::Html => format!("<b>{}</b>", self.escape(text)),
+            Markup::Text => text.to_string(),
+        }
+    }
+
+    fn escape(self, text: &str) -> String {
+        match self {
+            Markup::Markdown | Markup::Text => text.to_string(),
+            Markup::Slack | Markup::Html => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;"),
+        }
+    }
+}
+
+/// 列表最多显示MAX_LISTED个, 超出部分显示个数
+fn listed(items: impl ExactSizeIterator<Item = String>) -> String {
+    let total = items.len();
+    if total == 0 {
+        return "-".to_string();
+    }
+    let mut text = items.take(MAX_LISTED).collect::<Vec<_>>().join(", ");
+    if total > MAX_LISTED {
+        let _ = write!(text, " ... (+{})", total - MAX_LISTED);
+    }
+    text
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 按--notify-on判断是否需要通知
+fn should_notify(notify_on: NotifyOn, summary: &Summary) -> bool {
+    match notify_on {
+        NotifyOn::Always => true,
+        NotifyOn::Changes => summary.is_failed() || !summary.report.is_nothing_to_sync(),
+        NotifyOn::Failure => summary.is_failed(),
+    }
+}
+
+/// 配置的通知渠道
+fn notifiers(cli: &Cli) -> Vec<Box<dyn Notifier>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+    if let Some(webhook) = &cli.dingtalk_webhook {
+        notifiers.push(Box::new(DingTalk {
+            webhook: webhook.clone(),
+            signing_key: cli.dingtalk_signing_key.clone(),
+        }));
+    }
+    if let Some(webhook) = &cli.feishu_webhook {
+        notifiers.push(Box::new(Feishu {
+            webhook: webhook.clone(),
+            signing_key: cli.feishu_signing_key.clone(),
+            mention: cli.feishu_mention.clone(),
+        }));
+    }
+    if let Some(webhook) = &cli.wecom_webhook {
+        notifiers.push(Box::new(WeCom {
+            webhook: webhook.clone(),
+            mention: cli.wecom_mention.clone(),
+        }));
+    }
+    for webhook in &cli.slack_webhook {
+        notifiers.push(Box::new(Slack {
+            webhook: webhook.clone(),
+        }));
+    }
+    if let Some(host) = &cli.smtp_host {
+        notifiers.push(Box::new(Email {
+            host: host.clone(),
+            port: cli.smtp_port,
+            tls: cli.smtp_tls,
+            username: cli.smtp_username.clone(),
+            password: cli.smtp_password.clone(),
+            from: cli.email_from.clone(),
+            to: cli.email_to.clone(),
+            timeout: cli.http_timeout.as_duration(),
+        }));
+    }
+    if let Some(send_key) = &cli.serverchan_key {
+        notifiers.push(Box::new(ServerChan {
+            send_key: send_key.clone(),
+            on_success: cli.push_on_success,
+        }));
+    }
+    if let Some(device_key) = &cli.bark_key {
+        notifiers.push(Box::new(Bark {
+            server: cli.bark_server.clone(),
+            device_key: device_key.clone(),
+            on_success: cli.push_on_success,
+        }));
+    }
+    if let Some(url) = &cli.notify_webhook {
+        notifiers.push(Box::new(Webhook {
+            url: url.clone(),
+            template: cli.notify_webhook_template.clone(),
+        }));
+    }
+    if let Some(token) = &cli.telegram_bot_token {
+        for chat_id in &cli.telegram_chat_id {
+            notifiers.push(Box::new(Telegram {
+                bot_token: token.clone(),
+                chat_id: chat_id.clone(),
+            }));
+        }
+    }
+    notifiers
+}
+
+/// 同步结束后发送通知: 通知失败只记录日志, 不影响同步结果
+pub fn notify(cli: &Cli, report: &SyncReport, error: Option<&anyhow::Error>, elapsed: Duration) {
+    let notifiers = notifiers(cli);
+    if notifiers.is_empty() {
+        return;
+    }
+    let summary = Summary {
+        title: format!("release2gitee: {} -> {}", cli.source(), cli.target()),
+        report,
+        // 同一个错误已记录在失败的release中时不重复显示
+        error: error
+            .map(|e| redact::redact(&format!("{e:#}")).into_owned())
+            .filter(|error| !report.failed.iter().any(|release| &release.error == error)),
+        elapsed,
+    };
+    if !should_notify(cli.notify_on, &summary) {
+        return;
+    }
+    let client = match http::init_client(cli) {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("notify client init error: {e:#}");
+            return;
+        }
+    };
+    for notifier in notifiers {
+        if notifier.failure_only() && !summary.is_failed() {
+            continue;
+        }
+        match notifier.send(&client, &summary) {
+            Ok(()) => info!("notify sent: {}", notifier.name()),
+            Err(e) => warn!("notify error: {}, {e:#}", notifier.name()),
+        }
+    }
+}
+
+/// 钉钉群机器人: markdown消息, 设置了加签密钥时在地址后追加timestamp和sign
+struct DingTalk {
+    webhook: String,
+    signing_key: Option<String>,
+}
+
+impl Notifier for DingTalk {
+    fn name(&self) -> &'static str {
+        "dingtalk"
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()> {
+        let mut builder = client.post(&self.webhook);
+        if let Some(signing_key) = &self.signing_key {
+            let timestamp = provenance::now_secs() * 1000;
+            builder = builder.query(&[
+                ("timestamp", timestamp.to_string()),
+                ("sign", dingtalk_sign(signing_key, timestamp)),
+            ]);
+        }
+        let res = check_status(builder.json(&dingtalk_message(summary)).send_traced()?)?;
+        // 失败时仍返回200, 错误码在响应体中
+        let body: Value = res.json()?;
+        let errcode = body["errcode"].as_i64().unwrap_or_default();
+        if errcode != 0 {
+            bail!("dingtalk robot error: {errcode}, {}", body["errmsg"]);
+        }
+        Ok(())
+    }
+}
+
+fn dingtalk_message(summary: &Summary) -> Value {
+    json!({
+        "msgtype": "markdown",
+        "markdown": {
+            "title": summary.heading(),
+            "text": summary.markdown(),
+        },
+    })
+}
+
+/// 加签: base64(hmac-sha256(secret, "{timestamp}\n{secret}")), timestamp为毫秒
+fn dingtalk_sign(secret: &str, timestamp: u64) -> String {
+    let sign = hmac_sha256(
+        secret.as_bytes(),
+        format!("{timestamp}\n{secret}").as_bytes(),
+    );
+    STANDARD.encode(sign)
+}
+
+/// 飞书群机器人: 消息卡片(失败时标题为红色), 失败时@指定的成员
+struct Feishu {
+    webhook: String,
+    signing_key: Option<String>,
+    mention: Vec<String>,
+}
+
+impl Notifier for Feishu {
+    fn name(&self) -> &'static str {
+        "feishu"
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()> {
+        let mut message = feishu_message(summary, &self.mention);
+        if let Some(signing_key) = &self.signing_key {
+            let timestamp = provenance::now_secs();
+            message["timestamp"] = json!(timestamp.to_string());
+            message["sign"] = json!(feishu_sign(signing_key, timestamp));
+        }
+        let res = check_status(client.post(&self.webhook).json(&message).send_traced()?)?;
+        let body: Value = res.json()?;
+        let code = body["code"].as_i64().unwrap_or_default();
+        if code != 0 {
+            bail!("feishu bot error: {code}, {}", body["msg"]);
+        }
+        Ok(())
+    }
+}
+
+/// 卡片的markdown中@成员: open_id(ou_xxx)或all(所有人)
+fn feishu_message(summary: &Summary, mention: &[String]) -> Value {
+    let mut content = summary.details(Markup::Markdown);
+    if summary.is_failed() {
+        for id in mention {
+            let _ = write!(content, "<at id={id}></at>");
+        }
+    }
+    json!({
+        "msg_type": "interactive",
+        "card": {
+            "header": {
+                "title": { "tag": "plain_text", "content": summary.heading() },
+                "template": if summary.is_failed() { "red" } else { "green" },
+            },
+            "elements": [{ "tag": "markdown", "content": content.trim_end() }],
+        },
+    })
+}
+
+/// 签名校验: base64(hmac-sha256("{timestamp}\n{secret}", "")), timestamp为秒
+fn feishu_sign(secret: &str, timestamp: u64) -> String {
+    STANDARD.encode(hmac_sha256(
+        format!("{timestamp}\n{secret}").as_bytes(),
+        b"",
+    ))
+}
+
+/// 企业微信群机器人: markdown消息, 失败时@指定的成员
+struct WeCom {
+    webhook: String,
+    mention: Vec<String>,
+}
+
+impl Notifier for WeCom {
+    fn name(&self) -> &'static str {
+        "wecom"
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()> {
+        let message = wecom_message(summary, &self.mention);
+        let res = check_status(client.post(&self.webhook).json(&message).send_traced()?)?;
+        let body: Value = res.json()?;
+        let errcode = body["errcode"].as_i64().unwrap_or_default();
+        if errcode != 0 {
+            bail!("wecom robot error: {errcode}, {}", body["errmsg"]);
+        }
+        Ok(())
+    }
+}
+
+/// markdown中@成员: <@userid>, 所有人为<@all>
+fn wecom_message(summary: &Summary, mention: &[String]) -> Value {
+    let mut content = summary.markdown();
+    if summary.is_failed() {
+        for id in mention {
+            let _ = write!(content, "<@{id}>");
+        }
+    }
+    json!({
+        "msgtype": "markdown",
+        "markdown": { "content": content.trim_end() },
+    })
+}
+
+/// Slack incoming webhook: mrkdwn格式的section, text为通知中显示的摘要
+struct Slack {
+    webhook: String,
+}
+
+impl Notifier for Slack {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()> {
+        // 失败时返回4xx和错误信息(如invalid_payload, no_service)
+        check_status(
+            client
+                .post(&self.webhook)
+                .json(&slack_message(summary))
+                .send_traced()?,
+        )?;
+        Ok(())
+    }
+}
+
+fn slack_message(summary: &Summary) -> Value {
+    let heading = Markup::Slack.escape(&summary.heading());
+    let text = format!(
+        "{}\n{}",
+        Markup::Slack.bold(&summary.heading()),
+        summary.details(Markup::Slack)
+    );
+    json!({
+        "text": heading,
+        "blocks": [{
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": text.trim_end() },
+        }],
+    })
+}
+
+/// Telegram机器人: sendMessage发送到指定的chat(群组、频道或用户), HTML格式
+struct Telegram {
+    bot_token: String,
+    chat_id: String,
+}
+
+impl Notifier for Telegram {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        check_status(
+            client
+                .post(url)
+                .json(&telegram_message(summary, &self.chat_id))
+                .send_traced()?,
+        )?;
+        Ok(())
+    }
+}
+
+fn telegram_message(summary: &Summary, chat_id: &str) -> Value {
+    let text = format!(
+        "{}\n\n{}",
+        Markup::Html.bold(&summary.heading()),
+        summary.details(Markup::Html)
+    );
+    json!({
+        "chat_id": chat_id,
+        "text": text.trim_end(),
+        "parse_mode": "HTML",
+        "disable_web_page_preview": true,
+    })
+}
+
+/// Server酱: 推送到微信等, 默认只在失败时推送
+struct ServerChan {
+    send_key: String,
+    on_success: bool,
+}
+
+impl Notifier for ServerChan {
+    fn name(&self) -> &'static str {
+        "serverchan"
+    }
+
+    fn failure_only(&self) -> bool {
+        !self.on_success
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()> {
+        let message = json!({
+            // 标题最长32个字符
+            "title": summary.heading().chars().take(32).collect::<String>(),
+            "desp": summary.markdown(),
+        });
+        let url = serverchan_url(&self.send_key);
+        let res = check_status(client.post(url).json(&message).send_traced()?)?;
+        let body: Value = res.json()?;
+        let code = body["code"].as_i64().unwrap_or_default();
+        if code != 0 {
+            bail!("serverchan error: {code}, {}", body["message"]);
+        }
+        Ok(())
+    }
+}
+
+/// Server酱³的SendKey(sctp{uid}t...)使用各自的推送地址, Turbo版使用sctapi.ftqq.com
+fn serverchan_url(send_key: &str) -> String {
+    let uid = send_key
+        .strip_prefix("sctp")
+        .and_then(|rest| rest.split_once('t'))
+        .map(|(uid, _)| uid)
+        .filter(|uid| !uid.is_empty() && uid.bytes().all(|b| b.is_ascii_digit()));
+    match uid {
+        Some(uid) => format!("https://{uid}.push.ft07.com/send/{send_key}.send"),
+        None => format!("https://sctapi.ftqq.com/{send_key}.send"),
+    }
+}
+
+/// Bark: 推送到iOS设备, 默认只在失败时推送
+struct Bark {
+    server: String,
+    device_key: String,
+    on_success: bool,
+}
+
+impl Notifier for Bark {
+    fn name(&self) -> &'static str {
+        "bark"
+    }
+
+    fn failure_only(&self) -> bool {
+        !self.on_success
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()> {
+        let message = json!({
+            "device_key": self.device_key,
+            "title": summary.heading(),
+            "body": summary.details(Markup::Text).trim_end(),
+            "group": "release2gitee",
+        });
+        let url = format!("{}/push", self.server.trim_end_matches('/'));
+        let res = check_status(client.post(url).json(&message).send_traced()?)?;
+        let body: Value = res.json()?;
+        let code = body["code"].as_i64().unwrap_or_default();
+        if code != 200 {
+            bail!("bark error: {code}, {}", body["message"]);
+        }
+        Ok(())
+    }
+}
+
+/// 通用webhook: POST按模板生成的JSON, 未设置模板时发送全部变量
+struct Webhook {
+    url: String,
+    template: Option<PathBuf>,
+}
+
+impl Notifier for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, client: &Client, summary: &Summary) -> AnyResult<()> {
+        let variables = template_variables(summary);
+        let payload = match &self.template {
+            Some(path) => {
+                let template = fs::read_to_string(path)
+                    .with_context(|| format!("read notify template: {}", path.display()))?;
+                let template = serde_json::from_str(&template)
+                    .with_context(|| format!("parse notify template: {}", path.display()))?;
+                render_template(&template, &variables)
+            }
+            None => Value::Object(variables),
+        };
+        check_status(client.post(&self.url).json(&payload).send_traced()?)?;
+        Ok(())
+    }
+}
+
+/// 模板中可用的变量
+fn template_variables(summary: &Summary) -> Map<String, Value> {
+    let report = summary.report;
+    let variables = json!({
+        "title": summary.title,
+        "status": if summary.is_failed() { "failed" } else { "success" },
+        "synced": summary.synced_tags(),
+        "failed": report.failed,
+        "uploaded_bytes": report.uploaded_bytes(),
+        "elapsed_secs": summary.elapsed.as_secs(),
+        "error": summary.error,
+        "text": summary.details(Markup::Text).trim_end(),
+        "report": report,
+    });
+    match variables {
+        Value::Object(variables) => variables,
+        _ => unreachable!("json object"),
+    }
+}
+
+/// 替换模板中的变量: 值为"{{name}}"的字符串替换为变量的JSON值(如数组、对象),
+/// 其他字符串中的{{name}}替换为变量的文本
+fn render_template(template: &Value, variables: &Map<String, Value>) -> Value {
+    match template {
+        Value::String(text) => {
+            if let Some(value) = text
+                .strip_prefix("{{")
+                .and_then(|name| name.strip_suffix("}}"))
+                .and_then(|name| variables.get(name.trim()))
+            {
+                return value.clone();
+            }
+            let mut text = text.clone();
+            for (name, value) in variables {
+                let placeholder = format!("{{{{{name}}}}}");
+                if text.contains(&placeholder) {
+                    let value = match value {
+                        Value::String(value) => value.clone(),
+                        Value::Null => String::new(),
+                        value => value.to_string(),
+                    };
+                    text = text.replace(&placeholder, &value);
+                }
+            }
+            Value::String(text)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_template(item, variables))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), render_template(value, variables)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// 邮件: 主题为标题, 正文为纯文本的汇总, 发送到收件人(如邮件组)
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+struct Email {
+    host: String,
+    port: Option<u16>,
+    tls: SmtpTls,
+    username: Option<String>,
+    password: Option<String>,
+    from: Option<String>,
+    to: Vec<String>,
+    timeout: Duration,
+}
+
+impl Notifier for Email {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn send(&self, _client: &Client, summary: &Summary) -> AnyResult<()> {
+        send_email(self, &summary.heading(), &email_body(summary))
+    }
+}
+
+fn email_body(summary: &Summary) -> String {
+    format!(
+        "{}\n\n{}\n-- \nrelease2gitee {}\n",
+        summary.heading(),
+        summary.details(Markup::Text),
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+#[cfg(feature = "email")]
+fn send_email(email: &Email, subject: &str, body: &str) -> AnyResult<()> {
+    use lettre::message::header::ContentType;
+    use lettre::message::{Mailbox, Message};
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{SmtpTransport, Transport};
+
+    let Some(from) = email.from.as_ref().or(email.username.as_ref()) else {
+        bail!("email sender missing, set --email-from");
+    };
+    let mut builder = Message::builder()
+        .from(from.parse::<Mailbox>()?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in &email.to {
+        builder = builder.to(to.parse::<Mailbox>()?);
+    }
+    let message = builder.body(body.to_string())?;
+
+    let mut transport = match email.tls {
+        SmtpTls::Tls => SmtpTransport::relay(&email.host)?,
+        SmtpTls::Starttls => SmtpTransport::starttls_relay(&email.host)?,
+        SmtpTls::None => SmtpTransport::builder_dangerous(&email.host),
+    };
+    if let Some(port) = email.port {
+        transport = transport.port(port);
+    }
+    if let (Some(username), Some(password)) = (&email.username, &email.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    let transport = transport.timeout(Some(email.timeout)).build();
+    transport.send(&message)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "email"))]
+fn send_email(_email: &Email, _subject: &str, _body: &str) -> AnyResult<()> {
+    bail!("email notification is not supported, rebuild with --features email")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::SyncedRelease;
+
+    #[test]
+    fn test_dingtalk_message() {
+        let mut report = SyncReport::default();
+        report.synced(SyncedRelease {
+            tag_name: "v1.0.0".to_string(),
+            action: ReleaseAction::Created,
+            uploaded_assets: vec!["a.zip".to_string()],
+            uploaded_bytes: 3 * 1024 * 1024,
+            missing_assets: Vec::new(),
+            warnings: Vec::new(),
+        });
+        report.synced(SyncedRelease {
+            tag_name: "v0.9.0".to_string(),
+            action: ReleaseAction::Unchanged,
+            uploaded_assets: Vec::new(),
+            uploaded_bytes: 0,
+            missing_assets: Vec::new(),
+            warnings: Vec::new(),
+        });
+        report.failed("v0.8.0", &anyhow::anyhow!("upload file error:\na.zip"));
+        let summary = Summary {
+            title: "release2gitee: github:hepengju/redis-me -> gitee:hepengju/redis-me".to_string(),
+            report: &report,
+            error: None,
+            elapsed: Duration::from_millis(95_500),
+        };
+        assert!(summary.is_failed());
+        assert!(should_notify(NotifyOn::Failure, &summary));
+
+        let message = dingtalk_message(&summary);
+        assert_eq!(message["msgtype"], "markdown");
+        let text = message["markdown"]["text"].as_str().unwrap();
+        assert!(text.starts_with("### ❌ release2gitee: github:hepengju/redis-me"));
+        assert!(text.contains("- **synced** (1): v1.0.0\n"));
+        assert!(text.contains("- **failed** (1): v0.8.0: upload file error: a.zip\n"));
+        assert!(text.contains("- **uploaded**: 3MiB\n"));
+        assert!(text.contains("- **elapsed**: 95s\n"));
+
+        // 没有变化时默认不通知
+        let report = SyncReport::default();
+        let summary = Summary {
+            report: &report,
+            ..summary
+        };
+        assert!(!should_notify(NotifyOn::Changes, &summary));
+        assert!(should_notify(NotifyOn::Always, &summary));
+
+        assert_eq!(
+            dingtalk_sign("SECxxx", 1_577_262_236_757),
+            "TajHYUQC5a6smO3qjKNgFiJalsQ51g5gqH0iiy2oY+o="
+        );
+    }
+
+    #[test]
+    fn test_channel_messages() {
+        let mut report = SyncReport::default();
+        report.failed("v1.0.0", &anyhow::anyhow!("timeout"));
+        let summary = Summary {
+            title: "release2gitee: github:hepengju/redis-me -> gitee:hepengju/redis-me".to_string(),
+            report: &report,
+            error: None,
+            elapsed: Duration::from_secs(3),
+        };
+        let mention = vec!["ou_123".to_string(), "all".to_string()];
+        let message = feishu_message(&summary, &mention);
+        assert_eq!(message["msg_type"], "interactive");
+        assert_eq!(message["card"]["header"]["template"], "red");
+        let content = message["card"]["elements"][0]["content"].as_str().unwrap();
+        assert!(content.starts_with("- **synced** (0): -\n- **failed** (1): v1.0.0: timeout\n"));
+        assert!(content.ends_with("<at id=ou_123></at><at id=all></at>"));
+
+        let message = wecom_message(&summary, &["zhangsan".to_string()]);
+        let content = message["markdown"]["content"].as_str().unwrap();
+        assert!(content.starts_with("### ❌ release2gitee"));
+        assert!(content.ends_with("<@zhangsan>"));
+
+        // 成功时不@成员
+        let report = SyncReport::default();
+        let summary = Summary {
+            report: &report,
+            ..summary
+        };
+        let message = feishu_message(&summary, &mention);
+        assert_eq!(message["card"]["header"]["template"], "green");
+        assert!(!message.to_string().contains("<at"));
+        assert!(!wecom_message(&summary, &mention).to_string().contains("<@"));
+
+        let message = slack_message(&summary);
+        assert_eq!(
+            message["blocks"][0]["text"]["text"],
+            "*✅ release2gitee: github:hepengju/redis-me -&gt; gitee:hepengju/redis-me*\n\
+             - *synced* (0): -\n- *uploaded*: 0B\n- *elapsed*: 3s"
+        );
+        let message = telegram_message(&summary, "-100123");
+        assert_eq!(message["chat_id"], "-100123");
+        assert!(
+            message["text"]
+                .as_str()
+                .unwrap()
+                .starts_with("<b>✅ release2gitee: github:hepengju/redis-me -&gt; gitee")
+        );
+
+        let template = json!({
+            "msg": "[{{status}}] {{title}}",
+            "tags": "{{synced}}",
+            "bytes": "{{uploaded_bytes}}",
+            "nested": { "error": "error: {{error}}", "report": "{{report}}" },
+        });
+        let payload = render_template(&template, &template_variables(&summary));
+        assert_eq!(
+            payload["msg"],
+            "[success] release2gitee: github:hepengju/redis-me -> gitee:hepengju/redis-me"
+        );
+        assert_eq!(payload["tags"], json!([]));
+        assert_eq!(payload["bytes"], 0);
+        assert_eq!(payload["nested"]["error"], "error: ");
+        assert!(payload["nested"]["report"]["synced"].is_array());
+
+        assert_eq!(
+            serverchan_url("sctp12345tAbCd"),
+            "https://12345.push.ft07.com/send/sctp12345tAbCd.send"
+        );
+        assert_eq!(
+            serverchan_url("SCT1234TAbCd"),
+            "https://sctapi.ftqq.com/SCT1234TAbCd.send"
+        );
+
+        let body = email_body(&summary);
+        assert!(body.starts_with("✅ release2gitee: github:hepengju/redis-me"));
+        assert!(body.contains("\n- synced (0): -\n"));
+
+        assert_eq!(
+            feishu_sign("abc", 1_599_360_473),
+            "cgtpJzI2j6bUDggbdGYjskCK3FPgKpTkwosfWsMzKqM="
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use crate::AnyResult;
+use crate::cancel::Cancellation;
+use crate::http;
+use crate::model::{Assert, Release, RepoEndpoint};
+use crate::provider::ReleaseTarget;
+use crate::s3::S3Bucket;
+use log::info;
+use reqwest::Method;
+use reqwest::StatusCode;
+use reqwest::blocking::Body;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs::File;
+use std::path::Path;
+
+/// release列表的对象名
+const INDEX_KEY: &str = "index.json";
+
+/// 对象存储: 按key读写对象(key相对于配置的前缀)
+pub trait ObjectStore {
+    /// 读取对象, 不存在时返回None
+    fn read(&self, key: &str) -> AnyResult<Option<Vec<u8>>>;
+
+    fn write(&self, key: &str, content: Vec<u8>) -> AnyResult<()>;
+
+    /// 上传本地文件
+    fn upload(&self, key: &str, path: &Path) -> AnyResult<()>;
+
+    fn delete(&self, key: &str) -> AnyResult<()>;
+}
+
+/// release列表(index.json): 供下载页面或客户端读取
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ObjectIndex {
+    pub releases: Vec<IndexRelease>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexRelease {
+    pub id: u64,
+    pub tag_name: String,
+    pub name: String,
+    pub body: Option<String>,
+    pub prerelease: bool,
+    pub target_commitish: String,
+    pub assets: Vec<IndexAsset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexAsset {
+    pub name: String,
+    pub size: Option<u64>,
+    // 下载地址: {公开访问地址}/releases/{tag}/{name}
+    pub url: String,
+}
+
+impl IndexRelease {
+    fn to_release(&self) -> Release {
+        Release {
+            id: self.id,
+            tag_name: self.tag_name.clone(),
+            name: self.name.clone(),
+            body: self.body.clone(),
+            prerelease: self.prerelease,
+            draft: false,
+            target_commitish: self.target_commitish.clone(),
+            updated_at: None,
+            created_at: None,
+            published_at: None,
+            assets: self
+                .assets
+                .iter()
+                .map(|asset| Assert {
+                    id: None,
+                    name: asset.name.clone(),
+                    size: asset.size,
+                    browser_download_url: asset.url.clone(),
+                    digest: None,
+                    label: None,
+                    download_count: None,
+                    content_type: None,
+                    api_url: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 对象存储目标: 附件上传到 releases/{tag}/{name}, release列表写入index.json
+/// - 没有gitee的团队也可以使用国内可访问的对象存储作为镜像
+pub struct ObjectTarget {
+    store: Box<dyn ObjectStore>,
+    endpoint: RepoEndpoint,
+    // 读取一次后缓存, 每次修改后写回
+    index: RefCell<Option<ObjectIndex>>,
+}
+
+impl ObjectTarget {
+    pub fn new(store: Box<dyn ObjectStore>, endpoint: RepoEndpoint) -> Self {
+        ObjectTarget {
+            store,
+            endpoint,
+            index: RefCell::new(None),
+        }
+    }
+
+    fn index(&self) -> AnyResult<ObjectIndex> {
+        if let Some(index) = self.index.borrow().as_ref() {
+            return Ok(index.clone());
+        }
+        let index = match self.store.read(INDEX_KEY)? {
+            Some(content) => serde_json::from_slice(&content)?,
+            None => ObjectIndex::default(),
+        };
+        *self.index.borrow_mut() = Some(index.clone());
+        Ok(index)
+    }
+
+    /// 修改release列表并写回
+    fn update_index<T>(&self, f: impl FnOnce(&mut ObjectIndex) -> T) -> AnyResult<T> {
+        let mut index = self.index()?;
+        let result = f(&mut index);
+        self.store
+            .write(INDEX_KEY, serde_json::to_vec_pretty(&index)?)?;
+        *self.index.borrow_mut() = Some(index);
+        Ok(result)
+    }
+
+    fn asset_key(tag_name: &str, asset_name: &str) -> String {
+        format!("releases/{tag_name}/{asset_name}")
+    }
+}
+
+impl ReleaseTarget for ObjectTarget {
+    fn endpoint(&self) -> &RepoEndpoint {
+        &self.endpoint
+    }
+
+    fn releases(&self) -> AnyResult<Vec<Release>> {
+        let index = self.index()?;
+        Ok(index
+            .releases
+            .iter()
+            .map(IndexRelease::to_release)
+            .collect())
+    }
+
+    fn create_release(&self, release: &Release) -> AnyResult<Release> {
+        self.update_index(|index| {
+            let id = index.releases.iter().map(|r| r.id).max().unwrap_or(0) + 1;
+            let created = IndexRelease {
+                id,
+                tag_name: release.tag_name.clone(),
+                name: release.name.clone(),
+                body: release.body.clone(),
+                prerelease: release.prerelease,
+                target_commitish: release.target_commitish.clone(),
+                assets: Vec::new(),
+            };
+            index.releases.push(created.clone());
+            created.to_release()
+        })
+    }
+
+    fn update_release(&self, release: &Release) -> AnyResult<()> {
+        self.update_index(|index| {
+            if let Some(er) = index.releases.iter_mut().find(|er| er.id == release.id) {
+                er.name = release.name.clone();
+                er.body = release.body.clone();
+                er.prerelease = release.prerelease;
+            }
+        })
+    }
+
+    /// 删除release的附件和列表中的记录
+    fn delete_release(&self, release: &Release) -> AnyResult<()> {
+        for asset in &release.assets {
+            self.store
+                .delete(&Self::asset_key(&release.tag_name, &asset.name))?;
+        }
+        self.update_index(|index| index.releases.retain(|er| er.id != release.id))
+    }
+
+    fn upload_asset(
+        &self,
+        release: &Release,
+        asset_name: &str,
+        file_path: &Path,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()> {
+        cancellation.check()?;
+        let key = Self::asset_key(&release.tag_name, asset_name);
+        self.store.upload(&key, file_path)?;
+        let size = file_path.metadata()?.len();
+        let url = format!("{}/{key}", self.endpoint.host);
+        info!("{} object upload success: {key}", self.endpoint.forge);
+        self.update_index(|index| {
+            if let Some(er) = index.releases.iter_mut().find(|er| er.id == release.id) {
+                er.assets.retain(|asset| asset.name != asset_name);
+                er.assets.push(IndexAsset {
+                    name: asset_name.to_string(),
+                    size: Some(size),
+                    url,
+                });
+            }
+        })
+    }
+
+    fn delete_asset(&self, release: &Release, asset: &Assert) -> AnyResult<()> {
+        self.store
+            .delete(&Self::asset_key(&release.tag_name, &asset.name))?;
+        self.update_index(|index| {
+            if let Some(er) = index.releases.iter_mut().find(|er| er.id == release.id) {
+                er.assets.retain(|a| a.name != asset.name);
+            }
+        })
+    }
+}
+
+/// 按文件扩展名设置对象的Content-Type(浏览器下载时识别文件类型)
+pub fn content_type(name: &str) -> &'static str {
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("json") => "application/json",
+        Some("zip") => "application/zip",
+        Some("gz" | "tgz") => "application/gzip",
+        Some("xz") => "application/x-xz",
+        Some("7z") => "application/x-7z-compressed",
+        Some("dmg") => "application/x-apple-diskimage",
+        Some("exe") => "application/vnd.microsoft.portable-executable",
+        Some("msi") => "application/x-msi",
+        Some("deb") => "application/vnd.debian.binary-package",
+        Some("rpm") => "application/x-rpm",
+        Some("apk") => "application/vnd.android.package-archive",
+        Some("txt" | "sig" | "sha256" | "md") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+impl ObjectStore for S3Bucket {
+    fn read(&self, key: &str) -> AnyResult<Option<Vec<u8>>> {
+        let res = self.send(Method::GET, key, None)?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(http::check_status(res)?.bytes()?.to_vec()))
+    }
+
+    fn write(&self, key: &str, content: Vec<u8>) -> AnyResult<()> {
+        let body = Body::from(content);
+        let res = self.send_with_content_type(Method::PUT, key, content_type(key), Some(body))?;
+        http::check_status(res)?;
+        Ok(())
+    }
+
+    fn upload(&self, key: &str, path: &Path) -> AnyResult<()> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let body = Body::sized(file, size);
+        let res = self.send_with_content_type(Method::PUT, key, content_type(key), Some(body))?;
+        http::check_status(res)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> AnyResult<()> {
+        let res = self.send(Method::DELETE, key, None)?;
+        if res.status() != StatusCode::NOT_FOUND {
+            http::check_status(res)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::CancellationToken;
+    use crate::model::Forge;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+    use std::{env, fs};
+
+    /// 内存中的对象存储(clone共享同一份数据)
+    #[derive(Default, Clone)]
+    struct MemoryStore {
+        objects: Rc<RefCell<BTreeMap<String, Vec<u8>>>>,
+    }
+
+    impl ObjectStore for MemoryStore {
+        fn read(&self, key: &str) -> AnyResult<Option<Vec<u8>>> {
+            Ok(self.objects.borrow().get(key).cloned())
+        }
+
+        fn write(&self, key: &str, content: Vec<u8>) -> AnyResult<()> {
+            self.objects.borrow_mut().insert(key.to_string(), content);
+            Ok(())
+        }
+
+        fn upload(&self, key: &str, path: &Path) -> AnyResult<()> {
+            self.write(key, fs::read(path)?)
+        }
+
+        fn delete(&self, key: &str) -> AnyResult<()> {
+            self.objects.borrow_mut().remove(key);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_object_target() -> AnyResult<()> {
+        let store = MemoryStore::default();
+        let endpoint = RepoEndpoint {
+            forge: Forge::S3,
+            host: "https://dl.example.com/mirror".to_string(),
+            owner: "bucket".to_string(),
+            repo: "mirror".to_string(),
+            token: None,
+        };
+        let target = ObjectTarget::new(Box::new(store.clone()), endpoint);
+        assert!(target.releases()?.is_empty());
+
+        let release = Release {
+            id: 100,
+            tag_name: "v1.0.0".to_string(),
+            name: "v1.0.0".to_string(),
+            body: Some("notes".to_string()),
+            prerelease: false,
+            draft: false,
+            target_commitish: "main".to_string(),
+            updated_at: None,
+            created_at: None,
+            published_at: None,
+            assets: Vec::new(),
+        };
+        let created = target.create_release(&release)?;
+        assert_eq!(created.id, 1);
+
+        let file = env::temp_dir().join(format!("object-target-test-{}", std::process::id()));
+        fs::write(&file, "asset")?;
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        target.upload_asset(&created, "a.zip", &file, &cancellation)?;
+        fs::remove_file(&file)?;
+
+        assert_eq!(
+            store.objects.borrow()["releases/v1.0.0/a.zip"],
+            b"asset".to_vec()
+        );
+        let index: ObjectIndex = serde_json::from_slice(&store.objects.borrow()[INDEX_KEY])?;
+        assert_eq!(
+            index.releases[0].assets[0].url,
+            "https://dl.example.com/mirror/releases/v1.0.0/a.zip"
+        );
+
+        let releases = target.releases()?;
+        assert_eq!(releases[0].assets[0].size, Some(5));
+        target.delete_release(&releases[0])?;
+        assert!(target.releases()?.is_empty());
+        assert_eq!(store.objects.borrow().len(), 1);
+
+        assert_eq!(content_type("latest.json"), "application/json");
+        assert_eq!(content_type("app.DMG"), "application/x-apple-diskimage");
+        assert_eq!(content_type("app"), "application/octet-stream");
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use crate::object::{self, ObjectStore};
+use crate::s3::{self, uri_encode};
+use crate::trace::SendTraced;
+use crate::{AnyResult, http, provenance};
+use anyhow::{Context, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use hmac::{Hmac, Mac};
+use log::debug;
+use reqwest::blocking::{Body, Client, Response};
+use reqwest::{Method, StatusCode, Url};
+use sha1::Sha1;
+use std::env;
+use std::fs::File;
+use std::path::Path;
+
+/// 阿里云OSS bucket(可带前缀), 使用OSS原生的签名(HMAC-SHA1)
+/// - 访问密钥读取OSS_ACCESS_KEY_ID, OSS_ACCESS_KEY_SECRET环境变量
+#[derive(Debug)]
+pub struct OssBucket {
+    client: Client,
+    // 如: https://oss-cn-hangzhou.aliyuncs.com
+    endpoint: Url,
+    bucket: String,
+    prefix: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl OssBucket {
+    /// 解析 oss://bucket/prefix
+    pub fn open(client: &Client, endpoint: &str, location: &str) -> AnyResult<Self> {
+        let (bucket, prefix) = parse_location(location)?;
+        Ok(OssBucket {
+            client: client.clone(),
+            endpoint: Url::parse(&endpoint_url(endpoint))?,
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+            access_key: env::var("OSS_ACCESS_KEY_ID").context("OSS_ACCESS_KEY_ID is required")?,
+            secret_key: env::var("OSS_ACCESS_KEY_SECRET")
+                .context("OSS_ACCESS_KEY_SECRET is required")?,
+        })
+    }
+
+    fn send(
+        &self,
+        method: Method,
+        key: &str,
+        content_type: &str,
+        body: Option<Body>,
+    ) -> AnyResult<Response> {
+        let key = match self.prefix.as_str() {
+            "" => key.to_string(),
+            prefix => format!("{prefix}/{key}"),
+        };
+        // 虚拟主机风格: {scheme}://{bucket}.{endpoint}/{key}
+        let host = self.endpoint.host_str().unwrap_or_default();
+        let encoded_key = key.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+        let url = format!(
+            "{}://{}.{host}/{encoded_key}",
+            self.endpoint.scheme(),
+            self.bucket
+        );
+
+        let date = s3::http_date(provenance::now_secs());
+        let resource = format!("/{}/{key}", self.bucket);
+        let signature = signature(
+            &self.secret_key,
+            method.as_str(),
+            "",
+            content_type,
+            &date,
+            &[],
+            &resource,
+        );
+        debug!("{method}: {url}");
+        let mut builder = self
+            .client
+            .request(method, url)
+            .header(
+                "Authorization",
+                format!("OSS {}:{signature}", self.access_key),
+            )
+            .header("Date", date);
+        if !content_type.is_empty() {
+            builder = builder.header("Content-Type", content_type);
+        }
+        if let Some(body) = body {
+            builder = builder.body(body);
+        }
+        Ok(builder.send_traced()?)
+    }
+}
+
+impl ObjectStore for OssBucket {
+    fn read(&self, key: &str) -> AnyResult<Option<Vec<u8>>> {
+        let res = self.send(Method::GET, key, "", None)?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(http::check_status(res)?.bytes()?.to_vec()))
+    }
+
+    fn write(&self, key: &str, content: Vec<u8>) -> AnyResult<()> {
+        let content_type = object::content_type(key);
+        let res = self.send(Method::PUT, key, content_type, Some(Body::from(content)))?;
+        http::check_status(res)?;
+        Ok(())
+    }
+
+    fn upload(&self, key: &str, path: &Path) -> AnyResult<()> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let content_type = object::content_type(key);
+        let res = self.send(
+            Method::PUT,
+            key,
+            content_type,
+            Some(Body::sized(file, size)),
+        )?;
+        http::check_status(res)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> AnyResult<()> {
+        let res = self.send(Method::DELETE, key, "", None)?;
+        if res.status() != StatusCode::NOT_FOUND {
+            http::check_status(res)?;
+        }
+        Ok(())
+    }
+}
+
+/// oss://bucket/prefix => (bucket, prefix)
+pub fn parse_location(location: &str) -> AnyResult<(&str, &str)> {
+    let Some(rest) = location.strip_prefix("oss://") else {
+        bail!("invalid oss location, oss://bucket/prefix expected: {location}");
+    };
+    let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+    if bucket.is_empty() {
+        bail!("invalid oss location, bucket is required: {location}");
+    }
+    Ok((bucket, prefix.trim_matches('/')))
+}
+
+/// 服务地址, 省略协议时为https: oss-cn-hangzhou.aliyuncs.com => https://oss-cn-hangzhou.aliyuncs.com
+pub fn endpoint_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.contains("://") {
+        endpoint.to_string()
+    } else {
+        format!("https://{endpoint}")
+    }
+}
+
+/// OSS签名: base64(hmac-sha1(AccessKeySecret, VERB\nContent-MD5\nContent-Type\nDate\nCanonicalizedOSSHeaders+CanonicalizedResource))
+fn signature(
+    secret_key: &str,
+    method: &str,
+    content_md5: &str,
+    content_type: &str,
+    date: &str,
+    oss_headers: &[(&str, &str)],
+    resource: &str,
+) -> String {
+    let mut headers = oss_headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name.to_lowercase(), value.trim()))
+        .collect::<Vec<_>>();
+    headers.sort();
+    let string_to_sign = format!(
+        "{method}\n{content_md5}\n{content_type}\n{date}\n{}{resource}",
+        headers.concat()
+    );
+    let mut mac =
+        Hmac::<Sha1>::new_from_slice(secret_key.as_bytes()).expect("hmac accepts any key length");
+    mac.update(string_to_sign.as_bytes());
+    STANDARD.encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() -> AnyResult<()> {
+        // OSS文档中的签名示例
+        let signature = signature(
+            "OtxrzxIsfpFjA7SwPzILwy8Bw21TLhquhboDYROV",
+            "PUT",
+            "ODBGOERFMDMzQTczRUY3NUE3NzA5QzdFNUYzMDQxNEM=",
+            "text/html",
+            "Thu, 17 Nov 2005 18:49:58 GMT",
+            &[
+                ("X-OSS-Meta-Author", "foo@bar.com"),
+                ("X-OSS-Magic", "abracadabra"),
+            ],
+            "/oss-example/nelson",
+        );
+        assert_eq!(signature, "26NBxoKdsyly4EDv6inkoDft/yA=");
+
+        assert_eq!(
+            parse_location("oss://bucket/mirror/")?,
+            ("bucket", "mirror")
+        );
+        assert!(parse_location("s3://bucket").is_err());
+        assert_eq!(
+            endpoint_url("oss-cn-hangzhou.aliyuncs.com"),
+            "https://oss-cn-hangzhou.aliyuncs.com"
+        );
+        Ok(())
+    }
+}
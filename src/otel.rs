@@ -0,0 +1,310 @@
+//! 同步过程的链路追踪(--otlp-endpoint): 每次同步、每个release、每个附件、每个http请求一个span
+//! 以OTLP/HTTP JSON格式导出到OpenTelemetry Collector等, 未设置时不记录
+
+use crate::model::Cli;
+use crate::s3::hex;
+use log::{debug, info, warn};
+use reqwest::blocking::Client;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{Value, json};
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 缓存的span达到该数量时导出, 其余在同步结束时导出
+const BATCH_SIZE: usize = 512;
+
+static EXPORTER: OnceLock<Exporter> = OnceLock::new();
+
+thread_local! {
+    // 当前线程中未结束的span: 新建的span以最后一个为父span
+    static STACK: RefCell<Vec<SpanContext>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 开启链路追踪, 只在第一次设置时生效
+pub fn init(cli: &Cli) {
+    let Some(endpoint) = &cli.otlp_endpoint else {
+        return;
+    };
+    let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+    let client = match Client::builder().timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("otlp exporter init error: {e:#}");
+            return;
+        }
+    };
+    let exporter = Exporter {
+        url: url.clone(),
+        service_name: cli.otel_service_name.clone(),
+        client,
+        spans: Mutex::new(Vec::new()),
+    };
+    if EXPORTER.set(exporter).is_ok() {
+        info!("otlp traces export to: {url}");
+    }
+}
+
+/// span的标识: 跨线程时传递给子线程(attach)
+#[derive(Debug, Clone, Copy)]
+pub struct SpanContext {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+}
+
+/// 当前线程的span, 传递给子线程
+pub fn current() -> Option<SpanContext> {
+    STACK.with(|stack| stack.borrow().last().copied())
+}
+
+/// 子线程中以传入的span为父span, 返回的guard结束时恢复
+pub fn attach(context: Option<SpanContext>) -> Attached {
+    let attached = context.is_some();
+    if let Some(context) = context {
+        STACK.with(|stack| stack.borrow_mut().push(context));
+    }
+    Attached(attached)
+}
+
+pub struct Attached(bool);
+
+impl Drop for Attached {
+    fn drop(&mut self) {
+        if self.0 {
+            STACK.with(|stack| stack.borrow_mut().pop());
+        }
+    }
+}
+
+pub fn is_enabled() -> bool {
+    EXPORTER.get().is_some()
+}
+
+/// 开始一个span, 结束(drop)时记录; 未开启链路追踪时不记录
+pub fn span(name: impl Into<String>) -> Span {
+    if EXPORTER.get().is_none() {
+        return Span(None);
+    }
+    let parent = current();
+    let context = SpanContext {
+        trace_id: parent.map_or_else(random_id, |parent| parent.trace_id),
+        span_id: random_id(),
+    };
+    STACK.with(|stack| stack.borrow_mut().push(context));
+    Span(Some(SpanData {
+        context,
+        parent_span_id: parent.map(|parent| parent.span_id),
+        name: name.into(),
+        start: now_nanos(),
+        end: 0,
+        attributes: Vec::new(),
+        error: None,
+    }))
+}
+
+pub struct Span(Option<SpanData>);
+
+struct SpanData {
+    context: SpanContext,
+    parent_span_id: Option<[u8; 8]>,
+    name: String,
+    start: u128,
+    end: u128,
+    attributes: Vec<(&'static str, Value)>,
+    error: Option<String>,
+}
+
+impl Span {
+    /// 字符串属性
+    pub fn set(&mut self, key: &'static str, value: impl Display) {
+        if let Some(data) = &mut self.0 {
+            data.attributes
+                .push((key, json!({ "stringValue": value.to_string() })));
+        }
+    }
+
+    /// 整数属性(大小、状态码等)
+    pub fn set_int(&mut self, key: &'static str, value: u64) {
+        if let Some(data) = &mut self.0 {
+            data.attributes
+                .push((key, json!({ "intValue": value.to_string() })));
+        }
+    }
+
+    /// 标记失败
+    pub fn error(&mut self, error: impl Display) {
+        if let Some(data) = &mut self.0 {
+            data.error = Some(crate::redact::redact(&error.to_string()).into_owned());
+        }
+    }
+
+    /// 按结果标记失败
+    pub fn record<T, E: Display>(&mut self, result: &Result<T, E>) {
+        if let Err(e) = result {
+            self.error(e);
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(mut data) = self.0.take() else {
+            return;
+        };
+        STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            if let Some(i) = stack
+                .iter()
+                .rposition(|context| context.span_id == data.context.span_id)
+            {
+                stack.remove(i);
+            }
+        });
+        data.end = now_nanos();
+        if let Some(exporter) = EXPORTER.get() {
+            exporter.push(data);
+        }
+    }
+}
+
+/// 导出缓存的span(同步结束时调用)
+pub fn flush() {
+    if let Some(exporter) = EXPORTER.get() {
+        exporter.flush();
+    }
+}
+
+struct Exporter {
+    url: String,
+    service_name: String,
+    client: Client,
+    spans: Mutex<Vec<SpanData>>,
+}
+
+impl Exporter {
+    fn push(&self, span: SpanData) {
+        let full = {
+            let mut spans = self.spans.lock().unwrap_or_else(PoisonError::into_inner);
+            spans.push(span);
+            spans.len() >= BATCH_SIZE
+        };
+        if full {
+            self.flush();
+        }
+    }
+
+    /// 导出失败时丢弃(链路追踪不影响同步)
+    fn flush(&self) {
+        let spans = std::mem::take(&mut *self.spans.lock().unwrap_or_else(PoisonError::into_inner));
+        if spans.is_empty() {
+            return;
+        }
+        let body = export_request(&self.service_name, &spans);
+        match self.client.post(&self.url).json(&body).send() {
+            Ok(res) if res.status().is_success() => {
+                debug!("otlp exported {} spans", spans.len());
+            }
+            Ok(res) => warn!("otlp export error: {}, {}", self.url, res.status()),
+            Err(e) => warn!("otlp export error: {}, {e:#}", self.url),
+        }
+    }
+}
+
+/// OTLP/HTTP JSON: ExportTraceServiceRequest, trace和span的id为十六进制字符串
+fn export_request(service_name: &str, spans: &[SpanData]) -> Value {
+    let spans = spans
+        .iter()
+        .map(|span| {
+            let mut value = json!({
+                "traceId": hex(&span.context.trace_id),
+                "spanId": hex(&span.context.span_id),
+                "name": span.name,
+                // SPAN_KIND_INTERNAL
+                "kind": 1,
+                "startTimeUnixNano": span.start.to_string(),
+                "endTimeUnixNano": span.end.to_string(),
+                "attributes": span.attributes.iter()
+                    .map(|(key, value)| json!({ "key": key, "value": value }))
+                    .collect::<Vec<_>>(),
+                // STATUS_CODE_OK
+                "status": { "code": 1 },
+            });
+            if let Some(parent) = span.parent_span_id {
+                value["parentSpanId"] = json!(hex(&parent));
+            }
+            if let Some(error) = &span.error {
+                // STATUS_CODE_ERROR
+                value["status"] = json!({ "code": 2, "message": error });
+            }
+            value
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": service_name } }],
+            },
+            "scopeSpans": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+fn random_id<const N: usize>() -> [u8; N] {
+    let mut id = [0u8; N];
+    // 系统随机数不可用时退化为时间
+    if SystemRandom::new().fill(&mut id).is_err() {
+        let nanos = now_nanos().to_le_bytes();
+        for (i, byte) in id.iter_mut().enumerate() {
+            *byte = nanos[i % nanos.len()];
+        }
+    }
+    id
+}
+
+fn now_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_request() {
+        let parent = SpanContext {
+            trace_id: [1; 16],
+            span_id: [2; 8],
+        };
+        let span = SpanData {
+            context: SpanContext {
+                trace_id: parent.trace_id,
+                span_id: [3; 8],
+            },
+            parent_span_id: Some(parent.span_id),
+            name: "upload a.zip".to_string(),
+            start: 1_000,
+            end: 2_000,
+            attributes: vec![("asset.size", json!({ "intValue": "42" }))],
+            error: Some("timeout".to_string()),
+        };
+        let body = export_request("release2gitee", &[span]);
+        let span = &body["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["traceId"], "01".repeat(16));
+        assert_eq!(span["parentSpanId"], "02".repeat(8));
+        assert_eq!(span["startTimeUnixNano"], "1000");
+        assert_eq!(span["attributes"][0]["key"], "asset.size");
+        assert_eq!(span["status"]["code"], 2);
+
+        // 未开启时不记录
+        let mut span = super::span("sync");
+        span.set("tag", "v1.0.0");
+        assert!(span.0.is_none());
+        assert!(current().is_none());
+    }
+}
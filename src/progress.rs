@@ -0,0 +1,367 @@
+use crate::tui;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "cli")]
+use {
+    crate::units::ByteSize,
+    indicatif::{ProgressBar, ProgressStyle},
+    log::info,
+    std::collections::HashMap,
+    std::io::{self, IsTerminal},
+    std::sync::{Mutex, PoisonError},
+    std::time::{Duration, Instant},
+};
+
+/// 进度行的百分比间隔: 每完成10%输出一行
+#[cfg(feature = "cli")]
+const PERCENT_STEP: u64 = 10;
+
+/// 传输进度的接收方: 库的调用方通过SyncOptionsBuilder::progress设置, 驱动自己的进度界面(如GUI)
+/// - id: 每次传输(一次下载或上传尝试)的编号, 分段下载的各分段共享同一个编号
+/// - position: 已传输的字节数(断点续传时从已传输的位置开始)
+pub trait ProgressSink: Send + Sync {
+    /// 开始传输, label如: download a.zip, size为0时大小未知
+    fn on_start(&self, id: u64, label: &str, size: u64);
+
+    fn on_progress(&self, id: u64, position: u64);
+
+    /// 进度后缀信息, 如重试时的: retry 1/3 in 2s
+    fn on_message(&self, _id: u64, _message: &str) {}
+
+    /// 传输结束, success为false时表示传输失败或取消
+    fn on_finish(&self, id: u64, success: bool);
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// 传输进度: 通知进度的接收方或终端界面的传输面板(多个分段下载线程共享)
+#[derive(Clone)]
+pub(crate) struct Progress {
+    id: u64,
+    position: Arc<AtomicU64>,
+    sink: Option<Arc<dyn ProgressSink>>,
+    // 终端界面(--tui)传输面板中的编号
+    tui: Option<u64>,
+}
+
+impl Progress {
+    /// label: 进度行的前缀, 如: download a.zip; sink为None时(未设置接收方)不通知
+    pub fn new(label: &str, size: u64, sink: Option<&Arc<dyn ProgressSink>>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let tui = tui::transfer_start(label, size);
+        // 终端界面显示期间只更新传输面板
+        let sink = sink.filter(|_| tui.is_none()).cloned();
+        if let Some(sink) = &sink {
+            sink.on_start(id, label, size);
+        }
+        Progress {
+            id,
+            position: Arc::new(AtomicU64::new(0)),
+            sink,
+            tui,
+        }
+    }
+
+    pub fn set_position(&self, position: u64) {
+        self.position.store(position, Ordering::Relaxed);
+        if let Some(id) = self.tui {
+            tui::transfer_resume(id, position);
+        }
+        if let Some(sink) = &self.sink {
+            sink.on_progress(self.id, position);
+        }
+    }
+
+    pub fn inc(&self, n: u64) {
+        let position = self.position.fetch_add(n, Ordering::Relaxed) + n;
+        if let Some(id) = self.tui {
+            tui::transfer_inc(id, n);
+        }
+        if let Some(sink) = &self.sink {
+            sink.on_progress(self.id, position);
+        }
+    }
+
+    pub fn set_message(&self, message: String) {
+        if let Some(sink) = &self.sink {
+            sink.on_message(self.id, &message);
+        }
+    }
+
+    pub fn finish(&self) {
+        self.end(true);
+    }
+
+    pub fn abandon(&self) {
+        self.end(false);
+    }
+
+    fn end(&self, success: bool) {
+        if let Some(id) = self.tui {
+            tui::transfer_end(id);
+        }
+        if let Some(sink) = &self.sink {
+            sink.on_finish(self.id, success);
+        }
+    }
+}
+
+/// 命令行的进度显示: 终端中显示进度条, 非终端(CI日志)或--no-progress时定期输出进度行
+#[cfg(feature = "cli")]
+pub struct TerminalProgress {
+    lines: Option<Duration>,
+    transfers: Mutex<HashMap<u64, Transfer>>,
+}
+
+#[cfg(feature = "cli")]
+enum Transfer {
+    Bar(ProgressBar),
+    Lines(Lines),
+}
+
+#[cfg(feature = "cli")]
+impl TerminalProgress {
+    /// interval: 进度行的最长输出间隔
+    pub fn new(no_progress: bool, interval: Duration) -> Self {
+        TerminalProgress {
+            lines: (no_progress || !io::stderr().is_terminal()).then_some(interval),
+            transfers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn with_transfer(&self, id: u64, f: impl FnOnce(&mut Transfer) -> Option<String>) {
+        let line = {
+            let mut transfers = self
+                .transfers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            transfers.get_mut(&id).and_then(f)
+        };
+        if let Some(line) = line {
+            info!("{line}");
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl ProgressSink for TerminalProgress {
+    fn on_start(&self, id: u64, label: &str, size: u64) {
+        let transfer = match self.lines {
+            Some(interval) => Transfer::Lines(Lines::new(label, size, interval)),
+            None => {
+                let bar = ProgressBar::new(size);
+                let style = ProgressStyle::default_bar()
+                    .template("{elapsed_precise:.white.dim} [{wide_bar:.cyan}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar());
+                bar.set_style(style.progress_chars("#>-"));
+                Transfer::Bar(bar)
+            }
+        };
+        self.transfers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id, transfer);
+    }
+
+    fn on_progress(&self, id: u64, position: u64) {
+        self.with_transfer(id, |transfer| match transfer {
+            Transfer::Bar(bar) => {
+                bar.set_position(position);
+                None
+            }
+            Transfer::Lines(lines) => {
+                lines.advance(position.saturating_sub(lines.position), Instant::now())
+            }
+        });
+    }
+
+    /// 进度条后缀(重试信息), 进度行模式下重试已有警告日志, 不再输出
+    fn on_message(&self, id: u64, message: &str) {
+        self.with_transfer(id, |transfer| {
+            if let Transfer::Bar(bar) = transfer {
+                bar.set_message(message.to_string());
+            }
+            None
+        });
+    }
+
+    fn on_finish(&self, id: u64, success: bool) {
+        let transfer = self
+            .transfers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id);
+        match transfer {
+            Some(Transfer::Bar(bar)) if success => bar.finish_with_message(""),
+            Some(Transfer::Bar(bar)) => bar.abandon(),
+            Some(Transfer::Lines(mut lines)) if success => {
+                if let Some(line) = lines.finish() {
+                    info!("{line}");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 进度行: 每完成PERCENT_STEP或每隔interval输出一行
+#[cfg(feature = "cli")]
+struct Lines {
+    label: String,
+    size: u64,
+    position: u64,
+    interval: Duration,
+    step: u64,
+    logged: Instant,
+    done: bool,
+}
+
+#[cfg(feature = "cli")]
+impl Lines {
+    fn new(label: &str, size: u64, interval: Duration) -> Self {
+        Lines {
+            label: label.to_string(),
+            size,
+            position: 0,
+            interval,
+            step: 0,
+            logged: Instant::now(),
+            done: false,
+        }
+    }
+
+    fn percent(&self) -> u64 {
+        match self.size {
+            0 => 0,
+            size => (self.position.min(size) * 100) / size,
+        }
+    }
+
+    fn line(&self) -> String {
+        if self.size == 0 {
+            format!("{}: {}", self.label, ByteSize(self.position))
+        } else {
+            format!(
+                "{}: {}% ({}/{})",
+                self.label,
+                self.percent(),
+                ByteSize(self.position),
+                ByteSize(self.size)
+            )
+        }
+    }
+
+    fn advance(&mut self, n: u64, now: Instant) -> Option<String> {
+        self.position += n;
+        let step = self.percent() / PERCENT_STEP;
+        // 100%由finish输出
+        if self.size > 0 && self.position >= self.size {
+            return None;
+        }
+        if step > self.step || now.duration_since(self.logged) >= self.interval {
+            self.step = step;
+            self.logged = now;
+            return Some(self.line());
+        }
+        None
+    }
+
+    fn finish(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        self.done = true;
+        self.size = self.size.max(self.position);
+        Some(self.line())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_progress_lines() {
+        let start = Instant::now();
+        let mut lines = Lines::new("download a.zip", 1000, Duration::from_secs(10));
+        assert_eq!(lines.advance(50, start), None);
+        assert_eq!(
+            lines.advance(60, start).as_deref(),
+            Some("download a.zip: 11% (110B/1000B)")
+        );
+        assert_eq!(lines.advance(10, start), None);
+        // 百分比未变化, 超过间隔时间也输出
+        assert_eq!(
+            lines
+                .advance(10, start + Duration::from_secs(10))
+                .as_deref(),
+            Some("download a.zip: 13% (130B/1000B)")
+        );
+        assert_eq!(lines.advance(870, start), None);
+        assert_eq!(
+            lines.finish().as_deref(),
+            Some("download a.zip: 100% (1000B/1000B)")
+        );
+        assert_eq!(lines.finish(), None);
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(u64, String)>>);
+
+    impl ProgressSink for Recorder {
+        fn on_start(&self, id: u64, label: &str, size: u64) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((id, format!("start {label} {size}")));
+        }
+
+        fn on_progress(&self, id: u64, position: u64) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((id, format!("progress {position}")));
+        }
+
+        fn on_finish(&self, id: u64, success: bool) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((id, format!("finish {success}")));
+        }
+    }
+
+    #[test]
+    fn test_progress_sink() {
+        let recorder = Arc::new(Recorder::default());
+        let sink: Arc<dyn ProgressSink> = recorder.clone();
+        let progress = Progress::new("upload a.zip", 100, Some(&sink));
+        progress.set_position(40);
+        // 分段共享进度, position为累计值
+        progress.clone().inc(10);
+        progress.inc(50);
+        progress.finish();
+        let events = recorder
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, event)| {
+                assert_eq!(*id, progress.id);
+                event.clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                "start upload a.zip 100",
+                "progress 40",
+                "progress 50",
+                "progress 100",
+                "finish true"
+            ]
+        );
+    }
+}
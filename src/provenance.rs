@@ -0,0 +1,228 @@
+use crate::model::{Cli, Release};
+use crate::{AnyResult, fsutil};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 同步来源记录: 哪些gitee release是从github的哪个release同步过来的
+/// - 用于--propagate-deletes: github上已删除的release, 超过宽限期后从gitee删除
+/// - 只有记录中的release才会被删除, gitee上手动创建的release不受影响
+/// - 同时记录同步成功时github release的状态(更新时间、附件摘要), 没有变化的release直接跳过
+#[derive(Debug)]
+pub struct Provenance {
+    path: PathBuf,
+    state: ProvenanceState,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProvenanceState {
+    releases: BTreeMap<String, ProvenanceEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceEntry {
+    pub github_id: u64,
+    pub synced_at: u64,
+    // 第一次发现github上不存在的时间
+    #[serde(default)]
+    pub missing_since: Option<u64>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    // 附件: name@digest (没有digest时为name@size)
+    #[serde(default)]
+    pub assets: BTreeSet<String>,
+}
+
+impl Provenance {
+    pub fn open(cli: &Cli) -> Self {
+        let path = cli
+            .work_dir()
+            .join(&cli.github_repo)
+            .join(format!(".provenance-{}.json", cli.mirror_key()));
+
+        let mut state = ProvenanceState::default();
+        if path.exists() {
+            match fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<ProvenanceState>(&content)?))
+            {
+                Ok(last) => state = last,
+                Err(e) => warn!("provenance read error, ignore: {}, {e:#}", path.display()),
+            }
+        }
+        Provenance { path, state }
+    }
+
+    /// 记录同步成功的release
+    pub fn record(&mut self, release: &Release) {
+        self.state.releases.insert(
+            release.tag_name.clone(),
+            ProvenanceEntry {
+                github_id: release.id,
+                synced_at: now_secs(),
+                missing_since: None,
+                updated_at: release.updated_at.clone(),
+                assets: asset_fingerprints(release),
+            },
+        );
+    }
+
+    /// github release与上次同步成功时相比没有变化(id、更新时间、附件都一致)
+    pub fn is_unchanged(&self, release: &Release) -> bool {
+        self.state
+            .releases
+            .get(&release.tag_name)
+            .is_some_and(|entry| {
+                entry.github_id == release.id
+                    && entry.updated_at.is_some()
+                    && entry.updated_at == release.updated_at
+                    && entry.assets == asset_fingerprints(release)
+            })
+    }
+
+    /// 对比github现有的release id, 返回已删除且超过宽限期的tag
+    /// - 新发现缺失的记录开始计时, 重新出现的(如临时撤回后再发布)取消计时
+    pub fn expired_deletions(
+        &mut self,
+        github_ids: &BTreeSet<u64>,
+        now: u64,
+        grace: Duration,
+    ) -> Vec<(String, ProvenanceEntry)> {
+        let mut expired = Vec::new();
+        for (tag_name, entry) in self.state.releases.iter_mut() {
+            if github_ids.contains(&entry.github_id) {
+                entry.missing_since = None;
+                continue;
+            }
+
+            let missing_since = *entry.missing_since.get_or_insert(now);
+            if now.saturating_sub(missing_since) >= grace.as_secs() {
+                expired.push((tag_name.clone(), entry.clone()));
+            } else {
+                info!(
+                    "github release deleted, wait for grace period: {tag_name}, missing since: {missing_since}"
+                );
+            }
+        }
+        expired
+    }
+
+    pub fn forget(&mut self, tag_name: &str) {
+        self.state.releases.remove(tag_name);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.releases.is_empty()
+    }
+
+    /// 删除操作记录到审计日志(每行一个json)
+    pub fn audit_deletion(&self, tag_name: &str, entry: &ProvenanceEntry, gitee_id: u64) {
+        let path = self.path.with_extension("deletions.log");
+        let line = serde_json::json!({
+            "deleted_at": now_secs(),
+            "tag_name": tag_name,
+            "github_id": entry.github_id,
+            "gitee_id": gitee_id,
+            "missing_since": entry.missing_since,
+        });
+        let write = || -> AnyResult<()> {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(file, "{line}")?;
+            Ok(())
+        };
+        if let Err(e) = write() {
+            warn!("deletion audit log write error: {}, {e:#}", path.display());
+        }
+    }
+
+    pub fn save(&self) -> AnyResult<()> {
+        fs::create_dir_all(self.path.parent().expect("provenance dir"))?;
+        let content = serde_json::to_string_pretty(&self.state)?;
+        fsutil::write_file(&self.path, content, false)
+    }
+}
+
+fn asset_fingerprints(release: &Release) -> BTreeSet<String> {
+    release
+        .assets
+        .iter()
+        .map(|asset| match (&asset.digest, asset.size) {
+            (Some(digest), _) => format!("{}@{digest}", asset.name),
+            (None, Some(size)) => format!("{}@{size}", asset.name),
+            (None, None) => asset.name.clone(),
+        })
+        .collect()
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_deletions() {
+        let mut provenance = Provenance {
+            path: PathBuf::new(),
+            state: ProvenanceState::default(),
+        };
+        let release = |id: u64, tag_name: &str| Release {
+            id,
+            tag_name: tag_name.to_string(),
+            name: tag_name.to_string(),
+            body: None,
+            prerelease: false,
+            draft: false,
+            target_commitish: "main".to_string(),
+            updated_at: Some("2025-12-25T08:22:42Z".to_string()),
+            created_at: None,
+            published_at: None,
+            assets: Vec::new(),
+        };
+        provenance.record(&release(1, "v0.1.0"));
+        provenance.record(&release(2, "v0.2.0"));
+        assert!(provenance.is_unchanged(&release(2, "v0.2.0")));
+        let mut updated = release(2, "v0.2.0");
+        updated.updated_at = Some("2025-12-26T00:00:00Z".to_string());
+        assert!(!provenance.is_unchanged(&updated));
+
+        let grace = Duration::from_secs(3600);
+        let github_ids = BTreeSet::from([2]);
+        // 第一次发现缺失: 开始计时
+        assert!(
+            provenance
+                .expired_deletions(&github_ids, 1000, grace)
+                .is_empty()
+        );
+        assert!(
+            provenance
+                .expired_deletions(&github_ids, 4000, grace)
+                .is_empty()
+        );
+        let expired = provenance.expired_deletions(&github_ids, 4600, grace);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, "v0.1.0");
+
+        // 重新出现: 取消计时
+        let github_ids = BTreeSet::from([1, 2]);
+        assert!(
+            provenance
+                .expired_deletions(&github_ids, 9000, grace)
+                .is_empty()
+        );
+        let github_ids = BTreeSet::from([2]);
+        assert!(
+            provenance
+                .expired_deletions(&github_ids, 9000, grace)
+                .is_empty()
+        );
+    }
+}
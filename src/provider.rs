@@ -0,0 +1,839 @@
+use crate::cancel::Cancellation;
+use crate::cos::CosBucket;
+use crate::error::ApiError;
+use crate::gitlab::GitlabTarget;
+use crate::http::{Conditional, TokenRotation};
+use crate::listing::CachedTarget;
+use crate::local::LocalTarget;
+use crate::model::{Assert, Cli, Forge, Release, RepoEndpoint, SyncConfig};
+use crate::object::ObjectTarget;
+use crate::oss::OssBucket;
+use crate::report::MirrorStats;
+use crate::s3::S3Bucket;
+#[cfg(feature = "sftp")]
+use crate::sftp::SftpStore;
+use crate::store::WebdavStore;
+use crate::transfer::{Transferred, UploadBody};
+use crate::transport::{DownloadOptions, ReqwestTransport, Transport};
+use crate::{AnyResult, GITHUB_UPLOADS_URL, fsutil};
+use anyhow::bail;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use log::{debug, info, warn};
+use reqwest::Url;
+use reqwest::blocking::Client;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// 源仓库: 查询releases并下载附件
+pub trait ReleaseSource {
+    fn endpoint(&self) -> &RepoEndpoint;
+
+    /// 查询一页releases, 携带etag且没有变化时返回NotModified
+    fn fetch_releases(
+        &self,
+        per_page: usize,
+        page: usize,
+        etag: Option<&str>,
+    ) -> AnyResult<Conditional>;
+
+    /// 解析releases接口的响应(包括缓存的响应)
+    fn parse_releases(&self, text: &str) -> AnyResult<Vec<Release>>;
+
+    /// 下载附件到本地文件, 并校验摘要
+    fn download_asset(
+        &self,
+        asset: &Assert,
+        file_path: &Path,
+        durable: bool,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()>;
+
+    /// 读取仓库中的文件(如: CHANGELOG.zh-CN.md), 不存在时返回None
+    fn file_content(&self, _path: &str, _git_ref: &str) -> AnyResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// tag对应的commit sha, 不存在或不支持时返回None
+    fn tag_commit(&self, _tag_name: &str) -> AnyResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// 附件下载加速地址的成功/失败次数
+    fn mirror_stats(&self) -> Vec<MirrorStats> {
+        Vec::new()
+    }
+}
+
+/// 目标仓库: 创建、更新、删除release并上传附件
+pub trait ReleaseTarget {
+    fn endpoint(&self) -> &RepoEndpoint;
+
+    /// 最近的releases(最多100个)
+    fn releases(&self) -> AnyResult<Vec<Release>>;
+
+    fn create_release(&self, release: &Release) -> AnyResult<Release>;
+
+    fn update_release(&self, release: &Release) -> AnyResult<()>;
+
+    fn delete_release(&self, release: &Release) -> AnyResult<()>;
+
+    fn upload_asset(
+        &self,
+        release: &Release,
+        asset_name: &str,
+        file_path: &Path,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()>;
+
+    /// 删除附件(同名附件冲突时替换)
+    fn delete_asset(&self, release: &Release, asset: &Assert) -> AnyResult<()>;
+
+    /// 是否支持预发布标记(gitlab不支持, 不参与对比)
+    fn supports_prerelease(&self) -> bool {
+        true
+    }
+
+    /// release body的最大字符数, 超过时截断(None为不限制)
+    fn body_max_chars(&self) -> Option<usize> {
+        None
+    }
+
+    /// tag不存在时以git_ref(commit sha或分支)创建, 返回是否新建; 不支持tag的目标不处理
+    fn ensure_tag(&self, _tag_name: &str, _git_ref: &str) -> AnyResult<bool> {
+        Ok(false)
+    }
+
+    /// 将release设为最新版本, 返回是否支持(github的make_latest)
+    fn mark_latest(&self, _release: &Release) -> AnyResult<bool> {
+        Ok(false)
+    }
+
+    /// 是否支持附件的显示名称(github的label)
+    fn supports_asset_label(&self) -> bool {
+        false
+    }
+
+    /// 设置已上传附件的显示名称
+    fn label_asset(&self, _release: &Release, _asset_name: &str, _label: &str) -> AnyResult<()> {
+        Ok(())
+    }
+
+    /// 是否按创建顺序展示最新版本(gitee, gitea): 补同步旧版本后最新版本需要重新创建
+    fn latest_by_creation(&self) -> bool {
+        false
+    }
+}
+
+/// 按参数创建源仓库, 未设置config.transport时使用client发送请求
+pub fn source(cli: &Cli, client: &Client, config: &SyncConfig) -> Box<dyn ReleaseSource> {
+    Box::new(RestForge::with_transport(
+        cli,
+        transport(client, config),
+        cli.source(),
+    ))
+}
+
+/// 按参数创建目标仓库, config.transport只用于github风格的目标仓库
+pub fn target(
+    cli: &Cli,
+    client: &Client,
+    config: &SyncConfig,
+) -> AnyResult<Box<dyn ReleaseTarget>> {
+    let endpoint = cli.target();
+    let target: Box<dyn ReleaseTarget> = match endpoint.forge {
+        Forge::GitLab => Box::new(GitlabTarget::new(
+            client,
+            endpoint,
+            cli.upload_retry_times,
+            config.progress.clone(),
+        )),
+        Forge::S3 => {
+            let bucket = S3Bucket::open(client, cli.s3_url.as_deref().unwrap_or_default())?;
+            Box::new(ObjectTarget::new(Box::new(bucket), endpoint))
+        }
+        Forge::Oss => {
+            let oss_url = cli.oss_url.as_deref().unwrap_or_default();
+            let bucket = OssBucket::open(client, &cli.oss_endpoint, oss_url)?;
+            Box::new(ObjectTarget::new(Box::new(bucket), endpoint))
+        }
+        Forge::Cos => {
+            let cos_url = cli.cos_url.as_deref().unwrap_or_default();
+            let bucket = CosBucket::open(client, &cli.cos_region, cos_url)?;
+            Box::new(ObjectTarget::new(Box::new(bucket), endpoint))
+        }
+        Forge::Webdav => {
+            let webdav_url = cli.webdav_url.as_deref().unwrap_or_default();
+            let store = WebdavStore::open(client, webdav_url, cli.webdav_token.as_deref())?;
+            Box::new(ObjectTarget::new(Box::new(store), endpoint))
+        }
+        #[cfg(not(feature = "sftp"))]
+        Forge::Sftp => bail!("--target sftp is not supported, build with the sftp feature"),
+        #[cfg(feature = "sftp")]
+        Forge::Sftp => {
+            let store = SftpStore::open(
+                cli.sftp_url.as_deref().unwrap_or_default(),
+                cli.sftp_password.as_deref(),
+                cli.sftp_key.as_deref().map(Path::new),
+                cli.http_timeout.as_duration(),
+            )?;
+            Box::new(ObjectTarget::new(Box::new(store), endpoint))
+        }
+        Forge::Local => {
+            let output_dir = cli.output_dir.as_deref().unwrap_or_default();
+            Box::new(LocalTarget::open(
+                endpoint,
+                Path::new(output_dir),
+                cli.fsync,
+            )?)
+        }
+        _ => Box::new(RestForge::with_transport(
+            cli,
+            transport(client, config),
+            endpoint,
+        )),
+    };
+    Ok(match cli.listing_cache_ttl {
+        Some(ttl) => Box::new(CachedTarget::new(target, ttl.as_duration())),
+        None => target,
+    })
+}
+
+/// 调用方设置的http传输, 未设置时为基于client的默认传输(通知config.progress)
+pub fn transport(client: &Client, config: &SyncConfig) -> Arc<dyn Transport> {
+    config
+        .transport
+        .clone()
+        .unwrap_or_else(|| Arc::new(ReqwestTransport::new(client, config.progress.clone())))
+}
+
+/// github风格的REST接口: github, gitee, gitea
+pub struct RestForge {
+    transport: Arc<dyn Transport>,
+    endpoint: RepoEndpoint,
+    // 附件下载加速地址(仅用于github), 按顺序尝试
+    download_mirrors: Vec<String>,
+    mirror_timeout: Option<Duration>,
+    mirror_stats: Mutex<Vec<MirrorStats>>,
+    download_segments: usize,
+    upload_retry_times: u32,
+    // 同步草稿release(--include-drafts)
+    include_drafts: bool,
+    // 多个token(逗号分隔)时速率限制耗尽后轮换
+    tokens: TokenRotation,
+}
+
+impl RestForge {
+    pub fn new(cli: &Cli, client: &Client, endpoint: RepoEndpoint) -> Self {
+        Self::with_transport(cli, Arc::new(ReqwestTransport::new(client, None)), endpoint)
+    }
+
+    /// 通过指定的http传输发送请求(如测试中模拟的接口)
+    pub fn with_transport(
+        cli: &Cli,
+        transport: Arc<dyn Transport>,
+        mut endpoint: RepoEndpoint,
+    ) -> Self {
+        let tokens = TokenRotation::parse(endpoint.token.as_deref());
+        endpoint.token = tokens.current().map(str::to_string);
+        let download_mirrors = match endpoint.forge {
+            Forge::GitHub => cli.github_download_mirror.clone(),
+            _ => Vec::new(),
+        };
+        RestForge {
+            transport,
+            endpoint,
+            download_mirrors,
+            mirror_timeout: cli
+                .download_mirror_timeout
+                .map(|timeout| timeout.as_duration()),
+            mirror_stats: Mutex::default(),
+            download_segments: cli.download_segments,
+            upload_retry_times: cli.upload_retry_times,
+            include_drafts: cli.include_drafts,
+            tokens,
+        }
+    }
+
+    /// 解析releases: gitee企业版的release可能没有assets字段(或为null), 此时单独查询附件列表
+    fn releases_from_text(&self, text: &str) -> AnyResult<Vec<Release>> {
+        let mut releases: Vec<Release> = serde_json::from_str(text)?;
+        if self.endpoint.forge == Forge::Gitee {
+            for i in releases_without_assets(text)? {
+                let release = &mut releases[i];
+                release.assets = self.attach_files(release)?;
+                debug!(
+                    "gitee release assets omitted, attach files fetched: {}, {}",
+                    release.tag_name,
+                    release.assets.len()
+                );
+            }
+        }
+        Ok(releases)
+    }
+
+    /// tag是否存在: gitee没有按名称查询的接口, 分页查询tag列表
+    fn tag_exists(&self, tag_name: &str) -> AnyResult<bool> {
+        let api_url = self.endpoint.api_url();
+        let get = |url: &str| {
+            self.with_token(|endpoint| self.transport.get(url, endpoint.authorization()))
+        };
+        match self.endpoint.forge {
+            Forge::Gitee => {
+                for page in 1.. {
+                    let url = format!("{api_url}/tags?per_page=100&page={page}");
+                    let tags: Vec<serde_json::Value> = serde_json::from_str(&get(&url)?)?;
+                    if tags.iter().any(|tag| tag["name"] == tag_name) {
+                        return Ok(true);
+                    }
+                    if tags.len() < 100 {
+                        break;
+                    }
+                }
+                Ok(false)
+            }
+            Forge::Gitea | Forge::GitHub => {
+                let url = match self.endpoint.forge {
+                    Forge::Gitea => format!("{api_url}/tags/{tag_name}"),
+                    _ => format!("{api_url}/git/ref/tags/{tag_name}"),
+                };
+                match get(&url) {
+                    Ok(_) => Ok(true),
+                    Err(e) if is_not_found(&e) => Ok(false),
+                    Err(e) => Err(e),
+                }
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// gitee的附件列表: {api}/releases/{id}/attach_files
+    fn attach_files(&self, release: &Release) -> AnyResult<Vec<Assert>> {
+        let url = format!(
+            "{}/releases/{}/attach_files?per_page=100",
+            self.endpoint.api_url(),
+            release.id
+        );
+        let text =
+            self.with_token(|endpoint| self.transport.get(&url, endpoint.authorization()))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// 使用当前token访问接口, 速率限制耗尽时轮换到下一个token重试
+    fn with_token<T>(
+        &self,
+        mut request: impl FnMut(&RepoEndpoint) -> AnyResult<T>,
+    ) -> AnyResult<T> {
+        self.tokens.call(|token| {
+            let endpoint = RepoEndpoint {
+                token: token.map(str::to_string),
+                ..self.endpoint.clone()
+            };
+            request(&endpoint)
+        })
+    }
+
+    fn record_mirror(&self, mirror: &str, succeeded: bool) {
+        let mut stats = self
+            .mirror_stats
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        MirrorStats::record(&mut stats, mirror, succeeded as u32, !succeeded as u32);
+    }
+
+    /// 附件上传地址: gitee为attach_files表单, github为uploads.github.com的二进制请求体, gitea为assets表单
+    fn upload_url(&self, release: &Release, asset_name: &str) -> AnyResult<(Url, UploadBody)> {
+        let target = &self.endpoint;
+        match target.forge {
+            Forge::Gitee => {
+                let url = format!("{}/releases/{}/attach_files", target.api_url(), release.id);
+                Ok((Url::parse(&url)?, UploadBody::Multipart("file")))
+            }
+            Forge::Gitea => {
+                let url = format!("{}/releases/{}/assets", target.api_url(), release.id);
+                let url = Url::parse_with_params(&url, [("name", asset_name)])?;
+                Ok((url, UploadBody::Multipart("attachment")))
+            }
+            Forge::GitHub => {
+                let url = format!(
+                    "{}/{}/{}/releases/{}/assets",
+                    GITHUB_UPLOADS_URL, target.owner, target.repo, release.id
+                );
+                let url = Url::parse_with_params(&url, [("name", asset_name)])?;
+                Ok((url, UploadBody::Binary))
+            }
+            Forge::GitLab
+            | Forge::S3
+            | Forge::Oss
+            | Forge::Cos
+            | Forge::Webdav
+            | Forge::Sftp
+            | Forge::Local => {
+                bail!("{} is not a github style forge", target.forge)
+            }
+        }
+    }
+}
+
+impl ReleaseSource for RestForge {
+    fn endpoint(&self) -> &RepoEndpoint {
+        &self.endpoint
+    }
+
+    fn fetch_releases(
+        &self,
+        per_page: usize,
+        page: usize,
+        etag: Option<&str>,
+    ) -> AnyResult<Conditional> {
+        let url = releases_url(&self.endpoint, per_page, page);
+        self.with_token(|endpoint| {
+            self.transport
+                .get_if_none_match(&url, endpoint.authorization(), etag)
+        })
+    }
+
+    fn parse_releases(&self, text: &str) -> AnyResult<Vec<Release>> {
+        let mut releases = self.releases_from_text(text)?;
+        // 草稿release(仅有写权限的token可见): 默认不同步, --include-drafts时作为预发布同步
+        select_drafts(&mut releases, self.include_drafts);
+        // gitee的附件列表中包含源码压缩包, 不作为附件同步
+        if self.endpoint.forge == Forge::Gitee {
+            for release in releases.iter_mut() {
+                release
+                    .assets
+                    .retain(|asset| !asset.browser_download_url.contains("/archive/"));
+            }
+        }
+        Ok(releases)
+    }
+
+    /// 按顺序尝试加速地址, 全部失败(或摘要不一致)时回退为直接下载
+    fn download_asset(
+        &self,
+        asset: &Assert,
+        file_path: &Path,
+        durable: bool,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()> {
+        let direct_url = &asset.browser_download_url;
+        // 草稿release的附件通过接口下载, 不使用加速地址
+        let asset_api = format!("{}/releases/assets/", self.endpoint.api_url());
+        let authorization = direct_url
+            .starts_with(&asset_api)
+            .then(|| self.endpoint.authorization())
+            .flatten();
+        let mirrors = match authorization {
+            Some(_) => &[][..],
+            None => &self.download_mirrors[..],
+        };
+        for mirror in mirrors {
+            let mirror = mirror.trim_end_matches('/');
+            let mirror_url = format!("{mirror}/{direct_url}");
+            // 加速地址失败时尝试下一个, 不重试
+            let options = DownloadOptions {
+                durable,
+                segments: 1,
+                retry_times: 0,
+                timeout: self.mirror_timeout,
+            };
+            match self
+                .transport
+                .download(&mirror_url, None, file_path, options, cancellation)
+                .and_then(|transferred| verify_transferred_digest(asset, &transferred, file_path))
+            {
+                Ok(()) => {
+                    info!("asset downloaded via mirror: {}, {mirror}", asset.name);
+                    self.record_mirror(mirror, true);
+                    return Ok(());
+                }
+                Err(e) if cancellation.check().is_err() => return Err(e),
+                Err(e) => {
+                    warn!("mirror download error, try next: {mirror_url}, {e:#}");
+                    self.record_mirror(mirror, false);
+                }
+            }
+        }
+
+        let options = DownloadOptions {
+            durable,
+            segments: self.download_segments,
+            retry_times: self.upload_retry_times,
+            timeout: None,
+        };
+        let result = self
+            .transport
+            .download(
+                direct_url,
+                authorization.as_deref(),
+                file_path,
+                options,
+                cancellation,
+            )
+            .and_then(|transferred| verify_transferred_digest(asset, &transferred, file_path));
+        if !mirrors.is_empty() {
+            self.record_mirror("direct", result.is_ok());
+        }
+        result
+    }
+
+    fn mirror_stats(&self) -> Vec<MirrorStats> {
+        self.mirror_stats
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// contents接口(github/gitee/gitea一致): {api}/contents/{path}?ref={tag}, 内容为base64编码
+    fn file_content(&self, path: &str, git_ref: &str) -> AnyResult<Option<String>> {
+        let url = format!("{}/contents/{path}", self.endpoint.api_url());
+        let url = Url::parse_with_params(&url, [("ref", git_ref)])?;
+        let text = match self
+            .with_token(|endpoint| self.transport.get(url.as_str(), endpoint.authorization()))
+        {
+            Ok(text) => text,
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let content: serde_json::Value = serde_json::from_str(&text)?;
+        // 目录时返回数组, 按不存在处理
+        let Some(encoded) = content["content"].as_str() else {
+            return Ok(None);
+        };
+        let encoded = encoded.split_whitespace().collect::<String>();
+        Ok(Some(String::from_utf8(STANDARD.decode(encoded)?)?))
+    }
+
+    /// commits接口(github/gitee/gitea一致): {api}/commits/{tag}, 附注tag也返回指向的commit
+    fn tag_commit(&self, tag_name: &str) -> AnyResult<Option<String>> {
+        let url = format!("{}/commits/{tag_name}", self.endpoint.api_url());
+        match self.with_token(|endpoint| self.transport.get(&url, endpoint.authorization())) {
+            Ok(text) => {
+                let commit: serde_json::Value = serde_json::from_str(&text)?;
+                Ok(commit["sha"].as_str().map(str::to_string))
+            }
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn is_not_found(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<ApiError>()
+        .is_some_and(|e| e.status == 404)
+}
+
+impl ReleaseTarget for RestForge {
+    fn endpoint(&self) -> &RepoEndpoint {
+        &self.endpoint
+    }
+
+    fn ensure_tag(&self, tag_name: &str, git_ref: &str) -> AnyResult<bool> {
+        if self.tag_exists(tag_name)? {
+            return Ok(false);
+        }
+        let api_url = self.endpoint.api_url();
+        let (url, body) = match self.endpoint.forge {
+            Forge::Gitee => (
+                format!("{api_url}/tags"),
+                serde_json::json!({ "tag_name": tag_name, "refs": git_ref }),
+            ),
+            Forge::Gitea => (
+                format!("{api_url}/tags"),
+                serde_json::json!({ "tag_name": tag_name, "target": git_ref }),
+            ),
+            // github只能以commit sha创建
+            Forge::GitHub => (
+                format!("{api_url}/git/refs"),
+                serde_json::json!({ "ref": format!("refs/tags/{tag_name}"), "sha": git_ref }),
+            ),
+            _ => return Ok(false),
+        };
+        self.with_token(|endpoint| {
+            self.transport
+                .post(&url, &endpoint.require_authorization()?, &body)
+        })?;
+        info!(
+            "{} tag create success: {tag_name}, {git_ref}",
+            self.endpoint.forge
+        );
+        Ok(true)
+    }
+
+    fn mark_latest(&self, release: &Release) -> AnyResult<bool> {
+        if self.endpoint.forge != Forge::GitHub {
+            return Ok(false);
+        }
+        let url = format!("{}/releases/{}", self.endpoint.api_url(), release.id);
+        let body = serde_json::json!({ "make_latest": "true" });
+        self.with_token(|endpoint| {
+            self.transport
+                .patch(&url, &endpoint.require_authorization()?, &body)
+        })?;
+        Ok(true)
+    }
+
+    fn supports_asset_label(&self) -> bool {
+        self.endpoint.forge == Forge::GitHub
+    }
+
+    fn label_asset(&self, release: &Release, asset_name: &str, label: &str) -> AnyResult<()> {
+        let api_url = self.endpoint.api_url();
+        let url = format!("{api_url}/releases/{}/assets?per_page=100", release.id);
+        let text =
+            self.with_token(|endpoint| self.transport.get(&url, endpoint.authorization()))?;
+        let assets: Vec<Assert> = serde_json::from_str(&text)?;
+        let Some(id) = assets
+            .iter()
+            .find(|asset| asset.name == asset_name)
+            .and_then(|asset| asset.id)
+        else {
+            bail!("uploaded asset not found: {asset_name}");
+        };
+        let url = format!("{api_url}/releases/assets/{id}");
+        let body = serde_json::json!({ "name": asset_name, "label": label });
+        self.with_token(|endpoint| {
+            self.transport
+                .patch(&url, &endpoint.require_authorization()?, &body)
+        })?;
+        Ok(())
+    }
+
+    fn latest_by_creation(&self) -> bool {
+        matches!(self.endpoint.forge, Forge::Gitee | Forge::Gitea)
+    }
+
+    fn body_max_chars(&self) -> Option<usize> {
+        match self.endpoint.forge {
+            // gitee的描述按字节(TEXT, 65535)限制, 按中文3个字节预留
+            Forge::Gitee => Some(20_000),
+            Forge::GitHub => Some(125_000),
+            _ => None,
+        }
+    }
+
+    fn releases(&self) -> AnyResult<Vec<Release>> {
+        // 最近100个(gitea单页最多50个)
+        let per_page = match self.endpoint.forge {
+            Forge::Gitea => 50,
+            _ => 100,
+        };
+        match self.fetch_releases(per_page, 1, None)? {
+            Conditional::Modified { text, .. } => self.releases_from_text(&text),
+            Conditional::NotModified => bail!("unexpected 304 without etag"),
+        }
+    }
+
+    fn create_release(&self, release: &Release) -> AnyResult<Release> {
+        let url = format!("{}/releases", self.endpoint.api_url());
+        let body = serde_json::to_value(release)?;
+        let result = self.with_token(|endpoint| {
+            self.transport
+                .post(&url, &endpoint.require_authorization()?, &body)
+        })?;
+        Ok(serde_json::from_str(&result)?)
+    }
+
+    fn update_release(&self, release: &Release) -> AnyResult<()> {
+        let url = format!("{}/releases/{}", self.endpoint.api_url(), release.id);
+        let body = serde_json::to_value(release)?;
+        self.with_token(|endpoint| {
+            self.transport
+                .patch(&url, &endpoint.require_authorization()?, &body)
+        })?;
+        Ok(())
+    }
+
+    fn delete_release(&self, release: &Release) -> AnyResult<()> {
+        let url = format!("{}/releases/{}", self.endpoint.api_url(), release.id);
+        self.with_token(|endpoint| {
+            self.transport
+                .delete(&url, &endpoint.require_authorization()?)
+        })
+    }
+
+    fn upload_asset(
+        &self,
+        release: &Release,
+        asset_name: &str,
+        file_path: &Path,
+        cancellation: &Cancellation,
+    ) -> AnyResult<()> {
+        let (url, body) = self.upload_url(release, asset_name)?;
+        self.with_token(|endpoint| {
+            self.transport.upload(
+                url.as_str(),
+                &endpoint.require_authorization()?,
+                file_path,
+                body,
+                self.upload_retry_times,
+                cancellation,
+            )
+        })?;
+        Ok(())
+    }
+
+    fn delete_asset(&self, release: &Release, asset: &Assert) -> AnyResult<()> {
+        let Some(id) = asset.id else {
+            bail!("asset id is unknown: {}", asset.name);
+        };
+        let target = &self.endpoint;
+        let url = match target.forge {
+            Forge::Gitee => format!(
+                "{}/releases/{}/attach_files/{id}",
+                target.api_url(),
+                release.id
+            ),
+            Forge::Gitea => format!("{}/releases/{}/assets/{id}", target.api_url(), release.id),
+            Forge::GitHub => format!("{}/releases/assets/{id}", target.api_url()),
+            Forge::GitLab
+            | Forge::S3
+            | Forge::Oss
+            | Forge::Cos
+            | Forge::Webdav
+            | Forge::Sftp
+            | Forge::Local => {
+                bail!("{} is not a github style forge", target.forge)
+            }
+        };
+        self.with_token(|endpoint| {
+            self.transport
+                .delete(&url, &endpoint.require_authorization()?)
+        })
+    }
+}
+
+/// 草稿release: 不同步时移除, 同步时标记为预发布, 附件通过接口地址(携带token)下载
+fn select_drafts(releases: &mut Vec<Release>, include_drafts: bool) {
+    if !include_drafts {
+        let count = releases.len();
+        releases.retain(|release| !release.draft);
+        if releases.len() < count {
+            debug!("draft releases skipped: {}", count - releases.len());
+        }
+        return;
+    }
+    for release in releases.iter_mut().filter(|release| release.draft) {
+        release.prerelease = true;
+        // 草稿的附件没有公开的下载地址
+        for asset in release.assets.iter_mut() {
+            if let Some(api_url) = &asset.api_url {
+                asset.browser_download_url = api_url.clone();
+            }
+        }
+        info!("draft release included as prerelease: {}", release.tag_name);
+    }
+}
+
+/// 没有assets字段(或为null)的release的下标
+fn releases_without_assets(text: &str) -> AnyResult<Vec<usize>> {
+    let releases: Vec<serde_json::Value> = serde_json::from_str(text)?;
+    Ok(releases
+        .iter()
+        .enumerate()
+        .filter(|(_, release)| release.get("assets").is_none_or(|assets| assets.is_null()))
+        .map(|(i, _)| i)
+        .collect())
+}
+
+/// releases接口地址: gitea的分页参数为limit
+pub fn releases_url(endpoint: &RepoEndpoint, per_page: usize, page: usize) -> String {
+    match endpoint.forge {
+        Forge::Gitea => format!(
+            "{}/releases?limit={per_page}&page={page}",
+            endpoint.api_url()
+        ),
+        _ => format!(
+            "{}/releases?per_page={per_page}&page={page}",
+            endpoint.api_url()
+        ),
+    }
+}
+
+/// 校验下载的附件与github的digest是否一致, 不一致则删除文件
+pub fn verify_asset_digest(asset: &Assert, file_path: &Path) -> AnyResult<()> {
+    let Some(digest) = &asset.digest else {
+        return Ok(());
+    };
+    if !fsutil::verify_digest(file_path, digest)? {
+        let _ = fs::remove_file(file_path);
+        bail!("asset digest mismatch: {}, expected {digest}", asset.name);
+    }
+    debug!("asset digest verified: {}", asset.name);
+    Ok(())
+}
+
+/// 校验下载的附件摘要: sha256已在下载时计算, 无需再次读取文件
+fn verify_transferred_digest(
+    asset: &Assert,
+    transferred: &Transferred,
+    file_path: &Path,
+) -> AnyResult<()> {
+    match asset
+        .digest
+        .as_deref()
+        .and_then(|d| d.strip_prefix("sha256:"))
+    {
+        Some(expected) if !transferred.sha256.eq_ignore_ascii_case(expected) => {
+            let _ = fs::remove_file(file_path);
+            bail!(
+                "asset digest mismatch: {}, expected sha256:{expected}",
+                asset.name
+            );
+        }
+        Some(_) => {
+            debug!("asset digest verified: {}", asset.name);
+            Ok(())
+        }
+        None => verify_asset_digest(asset, file_path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_releases_without_assets() -> AnyResult<()> {
+        // gitee企业版: assets字段缺失或为null
+        let text = r#"[
+            {"id":3,"tag_name":"v0.3.0","name":"v0.3.0","body":null,"prerelease":false,"target_commitish":"main",
+             "assets":[{"name":"a.zip","browser_download_url":"https://gitee.com/o/r/releases/download/v0.3.0/a.zip"}]},
+            {"id":2,"tag_name":"v0.2.0","name":"v0.2.0","body":null,"prerelease":false,"target_commitish":"main","assets":null},
+            {"id":1,"tag_name":"v0.1.0","name":"v0.1.0","body":null,"prerelease":false,"target_commitish":"main"}
+        ]"#;
+        let releases: Vec<Release> = serde_json::from_str(text)?;
+        assert_eq!(releases[0].assets.len(), 1);
+        assert!(releases[1].assets.is_empty() && releases[2].assets.is_empty());
+        assert_eq!(releases_without_assets(text)?, [1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_draft_releases() -> AnyResult<()> {
+        let text = r#"[
+            {"id":2,"tag_name":"v0.2.0","name":"v0.2.0","body":null,"prerelease":false,"target_commitish":"main","draft":true,
+             "assets":[{"url":"https://api.github.com/repos/o/r/releases/assets/9","name":"a.zip",
+                        "browser_download_url":"https://github.com/o/r/releases/download/untagged-1/a.zip"}]},
+            {"id":1,"tag_name":"v0.1.0","name":"v0.1.0","body":null,"prerelease":false,"target_commitish":"main","draft":false,"assets":[]}
+        ]"#;
+        let releases: Vec<Release> = serde_json::from_str(text)?;
+        assert!(releases[0].draft && !releases[1].draft);
+
+        let mut skipped = releases.clone();
+        select_drafts(&mut skipped, false);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].tag_name, "v0.1.0");
+
+        let mut included = releases;
+        select_drafts(&mut included, true);
+        assert!(included[0].prerelease && !included[1].prerelease);
+        assert_eq!(
+            included[0].assets[0].browser_download_url,
+            "https://api.github.com/repos/o/r/releases/assets/9"
+        );
+        Ok(())
+    }
+}
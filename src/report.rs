@@ -0,0 +1,486 @@
+use crate::events::Events;
+use crate::i18n::{self, Lang, tr, tr_in};
+use crate::{AnyResult, redact};
+use log::{info, warn};
+use serde::Serialize;
+use std::env;
+use std::fmt::{Display, Formatter, Write as _};
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// 同步结果记录: 哪些release同步了、跳过了、清理了、失败了, 以及同步过程中的警告
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SyncReport {
+    pub synced: Vec<SyncedRelease>,
+    pub cleaned: Vec<String>,
+    pub deleted: Vec<String>,
+    pub failed: Vec<FailedRelease>,
+    pub skipped: Vec<SkippedRelease>,
+    // 不影响同步结果的问题(如超出目标仓库容量、源仓库releases信息过期)
+    pub warnings: Vec<String>,
+    // 附件下载加速地址的成功/失败次数(监控加速地址的可用性)
+    pub mirrors: Vec<MirrorStats>,
+    // 同步过程中的事件发送端(记录结果的同时发送事件), 同步结束后释放
+    #[serde(skip)]
+    pub(crate) events: Events,
+}
+
+/// 下载地址的使用情况, mirror为direct时表示直接下载
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MirrorStats {
+    pub mirror: String,
+    pub succeeded: u32,
+    pub failed: u32,
+}
+
+impl MirrorStats {
+    /// 按下载地址累计, 保持首次出现的顺序
+    pub fn record(stats: &mut Vec<MirrorStats>, mirror: &str, succeeded: u32, failed: u32) {
+        let index = match stats.iter().position(|m| m.mirror == mirror) {
+            Some(index) => index,
+            None => {
+                stats.push(MirrorStats {
+                    mirror: mirror.to_string(),
+                    ..Default::default()
+                });
+                stats.len() - 1
+            }
+        };
+        stats[index].succeeded += succeeded;
+        stats[index].failed += failed;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncedRelease {
+    pub tag_name: String,
+    pub action: ReleaseAction,
+    pub uploaded_assets: Vec<String>,
+    // 上传附件的总字节数
+    pub uploaded_bytes: u64,
+    // 未同步的附件(部分同步)
+    pub missing_assets: Vec<String>,
+    // 镜像与源仓库不一致的地方(如附件冲突跳过、改名)
+    pub warnings: Vec<String>,
+}
+
+impl Display for SyncReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}, {}: {}, {}: {}, {}: {}",
+            tr("synced", "已同步"),
+            self.synced.len(),
+            tr("cleaned", "已清理"),
+            self.cleaned.len(),
+            tr("deleted", "已删除"),
+            self.deleted.len(),
+            tr("failed", "失败"),
+            self.failed.len()
+        )?;
+        if !self.skipped.is_empty() {
+            write!(f, ", {}: {}", tr("skipped", "已跳过"), self.skipped.len())?;
+        }
+        match self.warning_count() {
+            0 => Ok(()),
+            count => write!(f, ", {}: {count}", tr("warnings", "警告")),
+        }
+    }
+}
+
+/// Gitee仓库Release的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseAction {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+impl ReleaseAction {
+    fn label(self, lang: Lang) -> &'static str {
+        match self {
+            ReleaseAction::Created => tr_in(lang, "created", "已创建"),
+            ReleaseAction::Updated => tr_in(lang, "updated", "已更新"),
+            ReleaseAction::Unchanged => tr_in(lang, "unchanged", "无变化"),
+        }
+    }
+}
+
+impl Display for ReleaseAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let action = match self {
+            ReleaseAction::Created => "created",
+            ReleaseAction::Updated => "updated",
+            ReleaseAction::Unchanged => "unchanged",
+        };
+        write!(f, "{action}")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedRelease {
+    pub tag_name: String,
+    pub error: String,
+}
+
+/// 未同步的release及原因(如不在同步计划中、上次运行已完成)
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedRelease {
+    pub tag_name: String,
+    pub reason: String,
+}
+
+impl SyncReport {
+    pub fn synced(&mut self, synced: SyncedRelease) {
+        self.synced.push(synced);
+    }
+
+    pub fn cleaned(&mut self, tag_name: &str) {
+        self.cleaned.push(tag_name.to_string());
+    }
+
+    /// github上已删除, 同步删除的gitee release
+    pub fn deleted(&mut self, tag_name: &str) {
+        self.deleted.push(tag_name.to_string());
+    }
+
+    pub fn failed(&mut self, tag_name: &str, error: &anyhow::Error) {
+        self.failed.push(FailedRelease {
+            tag_name: tag_name.to_string(),
+            error: redact::redact(&format!("{error:#}")).into_owned(),
+        });
+    }
+
+    pub fn skipped(&mut self, tag_name: &str, reason: &str) {
+        self.skipped.push(SkippedRelease {
+            tag_name: tag_name.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    pub fn warning(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    /// 警告总数: 同步过程中的警告和各release的警告
+    pub fn warning_count(&self) -> usize {
+        self.warnings.len()
+            + self
+                .synced
+                .iter()
+                .map(|release| release.warnings.len())
+                .sum::<usize>()
+    }
+
+    /// 合并其他目标平台的同步结果(并行同步多个目标平台): tag前加上目标平台, 如: gitee:hepengju/redis-me: v1.0.0
+    pub fn merge(&mut self, target: &str, other: SyncReport) {
+        let prefixed = |tag_name: String| format!("{target}: {tag_name}");
+        self.synced
+            .extend(other.synced.into_iter().map(|release| SyncedRelease {
+                tag_name: prefixed(release.tag_name),
+                ..release
+            }));
+        self.cleaned.extend(other.cleaned.into_iter().map(prefixed));
+        self.deleted.extend(other.deleted.into_iter().map(prefixed));
+        self.failed
+            .extend(other.failed.into_iter().map(|release| FailedRelease {
+                tag_name: prefixed(release.tag_name),
+                ..release
+            }));
+        self.skipped
+            .extend(other.skipped.into_iter().map(|release| SkippedRelease {
+                tag_name: prefixed(release.tag_name),
+                ..release
+            }));
+        self.warnings
+            .extend(other.warnings.into_iter().map(prefixed));
+        for stats in other.mirrors {
+            self.mirror_used(&stats.mirror, stats.succeeded, stats.failed);
+        }
+    }
+
+    /// 累计下载地址的成功/失败次数
+    pub fn mirror_used(&mut self, mirror: &str, succeeded: u32, failed: u32) {
+        MirrorStats::record(&mut self.mirrors, mirror, succeeded, failed);
+    }
+
+    /// 没有任何变化: 无新建/更新的release, 无上传的附件, 无清理和失败
+    /// 上传附件的总字节数
+    pub fn uploaded_bytes(&self) -> u64 {
+        self.synced
+            .iter()
+            .map(|release| release.uploaded_bytes)
+            .sum()
+    }
+
+    pub fn is_nothing_to_sync(&self) -> bool {
+        self.cleaned.is_empty()
+            && self.deleted.is_empty()
+            && self.failed.is_empty()
+            && self.synced.iter().all(|release| {
+                release.action == ReleaseAction::Unchanged && release.uploaded_assets.is_empty()
+            })
+    }
+
+    /// 生成Markdown表格(按--lang的语言)
+    pub fn to_markdown(&self, title: &str) -> String {
+        self.markdown(i18n::lang(), title)
+    }
+
+    fn markdown(&self, lang: Lang, title: &str) -> String {
+        let tr = |en, zh| tr_in(lang, en, zh);
+        let mut md = String::new();
+        let _ = writeln!(md, "### {title}\n");
+        if self.is_nothing_to_sync() {
+            let _ = writeln!(md, "{}", tr("nothing to sync", "没有需要同步的内容"));
+            return md;
+        }
+
+        let _ = writeln!(
+            md,
+            "{}",
+            tr("| status | tag | detail |", "| 状态 | 标签 | 详情 |")
+        );
+        md.push_str("| --- | --- | --- |\n");
+        for release in &self.synced {
+            let action = release.action.label(lang);
+            let mut detail = if release.uploaded_assets.is_empty() {
+                match lang {
+                    Lang::En => format!("release {action}, no asset uploaded"),
+                    Lang::Zh => format!("release{action}, 没有上传附件"),
+                }
+            } else {
+                let count = release.uploaded_assets.len();
+                let assets = release.uploaded_assets.join(", ");
+                match lang {
+                    Lang::En => format!("release {action}, {count} asset(s) uploaded: {assets}"),
+                    Lang::Zh => format!("release{action}, 上传{count}个附件: {assets}"),
+                }
+            };
+            if !release.missing_assets.is_empty() {
+                let count = release.missing_assets.len();
+                let assets = release.missing_assets.join(", ");
+                let _ = match lang {
+                    Lang::En => write!(
+                        detail,
+                        "; partial mirror, {count} asset(s) missing: {assets}"
+                    ),
+                    Lang::Zh => write!(detail, "; 部分同步, 缺少{count}个附件: {assets}"),
+                };
+            }
+            for warning in &release.warnings {
+                let _ = write!(detail, "; ⚠️ {warning}");
+            }
+            let _ = writeln!(
+                md,
+                "| ✅ {} | {} | {} |",
+                tr("synced", "已同步"),
+                escape_cell(&release.tag_name),
+                escape_cell(&detail)
+            );
+        }
+        for tag_name in &self.cleaned {
+            let _ = writeln!(
+                md,
+                "| 🧹 {} | {} | |",
+                tr("cleaned", "已清理"),
+                escape_cell(tag_name)
+            );
+        }
+        for tag_name in &self.deleted {
+            let _ = writeln!(
+                md,
+                "| 🗑️ {} | {} | {} |",
+                tr("deleted", "已删除"),
+                escape_cell(tag_name),
+                tr("deleted on github", "github上已删除")
+            );
+        }
+        for release in &self.skipped {
+            let _ = writeln!(
+                md,
+                "| ⏭️ {} | {} | {} |",
+                tr("skipped", "已跳过"),
+                escape_cell(&release.tag_name),
+                escape_cell(&release.reason)
+            );
+        }
+        for release in &self.failed {
+            let _ = writeln!(
+                md,
+                "| ❌ {} | {} | {} |",
+                tr("failed", "失败"),
+                escape_cell(&release.tag_name),
+                escape_cell(&release.error)
+            );
+        }
+        for warning in &self.warnings {
+            let _ = writeln!(
+                md,
+                "| ⚠️ {} | - | {} |",
+                tr("warning", "警告"),
+                escape_cell(warning)
+            );
+        }
+        if !self.mirrors.is_empty() {
+            let _ = writeln!(
+                md,
+                "\n{}",
+                tr(
+                    "| mirror | succeeded | failed |",
+                    "| 加速地址 | 成功 | 失败 |"
+                )
+            );
+            md.push_str("| --- | --- | --- |\n");
+            for stats in &self.mirrors {
+                let _ = writeln!(
+                    md,
+                    "| {} | {} | {} |",
+                    escape_cell(&stats.mirror),
+                    stats.succeeded,
+                    stats.failed
+                );
+            }
+        }
+        md
+    }
+
+    /// 如果设置了环境变量GITHUB_STEP_SUMMARY, 则将结果追加写入该文件(GitHub Actions的Summary页面展示)
+    pub fn write_github_step_summary(&self, title: &str) {
+        let Ok(path) = env::var("GITHUB_STEP_SUMMARY") else {
+            return;
+        };
+        if path.is_empty() {
+            return;
+        }
+
+        let write = || -> AnyResult<()> {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            file.write_all(self.to_markdown(title).as_bytes())?;
+            Ok(())
+        };
+        match write() {
+            Ok(()) => info!("github step summary write success: {path}"),
+            Err(e) => warn!("github step summary write error: {path}, {e:#}"),
+        }
+    }
+}
+
+// 表格单元格中的竖线和换行需要转义
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_markdown() {
+        let mut report = SyncReport::default();
+        report.synced(SyncedRelease {
+            tag_name: "v0.9.4".to_string(),
+            action: ReleaseAction::Created,
+            uploaded_assets: vec!["a.zip".to_string(), "b.tar.gz".to_string()],
+            uploaded_bytes: 2048,
+            missing_assets: Vec::new(),
+            warnings: Vec::new(),
+        });
+        report.cleaned("v0.1.0");
+        report.deleted("v0.2.0");
+        report.failed("v0.9.3", &anyhow::anyhow!("upload file error: a|b\nc"));
+        report.skipped("v0.9.0", "not in sync plan");
+        report.warning("source releases are stale, deletes not propagated".to_string());
+
+        let md = report.to_markdown("release2gitee");
+        assert!(md.contains(
+            "| ✅ synced | v0.9.4 | release created, 2 asset(s) uploaded: a.zip, b.tar.gz |"
+        ));
+        assert!(md.contains("| 🧹 cleaned | v0.1.0 | |"));
+        assert!(md.contains("| 🗑️ deleted | v0.2.0 | deleted on github |"));
+        assert!(md.contains("| ❌ failed | v0.9.3 | upload file error: a\\|b c |"));
+        assert!(md.contains("| ⏭️ skipped | v0.9.0 | not in sync plan |"));
+        assert!(
+            md.contains("| ⚠️ warning | - | source releases are stale, deletes not propagated |")
+        );
+        assert_eq!(
+            report.to_string(),
+            "synced: 1, cleaned: 1, deleted: 1, failed: 1, skipped: 1, warnings: 1"
+        );
+
+        let md = report.markdown(Lang::Zh, "release2gitee");
+        assert!(md.contains("| 状态 | 标签 | 详情 |"));
+        assert!(
+            md.contains("| ✅ 已同步 | v0.9.4 | release已创建, 上传2个附件: a.zip, b.tar.gz |")
+        );
+
+        let mut report = SyncReport::default();
+        report.synced(SyncedRelease {
+            tag_name: "v0.9.4".to_string(),
+            action: ReleaseAction::Unchanged,
+            uploaded_assets: Vec::new(),
+            uploaded_bytes: 0,
+            missing_assets: Vec::new(),
+            warnings: Vec::new(),
+        });
+        assert!(report.is_nothing_to_sync());
+        assert!(
+            report
+                .to_markdown("release2gitee")
+                .contains("nothing to sync")
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut target_report = SyncReport::default();
+        target_report.cleaned("v0.1.0");
+        target_report.failed("v0.9.3", &anyhow::anyhow!("quota exceeded"));
+
+        let mut report = SyncReport::default();
+        report.merge("gitee:hepengju/redis-me", target_report);
+        assert_eq!(report.cleaned, vec!["gitee:hepengju/redis-me: v0.1.0"]);
+        assert_eq!(report.failed[0].tag_name, "gitee:hepengju/redis-me: v0.9.3");
+        assert_eq!(
+            report.to_string(),
+            "synced: 0, cleaned: 1, deleted: 0, failed: 1"
+        );
+
+        let mut target_report = SyncReport::default();
+        target_report.skipped("v0.8.0", "completed in last run");
+        target_report.warning("pending uploads exceed target quota".to_string());
+        report.merge("gitea:hepengju/redis-me", target_report);
+        assert_eq!(
+            report.skipped[0].tag_name,
+            "gitea:hepengju/redis-me: v0.8.0"
+        );
+        assert_eq!(
+            report.warnings,
+            vec!["gitea:hepengju/redis-me: pending uploads exceed target quota"]
+        );
+    }
+
+    #[test]
+    fn test_mirror_stats() {
+        let mut report = SyncReport::default();
+        report.mirror_used("https://ghproxy.example", 1, 0);
+        let mut target_report = SyncReport::default();
+        target_report.mirror_used("https://ghproxy.example", 2, 1);
+        target_report.mirror_used("direct", 1, 0);
+        report.merge("gitea:hepengju/redis-me", target_report);
+        assert_eq!(
+            report.mirrors[0],
+            MirrorStats {
+                mirror: "https://ghproxy.example".to_string(),
+                succeeded: 3,
+                failed: 1,
+            }
+        );
+
+        report.failed("v0.9.3", &anyhow::anyhow!("quota exceeded"));
+        let md = report.to_markdown("release2gitee");
+        assert!(md.contains("| https://ghproxy.example | 3 | 1 |"));
+        assert!(md.contains("| direct | 1 | 0 |"));
+    }
+}
@@ -0,0 +1,138 @@
+//! 自定义的地址替换规则(--rewrite): 在内置的源仓库→目标仓库地址替换之后依次执行
+//! 用于替换raw.githubusercontent.com、文档站点、CDN等其他地址
+
+use regex::Regex;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// 替换规则: `FROM=>TO`按文本替换, `regex:PATTERN=>TO`按正则表达式替换(TO中可用$1、${name}引用分组)
+#[derive(Debug, Clone)]
+pub struct RewriteRule {
+    text: String,
+    from: Pattern,
+    to: String,
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Literal(String),
+    Regex(Regex),
+}
+
+impl FromStr for RewriteRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((from, to)) = s.split_once("=>") else {
+            return Err(format!("invalid rewrite rule, expect FROM=>TO: {s}"));
+        };
+        let from = match from.strip_prefix("regex:") {
+            Some(pattern) => Pattern::Regex(
+                Regex::new(pattern)
+                    .map_err(|e| format!("invalid rewrite regex: {pattern}, {e}"))?,
+            ),
+            None if from.is_empty() => return Err(format!("rewrite rule FROM is empty: {s}")),
+            None => Pattern::Literal(from.to_string()),
+        };
+        Ok(RewriteRule {
+            text: s.to_string(),
+            from,
+            to: to.to_string(),
+        })
+    }
+}
+
+impl Display for RewriteRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl RewriteRule {
+    pub fn apply(&self, content: &str) -> String {
+        match &self.from {
+            Pattern::Literal(from) => content.replace(from, &self.to),
+            Pattern::Regex(regex) => regex.replace_all(content, self.to.as_str()).into_owned(),
+        }
+    }
+}
+
+/// 依次执行替换规则
+pub fn apply_rules(rules: &[RewriteRule], content: String) -> String {
+    rules
+        .iter()
+        .fold(content, |content, rule| rule.apply(&content))
+}
+
+/// 文件名的通配符匹配: *匹配任意个字符, ?匹配一个字符
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.as_bytes(), name.as_bytes());
+    let (mut p, mut n) = (0, 0);
+    // 最近一个*的位置和它匹配到的位置, 不匹配时回溯
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_rules() -> Result<(), String> {
+        let rules = [
+            "https://raw.githubusercontent.com/hepengju/redis-me/=>https://gitee.com/hepengju/redis-me/raw/"
+                .parse::<RewriteRule>()?,
+            r"regex:https://cdn\.example\.com/(v[\d.]+)/=>https://mirror.example.cn/$1/".parse()?,
+        ];
+        let content = "logo: https://raw.githubusercontent.com/hepengju/redis-me/main/logo.png\n\
+                       cdn: https://cdn.example.com/v1.2.0/app.js"
+            .to_string();
+        assert_eq!(
+            apply_rules(&rules, content),
+            "logo: https://gitee.com/hepengju/redis-me/raw/main/logo.png\n\
+             cdn: https://mirror.example.cn/v1.2.0/app.js"
+        );
+        assert_eq!(
+            rules[1].to_string(),
+            r"regex:https://cdn\.example\.com/(v[\d.]+)/=>https://mirror.example.cn/$1/"
+        );
+
+        assert!("https://a.example.com".parse::<RewriteRule>().is_err());
+        assert!("=>https://b.example.com".parse::<RewriteRule>().is_err());
+        assert!("regex:(=>x".parse::<RewriteRule>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.nuspec", "app.nuspec"));
+        assert!(glob_match("latest*.yml", "latest-mac.yml"));
+        assert!(glob_match("checksums.txt", "checksums.txt"));
+        assert!(glob_match("app-?.?.json", "app-1.2.json"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("*a*b", "xaxxab"));
+        assert!(!glob_match("*.nuspec", "app.nuspec.zip"));
+        assert!(!glob_match("latest.yml", "latest.yaml"));
+        assert!(!glob_match("app-?.json", "app-10.json"));
+    }
+}
@@ -0,0 +1,253 @@
+//! 定时同步(--schedule): cron表达式`分 时 日 月 周`, 按--schedule-utc-offset的时区计算
+
+use crate::provenance;
+use crate::s3::civil_date;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+/// 查找下一次执行时间的最大范围(天): 超出时表达式无法匹配(如2月30日)
+const MAX_SEARCH_DAYS: u64 = 366 * 5;
+
+/// cron表达式: 分(0-59) 时(0-23) 日(1-31) 月(1-12) 周(0-7, 0和7为周日)
+/// - 每个字段支持: `*`, `5`, `1-5`, `*/15`, `0-30/10`, `1,15,30`
+/// - 也支持: `@hourly`, `@daily`, `@weekly`, `@monthly`
+/// - 日和周都不是`*`时, 满足其一即执行(与cron一致)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    text: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expr = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            expr => expr,
+        };
+        let fields = expr.split_whitespace().collect::<Vec<_>>();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "invalid cron expression, expect 5 fields (minute hour day month weekday): {s}"
+            ));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // 7也是周日
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Schedule {
+            text: s.trim().to_string(),
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            // 与vixie cron一致: 以*开头(包括*/2)的字段视为不限制
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// 字段的取值集合(按位): 逗号分隔的多个部分, 每部分为`*`、`a`或`a-b`, 可带`/step`
+fn parse_field(field: &str, min: u64, max: u64) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("invalid cron step: {part}"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let number = |text: &str| {
+            text.parse::<u64>()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or_else(|| format!("invalid cron value '{text}', expect {min}-{max}"))
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // 5/10: 从5开始每10个
+                None if step > 1 => (number(range)?, max),
+                None => (number(range)?, number(range)?),
+            },
+        };
+        if start > end {
+            return Err(format!("invalid cron range: {part}"));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+impl Schedule {
+    /// 下一次执行时间(unix秒, 晚于after的整分钟); offset_secs: 计算时使用的时区与UTC的偏移
+    pub fn next_after(&self, after: u64, offset_secs: i64) -> Option<u64> {
+        let matches = |bits: u64, n: i64| bits & (1 << n) != 0;
+        // 按当地时间计算, 返回时换算回UTC
+        let mut local = (after as i64 + offset_secs).div_euclid(60) * 60 + 60;
+        let limit = local + (MAX_SEARCH_DAYS * 86400) as i64;
+        while local < limit {
+            let (_, month, day) = civil_date(local.max(0) as u64);
+            // 1970-01-01为周四
+            let weekday = (local.div_euclid(86400) + 4).rem_euclid(7);
+            let day_matches = match (self.any_day, self.any_weekday) {
+                (false, false) => matches(self.days, day) || matches(self.weekdays, weekday),
+                _ => matches(self.days, day) && matches(self.weekdays, weekday),
+            };
+            if !matches(self.months, month) || !day_matches {
+                local = (local.div_euclid(86400) + 1) * 86400;
+                continue;
+            }
+            let secs = local.rem_euclid(86400);
+            if !matches(self.hours, secs / 3600) {
+                local = (local.div_euclid(3600) + 1) * 3600;
+                continue;
+            }
+            if !matches(self.minutes, secs % 3600 / 60) {
+                local += 60;
+                continue;
+            }
+            return u64::try_from(local - offset_secs).ok();
+        }
+        None
+    }
+
+    /// 从现在开始的下一次执行时间
+    pub fn next(&self, offset: UtcOffset) -> Option<u64> {
+        self.next_after(provenance::now_secs(), offset.0)
+    }
+}
+
+/// 时区与UTC的偏移: `+08:00`, `-05:30`, `+8`, `Z`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UtcOffset(pub i64);
+
+impl FromStr for UtcOffset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        if text.eq_ignore_ascii_case("z") || text.eq_ignore_ascii_case("utc") {
+            return Ok(UtcOffset(0));
+        }
+        let invalid = || format!("invalid utc offset: {s}, expect like +08:00");
+        let (sign, rest) = match text.as_bytes().first() {
+            Some(b'+') => (1, &text[1..]),
+            Some(b'-') => (-1, &text[1..]),
+            _ => (1, text),
+        };
+        let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+        let hours: i64 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(invalid());
+        }
+        Ok(UtcOffset(sign * (hours * 3600 + minutes * 60)))
+    }
+}
+
+impl Display for UtcOffset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { '-' } else { '+' };
+        let secs = self.0.abs();
+        write!(f, "{sign}{:02}:{:02}", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+/// 等待到指定时间: 每分钟重新计算, 系统时间调整后仍然准时
+pub fn sleep_until(unix_secs: u64) {
+    loop {
+        let now = provenance::now_secs();
+        if now >= unix_secs {
+            return;
+        }
+        thread::sleep(Duration::from_secs((unix_secs - now).min(60)));
+    }
+}
+
+/// 日志中的执行时间(按--schedule-utc-offset的时区): 2026-01-01 03:00 +08:00
+pub fn format_time(unix_secs: u64, offset: UtcOffset) -> String {
+    let local = (unix_secs as i64 + offset.0).max(0) as u64;
+    let (year, month, day) = civil_date(local);
+    let secs = local % 86400;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} {offset}",
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_next() -> Result<(), String> {
+        // 2026-01-01T00:00:00Z, 周四
+        let now = 1767225600;
+        let daily: Schedule = "0 3 * * *".parse()?;
+        assert_eq!(daily.next_after(now, 0), Some(now + 3 * 3600));
+        // 北京时间03:00为UTC前一天19:00
+        let offset = "+08:00".parse::<UtcOffset>()?;
+        assert_eq!(offset.to_string(), "+08:00");
+        assert_eq!(daily.next_after(now, offset.0), Some(now + 19 * 3600));
+        assert_eq!(
+            format_time(now + 19 * 3600, offset),
+            "2026-01-02 03:00 +08:00"
+        );
+
+        let every_15: Schedule = "*/15 * * * *".parse()?;
+        assert_eq!(every_15.next_after(now + 60, 0), Some(now + 15 * 60));
+        // 周一09:30: 2026-01-05
+        let monday: Schedule = "30 9 * * 1".parse()?;
+        assert_eq!(
+            monday.next_after(now, 0),
+            Some(now + 4 * 86400 + 9 * 3600 + 1800)
+        );
+        // 日和周都指定时满足其一: 15日或周五(2026-01-02)
+        let either: Schedule = "0 0 15 * 5".parse()?;
+        assert_eq!(either.next_after(now, 0), Some(now + 86400));
+        // */2不限制日: 同时满足奇数日和周一(2026-01-05), 而不是满足其一
+        let odd_monday: Schedule = "0 0 */2 * 1".parse()?;
+        assert_eq!(odd_monday.next_after(now, 0), Some(now + 4 * 86400));
+        assert_eq!(
+            "@daily".parse::<Schedule>()?.next_after(now, 0),
+            Some(now + 86400)
+        );
+        assert_eq!("0 0 30 2 *".parse::<Schedule>()?.next_after(now, 0), None);
+
+        assert!("0 3 * *".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        Ok(())
+    }
+}
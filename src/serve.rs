@@ -0,0 +1,277 @@
+use crate::cancel::CancellationToken;
+use crate::httpd::{self, Request};
+use crate::model::{Cli, ServeArgs, SyncConfig};
+use crate::s3::hex;
+use crate::status::StatusBoard;
+use crate::{AnyResult, redact, sync_with_report};
+use anyhow::bail;
+use hmac::{Hmac, Mac};
+use log::{debug, error, info, warn};
+use serde_json::Value;
+use sha2::Sha256;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// webhook请求体的上限(GitHub的payload最大25MB)
+const MAX_PAYLOAD: usize = 25 * 1024 * 1024;
+
+/// 读取请求的超时时间: 慢速连接的处理线程超时后结束
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 触发同步的release事件
+const SYNC_ACTIONS: &[&str] = &["published", "released", "prereleased", "edited"];
+
+/// webhook服务: 校验签名后, 按payload中的仓库排队同步(同一仓库排队中时不重复加入)
+/// - 同步在后台线程依次执行, webhook请求立即返回202(GitHub等待响应的时间只有10秒)
+/// - 每个连接在单独的线程中处理(读取超时后结束), 慢速连接不阻塞其他请求
+/// - GET /healthz, /status: 存活探针和各仓库最近一次同步的结果
+pub fn run(cli: &Cli, args: &ServeArgs) -> AnyResult<()> {
+    // 空的secret可以被任何人签名
+    if args.webhook_secret.is_empty() {
+        bail!("--webhook-secret must not be empty");
+    }
+    redact::register(&args.webhook_secret);
+    let listener = TcpListener::bind(&args.listen)?;
+    info!("webhook server listening: {}", listener.local_addr()?);
+
+    let (sender, receiver) = mpsc::channel();
+    let board = StatusBoard::new();
+    thread::scope(|scope| {
+        scope.spawn(|| sync_worker(receiver, &board));
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let (sender, board) = (&sender, &board);
+            scope.spawn(move || {
+                if let Err(e) = handle_connection(stream, cli, args, sender, board) {
+                    warn!("webhook connection error: {e:#}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// 依次执行排队的同步
+fn sync_worker(receiver: Receiver<Cli>, board: &StatusBoard) {
+    for cli in receiver {
+        let repo = format!("{}/{}", cli.github_owner, cli.github_repo);
+        // 开始同步前移出队列: 同步期间收到的事件会再次排队
+        let started = board.start(&repo);
+        info!("webhook sync start: {repo}");
+        let cancel = CancellationToken::new();
+        let result = sync_with_report(&cli, &SyncConfig::from(&cli), &cancel);
+        match &result {
+            Ok(report) => info!("webhook sync success: {repo}, {report}"),
+            Err(e) => error!("webhook sync failed: {repo}, {e:#}"),
+        }
+        board.finish(&repo, started, &result);
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    cli: &Cli,
+    args: &ServeArgs,
+    sender: &Sender<Cli>,
+    board: &StatusBoard,
+) -> AnyResult<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Some(request) = httpd::read_request(&mut reader, MAX_PAYLOAD)? else {
+        return Ok(());
+    };
+    let mut writer = stream;
+    if let Some((status, content_type, body)) = board.route(&request) {
+        return httpd::write_response(&mut writer, status, content_type, &body);
+    }
+    let (status, message) = match handle_webhook(&request, cli, &args.webhook_secret) {
+        Webhook::Sync(repo_cli) => {
+            let repo = format!("{}/{}", repo_cli.github_owner, repo_cli.github_repo);
+            if board.enqueue(&repo) {
+                info!("webhook sync queued: {repo}");
+                sender.send(*repo_cli)?;
+                (202, format!("sync queued: {repo}"))
+            } else {
+                (202, format!("sync already queued: {repo}"))
+            }
+        }
+        Webhook::Ignored(message) => {
+            debug!("webhook ignored: {message}");
+            (200, message)
+        }
+        Webhook::Rejected(status, message) => {
+            warn!(
+                "webhook rejected: {} {}, {message}",
+                request.method, request.path
+            );
+            (status, message)
+        }
+    };
+    httpd::write_response(&mut writer, status, "text/plain", message.as_bytes())
+}
+
+/// webhook请求的处理结果
+#[derive(Debug)]
+enum Webhook {
+    // 同步payload中的仓库
+    Sync(Box<Cli>),
+    // 无需同步的事件(如ping、其他action)
+    Ignored(String),
+    // 签名错误等
+    Rejected(u16, String),
+}
+
+fn handle_webhook(request: &Request, cli: &Cli, secret: &str) -> Webhook {
+    if request.method != "POST" {
+        return Webhook::Rejected(405, "method not allowed".to_string());
+    }
+    if !verify_signature(secret, &request.body, request.header("x-hub-signature-256")) {
+        return Webhook::Rejected(401, "invalid signature".to_string());
+    }
+    let event = request.header("x-github-event").unwrap_or_default();
+    if event == "ping" {
+        return Webhook::Ignored("pong".to_string());
+    }
+    if event != "release" {
+        return Webhook::Ignored(format!("event ignored: {event}"));
+    }
+    let Ok(payload) = serde_json::from_slice::<Value>(&request.body) else {
+        return Webhook::Rejected(400, "invalid payload".to_string());
+    };
+    let action = payload["action"].as_str().unwrap_or_default();
+    if !SYNC_ACTIONS.contains(&action) {
+        return Webhook::Ignored(format!("release action ignored: {action}"));
+    }
+    let (Some(owner), Some(repo)) = (
+        payload["repository"]["owner"]["login"].as_str(),
+        payload["repository"]["name"].as_str(),
+    ) else {
+        return Webhook::Rejected(400, "repository missing in payload".to_string());
+    };
+    match repo_cli(cli, owner, repo) {
+        Some(cli) => Webhook::Sync(Box::new(cli)),
+        None => Webhook::Ignored(format!("repository not configured: {owner}/{repo}")),
+    }
+}
+
+/// payload中仓库的同步参数
+/// - 设置了--github-owner/--github-repo时只同步该仓库
+/// - 否则同步任意仓库: gitee仓库名默认与github相同(--gitee-owner为组织或个人空间)
+fn repo_cli(cli: &Cli, owner: &str, repo: &str) -> Option<Cli> {
+    let mut cli = Cli {
+        command: None,
+        ..cli.clone()
+    };
+    if !cli.github_repo.is_empty() {
+        let configured = cli.github_owner.eq_ignore_ascii_case(owner)
+            && cli.github_repo.eq_ignore_ascii_case(repo);
+        return configured.then_some(cli);
+    }
+    cli.github_owner = owner.to_string();
+    cli.github_repo = repo.to_string();
+    if cli.gitee_repo.is_empty() {
+        cli.gitee_repo = repo.to_string();
+    }
+    Some(cli)
+}
+
+/// X-Hub-Signature-256: sha256=hex(hmac-sha256(secret, body)), 按固定时间比较
+fn verify_signature(secret: &str, body: &[u8], signature: Option<&str>) -> bool {
+    let Some(signature) = signature.and_then(|value| value.strip_prefix("sha256=")) else {
+        return false;
+    };
+    let expected = hex(&sign(secret, body));
+    expected.len() == signature.len()
+        && expected
+            .bytes()
+            .zip(signature.to_ascii_lowercase().bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn sign(secret: &str, body: &[u8]) -> Vec<u8> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(body);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use crate::model::Command;
+    use clap::Parser;
+    use serde_json::json;
+
+    fn request(event: &str, body: &[u8], signature: &str) -> Request {
+        Request {
+            method: "POST".to_string(),
+            path: "/webhook".to_string(),
+            headers: vec![
+                ("x-github-event".to_string(), event.to_string()),
+                ("x-hub-signature-256".to_string(), signature.to_string()),
+            ],
+            body: body.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_handle_webhook() {
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--gitee-owner=mirror",
+            "--gitee-token=t",
+            "serve",
+            "--webhook-secret=s3cret",
+        ]);
+        let body = serde_json::to_vec(&json!({
+            "action": "published",
+            "repository": { "name": "redis-me", "owner": { "login": "hepengju" } },
+        }))
+        .unwrap();
+        let signature = format!("sha256={}", hex(&sign("s3cret", &body)));
+
+        match handle_webhook(&request("release", &body, &signature), &cli, "s3cret") {
+            Webhook::Sync(cli) => {
+                assert_eq!(cli.github_owner, "hepengju");
+                assert_eq!(cli.github_repo, "redis-me");
+                assert_eq!(cli.gitee_owner, "mirror");
+                assert_eq!(cli.gitee_repo, "redis-me");
+                assert!(cli.command.is_none());
+            }
+            other => panic!("sync expected: {other:?}"),
+        }
+        assert!(matches!(
+            handle_webhook(&request("release", &body, "sha256=00"), &cli, "s3cret"),
+            Webhook::Rejected(401, _)
+        ));
+        assert!(matches!(
+            handle_webhook(&request("push", &body, &signature), &cli, "s3cret"),
+            Webhook::Ignored(_)
+        ));
+
+        // 只同步配置的仓库
+        let cli = Cli {
+            github_owner: "hepengju".to_string(),
+            github_repo: "other".to_string(),
+            ..cli
+        };
+        assert!(matches!(
+            handle_webhook(&request("release", &body, &signature), &cli, "s3cret"),
+            Webhook::Ignored(_)
+        ));
+    }
+
+    #[test]
+    fn test_empty_secret() {
+        let cli = Cli::parse_from(["release2gitee", "serve", "--webhook-secret="]);
+        let Some(Command::Serve(args)) = &cli.command else {
+            panic!("serve command expected");
+        };
+        let e = run(&cli, args).unwrap_err();
+        assert_eq!(e.to_string(), "--webhook-secret must not be empty");
+    }
+}
@@ -0,0 +1,216 @@
+//! 下载次数统计(stats子命令): 汇总源仓库和目标仓库每个附件的下载次数, 用于判断镜像的实际使用情况
+//! - 附件按tag和附件名对应, 目标仓库没有下载次数(如gitee、对象存储)时显示为-
+
+use crate::AnyResult;
+use crate::http::{self, Conditional};
+use crate::model::{Cli, Forge, Release, StatsArgs, StatsFormat, SyncConfig};
+use crate::provider::{self, ReleaseSource, RestForge};
+use anyhow::bail;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// 下载次数统计结果
+#[derive(Debug, Serialize)]
+pub struct DownloadStats {
+    pub source: String,
+    pub target: String,
+    pub source_total: u64,
+    pub target_total: u64,
+    pub releases: Vec<ReleaseStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReleaseStats {
+    pub tag_name: String,
+    pub assets: Vec<AssetStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssetStats {
+    pub name: String,
+    pub source: Option<u64>,
+    pub target: Option<u64>,
+}
+
+pub fn run(cli: &Cli, args: &StatsArgs) -> AnyResult<String> {
+    let client = &http::init_client(cli)?;
+    let source = provider::source(cli, client, &SyncConfig::default());
+    let source_releases = latest_releases(source.as_ref(), cli.github_latest_release_count)?;
+    // 只有github风格的接口可查询目标仓库的附件信息
+    let endpoint = cli.target();
+    let target_releases = match endpoint.forge {
+        Forge::GitHub | Forge::Gitee | Forge::Gitea => {
+            let target = RestForge::new(cli, client, endpoint.clone());
+            latest_releases(&target, 100)?
+        }
+        _ => Vec::new(),
+    };
+    let stats = DownloadStats::collect(
+        source.endpoint().forge,
+        endpoint.forge,
+        &source_releases,
+        &target_releases,
+    );
+    Ok(match args.format {
+        StatsFormat::Table => stats.to_string(),
+        StatsFormat::Json => serde_json::to_string_pretty(&stats)?,
+    })
+}
+
+fn latest_releases(source: &dyn ReleaseSource, per_page: usize) -> AnyResult<Vec<Release>> {
+    match source.fetch_releases(per_page, 1, None)? {
+        Conditional::Modified { text, .. } => source.parse_releases(&text),
+        Conditional::NotModified => bail!("unexpected 304 without etag"),
+    }
+}
+
+impl DownloadStats {
+    /// 以源仓库的releases为准对应目标仓库的附件, 目标仓库独有的附件(如改名后的附件)同样列出
+    fn collect(
+        source: Forge,
+        target: Forge,
+        source_releases: &[Release],
+        target_releases: &[Release],
+    ) -> Self {
+        let releases = source_releases
+            .iter()
+            .map(|release| {
+                let target_release = target_releases
+                    .iter()
+                    .find(|tr| tr.tag_name == release.tag_name);
+                let target_count = |name: &str| {
+                    target_release?
+                        .assets
+                        .iter()
+                        .find(|asset| asset.name == name)?
+                        .download_count
+                };
+                let mut assets = release
+                    .assets
+                    .iter()
+                    .map(|asset| AssetStats {
+                        name: asset.name.clone(),
+                        source: asset.download_count,
+                        target: target_count(&asset.name),
+                    })
+                    .collect::<Vec<_>>();
+                for asset in target_release.iter().flat_map(|tr| &tr.assets) {
+                    if !release.assets.iter().any(|a| a.name == asset.name) {
+                        assets.push(AssetStats {
+                            name: asset.name.clone(),
+                            source: None,
+                            target: asset.download_count,
+                        });
+                    }
+                }
+                ReleaseStats {
+                    tag_name: release.tag_name.clone(),
+                    assets,
+                }
+            })
+            .collect::<Vec<ReleaseStats>>();
+        let total = |count: fn(&AssetStats) -> Option<u64>| {
+            releases
+                .iter()
+                .flat_map(|release| &release.assets)
+                .filter_map(count)
+                .sum()
+        };
+        DownloadStats {
+            source: source.to_string(),
+            target: target.to_string(),
+            source_total: total(|asset| asset.source),
+            target_total: total(|asset| asset.target),
+            releases,
+        }
+    }
+}
+
+impl Display for DownloadStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let count = |count: Option<u64>| count.map_or("-".to_string(), |count| count.to_string());
+        let mut rows = vec![[
+            "tag".to_string(),
+            "asset".to_string(),
+            self.source.clone(),
+            self.target.clone(),
+        ]];
+        for release in &self.releases {
+            for asset in &release.assets {
+                rows.push([
+                    release.tag_name.clone(),
+                    asset.name.clone(),
+                    count(asset.source),
+                    count(asset.target),
+                ]);
+            }
+        }
+        // 各列按最长的内容对齐(按字符数, 附件名可能包含中文)
+        let widths = (0..4)
+            .map(|i| rows.iter().map(|row| row[i].chars().count()).max())
+            .map(Option::unwrap_or_default)
+            .collect::<Vec<_>>();
+        for row in &rows {
+            writeln!(
+                f,
+                "{:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+            )?;
+        }
+        let total = self.source_total + self.target_total;
+        let share = match total {
+            0 => 0.0,
+            total => self.target_total as f64 * 100.0 / total as f64,
+        };
+        write!(
+            f,
+            "total: {} {}, {} {} ({share:.1}%)",
+            self.source, self.source_total, self.target, self.target_total
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_download_stats() -> AnyResult<()> {
+        let source: Vec<Release> = serde_json::from_str(
+            r#"[{"id":2,"tag_name":"v1.1.0","name":"v1.1.0","body":null,"prerelease":false,"target_commitish":"main",
+                 "assets":[{"name":"app.dmg","size":1,"browser_download_url":"https://github.com/o/r/releases/download/v1.1.0/app.dmg","download_count":75},
+                           {"name":"app.msi","size":1,"browser_download_url":"https://github.com/o/r/releases/download/v1.1.0/app.msi","download_count":5}]},
+                {"id":1,"tag_name":"v1.0.0","name":"v1.0.0","body":null,"prerelease":false,"target_commitish":"main","assets":[]}]"#,
+        )?;
+        let target: Vec<Release> = serde_json::from_str(
+            r#"[{"id":9,"tag_name":"v1.1.0","name":"v1.1.0","body":null,"prerelease":false,"target_commitish":"main",
+                 "assets":[{"name":"app.dmg","size":1,"browser_download_url":"https://gitee.com/o/r/releases/download/v1.1.0/app.dmg","download_count":20},
+                           {"name":"app.msi","size":1,"browser_download_url":"https://gitee.com/o/r/releases/download/v1.1.0/app.msi"},
+                           {"name":"extra.zip","size":1,"browser_download_url":"https://gitee.com/o/r/releases/download/v1.1.0/extra.zip","download_count":0}]}]"#,
+        )?;
+        let stats = DownloadStats::collect(Forge::GitHub, Forge::Gitee, &source, &target);
+        assert_eq!((stats.source_total, stats.target_total), (80, 20));
+        assert_eq!(
+            stats.to_string(),
+            "tag     asset      github  gitee\n\
+             v1.1.0  app.dmg        75     20\n\
+             v1.1.0  app.msi         5      -\n\
+             v1.1.0  extra.zip       -      0\n\
+             total: github 80, gitee 20 (20.0%)"
+        );
+        let json = serde_json::to_value(&stats)?;
+        assert_eq!(
+            json["releases"][0]["assets"][1]["target"],
+            serde_json::Value::Null
+        );
+        assert_eq!(json["releases"][1]["assets"], serde_json::json!([]));
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::httpd::{self, Request};
+use crate::report::SyncReport;
+use crate::{AnyResult, provenance, redact};
+use log::{debug, info};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 常驻运行(定时同步、webhook服务)的状态: /healthz用于存活探针, /status查看各仓库最近一次同步的结果
+#[derive(Debug)]
+pub struct StatusBoard {
+    state: Mutex<ServiceStatus>,
+}
+
+impl Default for StatusBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// /status的响应
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ServiceStatus {
+    // 启动时间(unix秒)
+    pub started_at: u64,
+    // 正在同步的仓库
+    pub running: Option<String>,
+    // 排队等待同步的仓库
+    pub queued: BTreeSet<String>,
+    // 仓库 -> 最近一次同步的结果
+    pub repos: BTreeMap<String, RepoStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoStatus {
+    // 最近一次同步的开始时间(unix秒)
+    pub last_run: u64,
+    pub duration_secs: f64,
+    // success, nothing_to_sync, failed
+    pub result: &'static str,
+    pub synced: usize,
+    pub failed: usize,
+    pub error: Option<String>,
+    // 连续失败的次数
+    pub consecutive_failures: u32,
+}
+
+impl StatusBoard {
+    pub fn new() -> Self {
+        StatusBoard {
+            state: Mutex::new(ServiceStatus {
+                started_at: provenance::now_secs(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, ServiceStatus> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 加入同步队列, 已在队列中时返回false
+    pub fn enqueue(&self, repo: &str) -> bool {
+        self.state().queued.insert(repo.to_string())
+    }
+
+    /// 开始同步: 移出队列(同步期间收到的事件会再次排队)
+    pub fn start(&self, repo: &str) -> Instant {
+        let mut state = self.state();
+        state.queued.remove(repo);
+        state.running = Some(repo.to_string());
+        Instant::now()
+    }
+
+    pub fn finish(&self, repo: &str, started: Instant, result: &AnyResult<SyncReport>) {
+        let duration = started.elapsed();
+        let mut state = self.state();
+        state.running = None;
+        let consecutive_failures = match (result, state.repos.get(repo)) {
+            (Ok(_), _) => 0,
+            (Err(_), Some(last)) => last.consecutive_failures + 1,
+            (Err(_), None) => 1,
+        };
+        let (result, synced, failed, error) = match result {
+            Ok(report) if report.is_nothing_to_sync() => ("nothing_to_sync", 0, 0, None),
+            Ok(report) => ("success", report.synced.len(), report.failed.len(), None),
+            Err(e) => {
+                let error = redact::redact(&format!("{e:#}")).into_owned();
+                ("failed", 0, 0, Some(error))
+            }
+        };
+        let status = RepoStatus {
+            last_run: provenance::now_secs().saturating_sub(duration.as_secs()),
+            duration_secs: duration.as_secs_f64(),
+            result,
+            synced,
+            failed,
+            error,
+            consecutive_failures,
+        };
+        state.repos.insert(repo.to_string(), status);
+    }
+
+    pub fn snapshot(&self) -> ServiceStatus {
+        self.state().clone()
+    }
+
+    /// /healthz和/status的响应, 其他请求返回None
+    pub fn route(&self, request: &Request) -> Option<(u16, &'static str, Vec<u8>)> {
+        if request.method != "GET" {
+            return None;
+        }
+        match request.path.split('?').next().unwrap_or_default() {
+            "/healthz" => Some((200, "text/plain", b"ok".to_vec())),
+            "/status" => {
+                let body = serde_json::to_vec_pretty(&self.snapshot()).unwrap_or_default();
+                Some((200, "application/json", body))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 状态服务(定时同步时的--status-listen): 后台线程处理/healthz和/status
+pub fn spawn_server(listen: &str, board: Arc<StatusBoard>) -> AnyResult<()> {
+    let listener = TcpListener::bind(listen)?;
+    info!("status server listening: {}", listener.local_addr()?);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            if let Err(e) = handle_connection(stream, &board) {
+                debug!("status connection error: {e:#}");
+            }
+        }
+    });
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, board: &StatusBoard) -> AnyResult<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Some(request) = httpd::read_request(&mut reader, 0)? else {
+        return Ok(());
+    };
+    let (status, content_type, body) =
+        board
+            .route(&request)
+            .unwrap_or((404, "text/plain", b"not found".to_vec()));
+    let mut writer = stream;
+    httpd::write_response(&mut writer, status, content_type, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use serde_json::Value;
+
+    fn get(path: &str) -> Request {
+        Request {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_status_board() -> AnyResult<()> {
+        let board = StatusBoard::new();
+        assert!(board.enqueue("hepengju/redis-me"));
+        assert!(!board.enqueue("hepengju/redis-me"));
+        let started = board.start("hepengju/redis-me");
+        board.finish("hepengju/redis-me", started, &Err(anyhow!("timeout")));
+        let started = board.start("hepengju/redis-me");
+        board.finish("hepengju/redis-me", started, &Err(anyhow!("timeout")));
+
+        assert_eq!(board.route(&get("/healthz")).map(|r| r.0), Some(200));
+        assert!(board.route(&get("/webhook")).is_none());
+        let (_, _, body) = board.route(&get("/status")).unwrap();
+        let status: Value = serde_json::from_slice(&body)?;
+        let repo = &status["repos"]["hepengju/redis-me"];
+        assert_eq!(repo["result"], "failed");
+        assert_eq!(repo["error"], "timeout");
+        assert_eq!(repo["consecutive_failures"], 2);
+        assert_eq!(status["queued"], Value::Array(Vec::new()));
+        assert!(status["running"].is_null());
+        Ok(())
+    }
+}
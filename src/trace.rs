@@ -0,0 +1,176 @@
+use crate::{AnyResult, otel, redact};
+use log::{info, warn};
+use reqwest::Url;
+use reqwest::blocking::{Request, RequestBuilder, Response};
+use reqwest::header::HeaderMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+/// 值整体隐藏的请求头、响应头
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "private-token",
+    "x-amz-security-token",
+    "x-oss-security-token",
+    "x-cos-security-token",
+];
+
+/// --trace-http: 进程内所有http请求共享
+static TRACER: OnceLock<Tracer> = OnceLock::new();
+
+/// 开启http跟踪, 只在第一次设置时生效
+pub fn open(path: &Path) -> AnyResult<()> {
+    if TRACER.get().is_none() {
+        let _ = TRACER.set(Tracer::create(path)?);
+        info!("http trace: {}", path.display());
+    }
+    Ok(())
+}
+
+/// 发送请求: 开启--trace-http时记录请求和响应(状态、响应头), 开启--otlp-endpoint时记录span
+pub trait SendTraced {
+    fn send_traced(self) -> reqwest::Result<Response>;
+}
+
+impl SendTraced for RequestBuilder {
+    fn send_traced(self) -> reqwest::Result<Response> {
+        let tracer = TRACER.get();
+        if tracer.is_none() && !otel::is_enabled() {
+            return self.send();
+        }
+        let (client, request) = self.build_split();
+        let request = request?;
+        // 链路追踪: 每个http请求一个span
+        let mut span = otel::span(format!(
+            "{} {}",
+            request.method(),
+            request.url().host_str().unwrap_or_default()
+        ));
+        span.set("http.request.method", request.method());
+        span.set("url.full", redact::redact(request.url().as_str()));
+        if let Some(tracer) = tracer {
+            tracer.request(&request);
+        }
+        let result = client.execute(request);
+        match &result {
+            Ok(res) => {
+                span.set_int("http.response.status_code", res.status().as_u16().into());
+                if !res.status().is_success() {
+                    span.error(res.status());
+                }
+                if let Some(tracer) = tracer {
+                    tracer.response(res);
+                }
+            }
+            Err(e) => {
+                span.error(e);
+                if let Some(tracer) = tracer {
+                    tracer.write(format!("<== error: {e}\n"));
+                }
+            }
+        }
+        result
+    }
+}
+
+/// 记录读取的响应体(接口返回的文本, 附件下载的内容不记录)
+pub fn response_body(url: &Url, text: &str) {
+    if let Some(tracer) = TRACER.get() {
+        tracer.write(format!("<== body: {url}\n{text}\n\n"));
+    }
+}
+
+/// http跟踪文件: 与普通日志分开, 请求头中的认证信息和所有内容中的token都已隐藏
+struct Tracer {
+    file: Mutex<File>,
+}
+
+impl Tracer {
+    fn create(path: &Path) -> AnyResult<Self> {
+        Ok(Tracer {
+            file: Mutex::new(File::create(path)?),
+        })
+    }
+
+    fn request(&self, request: &Request) {
+        let mut text = format!("==> {} {}\n", request.method(), request.url());
+        write_headers(&mut text, request.headers());
+        match request.body().map(|body| body.as_bytes()) {
+            None => {}
+            Some(Some(bytes)) => match std::str::from_utf8(bytes) {
+                Ok(body) => {
+                    let _ = writeln!(text, "\n{body}");
+                }
+                Err(_) => {
+                    let _ = writeln!(text, "\n<{} bytes binary>", bytes.len());
+                }
+            },
+            Some(None) => text.push_str("\n<streamed body>\n"),
+        }
+        text.push('\n');
+        self.write(text);
+    }
+
+    fn response(&self, res: &Response) {
+        let mut text = format!("<== {} {}\n", res.status(), res.url());
+        write_headers(&mut text, res.headers());
+        text.push('\n');
+        self.write(text);
+    }
+
+    /// 每条记录整体写入, 并行请求的记录不会交错
+    fn write(&self, text: String) {
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = file.write_all(redact::redact(&text).as_bytes()) {
+            warn!("http trace write error: {e}");
+        }
+    }
+}
+
+fn write_headers(text: &mut String, headers: &HeaderMap) {
+    for (name, value) in headers {
+        let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+            "***"
+        } else {
+            value.to_str().unwrap_or("<binary>")
+        };
+        let _ = writeln!(text, "{name}: {value}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::blocking::Client;
+    use serde_json::json;
+    use std::{env, fs};
+
+    #[test]
+    fn test_tracer() -> AnyResult<()> {
+        let path = env::temp_dir().join(format!("http-trace-test-{}", std::process::id()));
+        let tracer = Tracer::create(&path)?;
+        let request = Client::new()
+            .post("https://gitee.com/api/v5/repos/o/r/releases?access_token=abc123")
+            .header("Authorization", "token secret-token-value")
+            .json(&json!({ "tag_name": "v1.0.0" }))
+            .build()?;
+        tracer.request(&request);
+        response_body(request.url(), "ignored without --trace-http");
+
+        let text = fs::read_to_string(&path)?;
+        assert!(text.starts_with("==> POST https://gitee.com/api/v5/repos/o/r/releases"));
+        assert!(text.contains("authorization: ***"));
+        assert!(text.contains(r#"{"tag_name":"v1.0.0"}"#));
+        assert!(!text.contains("secret-token-value"));
+        assert!(!text.contains("abc123"));
+        assert!(!text.contains("ignored"));
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,874 @@
+use crate::AnyResult;
+use crate::cancel::Cancellation;
+use crate::fsutil::{self, FileWriter};
+use crate::http::{self, USER_AGENT};
+use crate::otel;
+use crate::progress::{Progress, ProgressSink};
+use crate::s3::hex;
+use crate::trace::SendTraced;
+use crate::units::ByteRate;
+use anyhow::{anyhow, bail};
+use log::{debug, info, warn};
+use multipart::Part;
+use reqwest::StatusCode;
+use reqwest::blocking::{Body, Client, RequestBuilder, multipart};
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_RANGE, RANGE};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 传输完成的结果: 边传输边计算, 无需再次读取文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transferred {
+    pub size: u64,
+    // 小写十六进制
+    pub sha256: String,
+}
+
+/// 下载附件: 失败时(网络错误、5xx)重试
+/// - 重试时从已下载的位置继续(Range请求), 服务端不支持时重新下载
+/// - 下载的字节数与Content-Length不一致时按连接中断处理
+/// - timeout: 每次请求(包括读取响应体)的超时时间, 默认使用client的超时时间
+/// - authorization: 通过接口下载时(如github草稿release的附件)的Authorization请求头
+#[allow(clippy::too_many_arguments)]
+pub fn download(
+    client: &Client,
+    url: &str,
+    authorization: Option<&str>,
+    file_path: &Path,
+    durable: bool,
+    retry_times: u32,
+    timeout: Option<Duration>,
+    sink: Option<&Arc<dyn ProgressSink>>,
+    cancellation: &Cancellation,
+) -> AnyResult<Transferred> {
+    info!("downloading: {}", url);
+
+    let name = file_path.file_name().unwrap().display().to_string();
+    let mut state = TransferState::new(TransferKind::Download, name, retry_times, sink);
+    // 创建文件: 安全模式下先写临时文件, 完成后再重命名
+    let mut file = FileWriter::create(file_path, durable)?;
+    let meter = Meter::default();
+    loop {
+        match download_once(
+            client,
+            url,
+            authorization,
+            timeout,
+            &mut file,
+            &meter,
+            &mut state,
+            cancellation,
+        ) {
+            Ok(()) => {
+                state.finish();
+                file.commit()?;
+                return Ok(meter.finish());
+            }
+            Err(e) => state.retry(e, cancellation)?,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn download_once(
+    client: &Client,
+    url: &str,
+    authorization: Option<&str>,
+    timeout: Option<Duration>,
+    file: &mut FileWriter,
+    meter: &Meter,
+    state: &mut TransferState,
+    cancellation: &Cancellation,
+) -> AnyResult<()> {
+    let offset = meter.size();
+    let mut builder = get(client, url, authorization);
+    if offset > 0 {
+        builder = builder.header(RANGE, format!("bytes={offset}-"));
+    }
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    let res = builder.send_traced()?;
+    if !res.status().is_success() {
+        return Err(http::api_error(res).into());
+    }
+    if offset > 0 && res.status() != StatusCode::PARTIAL_CONTENT {
+        debug!("range not supported, restart download: {url}");
+        file.restart()?;
+        meter.reset();
+    }
+
+    // 获取内容长度用于进度条和完整性检查
+    let offset = meter.size();
+    let content_length = res.content_length();
+    let total_size = offset + content_length.unwrap_or(0);
+    let pb = state.start(total_size, offset);
+    if offset == 0 {
+        file.preallocate(total_size)?;
+    }
+
+    let mut reader = TransferRead::new(res, pb, cancellation, meter);
+    io::copy(&mut reader, file)?;
+    if content_length.is_some() && meter.size() != total_size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("incomplete download: {}/{total_size}", meter.size()),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// 分段下载时每段的最小大小: 较小的附件单连接下载
+const SEGMENT_MIN_SIZE: u64 = 16 * 1024 * 1024;
+
+/// 多连接分段下载(--download-segments): 大附件按字节范围并行下载后拼接, 跨洋高延迟链路下明显更快
+/// - 先请求第一个字节探测附件大小, 服务端不支持Range或附件较小时单连接下载
+/// - 分段下载失败时(各分段重试后)回退为单连接下载
+#[allow(clippy::too_many_arguments)]
+pub fn download_segmented(
+    client: &Client,
+    url: &str,
+    authorization: Option<&str>,
+    file_path: &Path,
+    durable: bool,
+    segments: usize,
+    retry_times: u32,
+    sink: Option<&Arc<dyn ProgressSink>>,
+    cancellation: &Cancellation,
+) -> AnyResult<Transferred> {
+    if segments > 1 {
+        match probe_size(client, url, authorization) {
+            Ok(Some(size)) => {
+                let segments = segments.min((size / SEGMENT_MIN_SIZE) as usize);
+                if segments > 1 {
+                    match download_ranges(
+                        client,
+                        url,
+                        authorization,
+                        file_path,
+                        size,
+                        segments,
+                        retry_times,
+                        sink,
+                        cancellation,
+                    ) {
+                        Ok(transferred) => return Ok(transferred),
+                        Err(e) if cancellation.check().is_err() => return Err(e),
+                        Err(e) => warn!("segmented download error, fallback: {url}, {e:#}"),
+                    }
+                }
+            }
+            Ok(None) => debug!("range not supported, single stream download: {url}"),
+            Err(e) => debug!("range probe error, single stream download: {url}, {e:#}"),
+        }
+    }
+    download(
+        client,
+        url,
+        authorization,
+        file_path,
+        durable,
+        retry_times,
+        None,
+        sink,
+        cancellation,
+    )
+}
+
+/// 下载请求: 携带Authorization时通过接口下载二进制内容(跨域重定向时不转发Authorization)
+fn get(client: &Client, url: &str, authorization: Option<&str>) -> RequestBuilder {
+    let builder = client.get(url).header("User-Agent", USER_AGENT);
+    match authorization {
+        Some(authorization) => builder
+            .header(AUTHORIZATION, authorization)
+            .header(ACCEPT, "application/octet-stream"),
+        None => builder,
+    }
+}
+
+/// 请求第一个字节: 返回206时从Content-Range(bytes 0-0/size)中取得附件大小
+fn probe_size(client: &Client, url: &str, authorization: Option<&str>) -> AnyResult<Option<u64>> {
+    let res = get(client, url, authorization)
+        .header(RANGE, "bytes=0-0")
+        .send_traced()?;
+    if !res.status().is_success() {
+        return Err(http::api_error(res).into());
+    }
+    if res.status() != StatusCode::PARTIAL_CONTENT {
+        return Ok(None);
+    }
+    Ok(res
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit_once('/'))
+        .and_then(|(_, size)| size.parse().ok()))
+}
+
+/// 按字节范围并行下载各分段, 写入同一文件的不同位置
+/// - 总是先写临时文件, 全部分段完成后再重命名, 中断时不会留下有空洞的文件
+#[allow(clippy::too_many_arguments)]
+fn download_ranges(
+    client: &Client,
+    url: &str,
+    authorization: Option<&str>,
+    file_path: &Path,
+    size: u64,
+    segments: usize,
+    retry_times: u32,
+    sink: Option<&Arc<dyn ProgressSink>>,
+    cancellation: &Cancellation,
+) -> AnyResult<Transferred> {
+    info!("downloading in {segments} segments: {url}");
+    let file = FileWriter::create(file_path, true)?;
+    file.preallocate(size)?;
+    let name = file_path.file_name().unwrap().display().to_string();
+    let pb = Progress::new(&format!("download {name}"), size, sink);
+    let segment_size = size.div_ceil(segments as u64);
+    let result = thread::scope(|scope| {
+        let handles = (0..segments as u64)
+            .map(|i| {
+                let range = i * segment_size..((i + 1) * segment_size).min(size);
+                let name = format!("{name}#{i}");
+                let (file, pb) = (&file, &pb);
+                let context = otel::current();
+                scope.spawn(move || {
+                    let _attached = otel::attach(context);
+                    let mut state =
+                        TransferState::new(TransferKind::Download, name, retry_times, sink);
+                    download_segment(
+                        client,
+                        url,
+                        authorization,
+                        file,
+                        range,
+                        pb,
+                        &mut state,
+                        cancellation,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow!("segment download panicked")))
+            })
+            .collect::<AnyResult<Vec<()>>>()
+    });
+    if let Err(e) = result {
+        pb.abandon();
+        return Err(e);
+    }
+    pb.finish();
+    file.commit()?;
+    Ok(Transferred {
+        size,
+        sha256: fsutil::sha256_file(file_path)?,
+    })
+}
+
+/// 下载一个分段: 失败时(网络错误、5xx)重试, 从该分段已下载的位置继续
+#[allow(clippy::too_many_arguments)]
+fn download_segment(
+    client: &Client,
+    url: &str,
+    authorization: Option<&str>,
+    file: &FileWriter,
+    range: Range<u64>,
+    pb: &Progress,
+    state: &mut TransferState,
+    cancellation: &Cancellation,
+) -> AnyResult<()> {
+    let meter = Meter::default();
+    loop {
+        match download_range(
+            client,
+            url,
+            authorization,
+            file,
+            &range,
+            pb,
+            &meter,
+            cancellation,
+        ) {
+            Ok(()) => return Ok(()),
+            Err(e) => state.retry(e, cancellation)?,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn download_range(
+    client: &Client,
+    url: &str,
+    authorization: Option<&str>,
+    file: &FileWriter,
+    range: &Range<u64>,
+    pb: &Progress,
+    meter: &Meter,
+    cancellation: &Cancellation,
+) -> AnyResult<()> {
+    let start = range.start + meter.size();
+    let res = get(client, url, authorization)
+        .header(RANGE, format!("bytes={start}-{}", range.end - 1))
+        .send_traced()?;
+    if !res.status().is_success() {
+        return Err(http::api_error(res).into());
+    }
+    if res.status() != StatusCode::PARTIAL_CONTENT {
+        bail!("range not honored: {}", res.status());
+    }
+
+    let mut writer = file.open_at(start)?;
+    let mut reader = TransferRead::new(res, pb.clone(), cancellation, meter);
+    io::copy(&mut reader, &mut writer)?;
+    let expected = range.end - range.start;
+    if meter.size() != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("incomplete segment: {}/{expected}", meter.size()),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// 上传附件: 失败时(网络错误、5xx)重试, 每次重试重新打开文件构造请求体
+/// - 各平台的上传接口不支持断点续传, 重试时从头上传
+/// - 读取的字节数与文件大小不一致时(上传过程中文件被修改)失败
+#[allow(clippy::too_many_arguments)]
+pub fn upload(
+    client: &Client,
+    url: &str,
+    authorization: &str,
+    file_path: &Path,
+    body: UploadBody,
+    retry_times: u32,
+    sink: Option<&Arc<dyn ProgressSink>>,
+    cancellation: &Cancellation,
+) -> AnyResult<Transferred> {
+    let name = file_path.file_name().unwrap().display().to_string();
+    info!("uploading: {}, file: {}", url, name);
+
+    let mut state = TransferState::new(TransferKind::Upload, name, retry_times, sink);
+    loop {
+        match upload_once(
+            client,
+            url,
+            authorization,
+            file_path,
+            body,
+            &mut state,
+            cancellation,
+        ) {
+            Ok(transferred) => {
+                state.finish();
+                return Ok(transferred);
+            }
+            Err(e) => state.retry(e, cancellation)?,
+        }
+    }
+}
+
+/// 附件上传的请求体格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadBody {
+    /// multipart表单, 参数为文件的字段名(gitee: attach_files的file, gitea: assets的attachment)
+    Multipart(&'static str),
+    /// 文件内容作为请求体(github: uploads.github.com)
+    Binary,
+    /// 文件内容作为请求体, PUT方式(gitlab: generic package registry)
+    Package,
+}
+
+fn upload_once(
+    client: &Client,
+    url: &str,
+    authorization: &str,
+    file_path: &Path,
+    body: UploadBody,
+    state: &mut TransferState,
+    cancellation: &Cancellation,
+) -> AnyResult<Transferred> {
+    // 请求体读取后无法复用, 每次都重新打开文件, 进度条也重新开始
+    let file = File::open(file_path)?;
+    let file_size = file.metadata()?.len();
+    let pb = state.start(file_size, 0);
+    let meter = Meter::default();
+    let reader = TransferRead::new(file, pb, cancellation, &meter);
+
+    let builder = match body {
+        UploadBody::Multipart(_) | UploadBody::Binary => client.post(url),
+        UploadBody::Package => client.put(url),
+    };
+    let builder = builder
+        .header("Authorization", authorization)
+        .header("User-Agent", USER_AGENT);
+    let builder = match body {
+        UploadBody::Multipart(field) => {
+            let full_name = file_path.display().to_string();
+            let part = Part::reader(reader).file_name(full_name);
+            builder.multipart(multipart::Form::new().part(field, part))
+        }
+        UploadBody::Binary | UploadBody::Package => builder
+            .header("Content-Type", "application/octet-stream")
+            .body(Body::sized(reader, file_size)),
+    };
+    let res = builder.send_traced()?;
+    if !res.status().is_success() {
+        return Err(http::api_error(res).into());
+    }
+    let transferred = meter.finish();
+    if transferred.size != file_size {
+        anyhow::bail!(
+            "file changed during upload: {}, {}/{file_size}",
+            file_path.display(),
+            transferred.size
+        );
+    }
+    debug!(
+        "upload sha256: {}, {}",
+        file_path.display(),
+        transferred.sha256
+    );
+    Ok(transferred)
+}
+
+/// 传输的字节数和sha256: 下载重试时从断点继续累计
+#[derive(Debug, Clone, Default)]
+struct Meter(Arc<Mutex<(u64, Sha256)>>);
+
+impl Meter {
+    fn update(&self, buf: &[u8]) {
+        let mut meter = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        meter.0 += buf.len() as u64;
+        meter.1.update(buf);
+    }
+
+    fn size(&self) -> u64 {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).0
+    }
+
+    fn reset(&self) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Default::default();
+    }
+
+    fn finish(&self) -> Transferred {
+        let meter = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        Transferred {
+            size: meter.0,
+            sha256: hex(&meter.1.clone().finalize()),
+        }
+    }
+}
+
+/// 传输的数据流(下载、上传共用): 更新进度条、检查取消、计算大小和摘要
+struct TransferRead<R> {
+    inner: R,
+    progress: Progress,
+    cancellation: Cancellation,
+    meter: Meter,
+}
+
+impl<R> TransferRead<R> {
+    fn new(inner: R, progress: Progress, cancellation: &Cancellation, meter: &Meter) -> Self {
+        TransferRead {
+            inner,
+            progress,
+            cancellation: cancellation.clone(),
+            meter: meter.clone(),
+        }
+    }
+}
+
+impl<R: Read> Read for TransferRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // 已取消: 读取失败中断传输
+        self.cancellation.check().map_err(io::Error::other)?;
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.meter.update(&buf[..n]);
+            self.progress.inc(n as u64);
+            if let Some(throttle) = throttle() {
+                throttle.consume(n as u64);
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// 带宽限制(--limit-rate): 进程内所有下载、上传(包括并行同步的多个目标平台)共享
+static THROTTLE: RwLock<Option<Arc<Throttle>>> = RwLock::new(None);
+
+fn throttle() -> Option<Arc<Throttle>> {
+    THROTTLE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// 设置带宽限制, None为不限制
+/// - 与当前的限制相同时保留(共享令牌桶), 不同时替换(如serve中按仓库设置的不同限制)
+pub fn limit_rate(rate: Option<ByteRate>) {
+    let mut throttle = THROTTLE.write().unwrap_or_else(PoisonError::into_inner);
+    let current = throttle.as_ref().map(|throttle| throttle.rate);
+    if current == rate.map(|rate| rate.bytes_per_sec()) {
+        return;
+    }
+    match rate {
+        Some(rate) if current.is_some() => {
+            info!("transfer rate limit changed: {rate}, in-flight transfers use the new limit")
+        }
+        Some(rate) => info!("transfer rate limited: {rate}"),
+        None => info!("transfer rate limit removed"),
+    }
+    *throttle = rate.map(|rate| Arc::new(Throttle::new(rate)));
+}
+
+/// 令牌桶: 每秒补充rate个字节, 最多积累1秒; 不足时等待
+struct Throttle {
+    rate: u64,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl Throttle {
+    fn new(rate: ByteRate) -> Self {
+        let rate = rate.bytes_per_sec();
+        Throttle {
+            rate,
+            bucket: Mutex::new((rate as f64, Instant::now())),
+        }
+    }
+
+    /// 取出n个字节的令牌, 返回需要等待的时间
+    fn take(&self, n: u64) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let (tokens, updated) = &mut *bucket;
+        let now = Instant::now();
+        let rate = self.rate as f64;
+        *tokens = (*tokens + now.duration_since(*updated).as_secs_f64() * rate).min(rate);
+        *updated = now;
+        *tokens -= n as f64;
+        if *tokens < 0.0 {
+            Duration::from_secs_f64(-*tokens / rate)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    fn consume(&self, n: u64) {
+        let wait = self.take(n);
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// 传输方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferKind {
+    Download,
+    Upload,
+}
+
+impl TransferKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            TransferKind::Download => "download",
+            TransferKind::Upload => "upload",
+        }
+    }
+}
+
+/// 附件传输状态(下载、上传共用)
+/// - 记录重试次数, 重试时进度条后缀显示 retry n/m, 等待期间显示倒计时而不是静默停顿
+pub struct TransferState {
+    kind: TransferKind,
+    name: String,
+    retry_times: u32,
+    attempt: u32,
+    progress: Option<Progress>,
+    sink: Option<Arc<dyn ProgressSink>>,
+}
+
+/// 重试事件(JSON): 非终端环境(CI日志)中也可以看到重试进度
+#[derive(Debug, Serialize)]
+pub struct TransferEvent<'a> {
+    pub kind: TransferKind,
+    pub name: &'a str,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub backoff_secs: u64,
+    pub error: String,
+}
+
+impl TransferState {
+    pub fn new(
+        kind: TransferKind,
+        name: String,
+        retry_times: u32,
+        sink: Option<&Arc<dyn ProgressSink>>,
+    ) -> Self {
+        TransferState {
+            kind,
+            name,
+            retry_times,
+            attempt: 0,
+            progress: None,
+            sink: sink.cloned(),
+        }
+    }
+
+    /// 开始一次传输: 新建进度条(断点续传时从已传输的位置开始), 重试时后缀显示第几次重试
+    fn start(&mut self, size: u64, position: u64) -> Progress {
+        let pb = Progress::new(&self.label(), size, self.sink.as_ref());
+        pb.set_position(position);
+        if self.attempt > 0 {
+            pb.set_message(self.suffix(None));
+        }
+        self.progress = Some(pb.clone());
+        pb
+    }
+
+    fn finish(&mut self) {
+        if let Some(pb) = self.progress.take() {
+            pb.finish();
+        }
+    }
+
+    fn abandon(&mut self) {
+        if let Some(pb) = self.progress.take() {
+            pb.abandon();
+        }
+    }
+
+    /// 传输失败: 可重试时等待退避时间(进度条倒计时), 否则返回错误
+    fn retry(&mut self, e: anyhow::Error, cancellation: &Cancellation) -> AnyResult<()> {
+        // 取消导致的传输失败不再重试
+        if let Err(cancelled) = cancellation.check() {
+            self.abandon();
+            return Err(cancelled.into());
+        }
+        if self.attempt >= self.retry_times || !http::is_retryable(&e) {
+            self.abandon();
+            return Err(e);
+        }
+
+        self.attempt += 1;
+        let backoff = 2u64.pow(self.attempt);
+        warn!(
+            "{} error, retry {}/{} after {backoff}s: {}, {e:#}",
+            self.kind.as_str(),
+            self.attempt,
+            self.retry_times,
+            self.name
+        );
+        debug!("{}", serde_json::to_string(&self.event(backoff, &e))?);
+
+        let pb = match self.progress.take() {
+            Some(pb) => pb,
+            None => Progress::new(&self.label(), 0, self.sink.as_ref()),
+        };
+        for remaining in (1..=backoff).rev() {
+            pb.set_message(self.suffix(Some(remaining)));
+            thread::sleep(Duration::from_secs(1));
+            if let Err(cancelled) = cancellation.check() {
+                pb.abandon();
+                return Err(cancelled.into());
+            }
+        }
+        pb.abandon();
+        Ok(())
+    }
+
+    /// 进度行的前缀: download a.zip
+    fn label(&self) -> String {
+        format!("{} {}", self.kind.as_str(), self.name)
+    }
+
+    /// 进度条后缀: retry 1/3 或 retry 1/3 in 2s
+    fn suffix(&self, remaining: Option<u64>) -> String {
+        match remaining {
+            Some(secs) => format!("retry {}/{} in {secs}s", self.attempt, self.retry_times),
+            None => format!("retry {}/{}", self.attempt, self.retry_times),
+        }
+    }
+
+    fn event(&self, backoff_secs: u64, e: &anyhow::Error) -> TransferEvent<'_> {
+        TransferEvent {
+            kind: self.kind,
+            name: &self.name,
+            attempt: self.attempt,
+            max_attempts: self.retry_times,
+            backoff_secs,
+            error: format!("{e:#}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::CancellationToken;
+    use crate::error::ApiError;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_transfer_state() -> AnyResult<()> {
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        let mut state = TransferState::new(TransferKind::Upload, "a.zip".to_string(), 3, None);
+
+        // 不可重试的错误直接返回
+        let e = anyhow::Error::from(ApiError {
+            status: 404,
+            url: "https://gitee.com".to_string(),
+            message: String::new(),
+            rate_limited: false,
+            rate_limit_reset: None,
+        });
+        assert!(state.retry(e, &cancellation).is_err());
+        assert_eq!(state.attempt, 0);
+
+        state.attempt = 1;
+        assert_eq!(state.suffix(None), "retry 1/3");
+        assert_eq!(state.suffix(Some(2)), "retry 1/3 in 2s");
+        let event = serde_json::to_value(state.event(2, &anyhow::anyhow!("timeout")))?;
+        assert_eq!(event["kind"], "upload");
+        assert_eq!(event["attempt"], 1);
+        assert_eq!(event["max_attempts"], 3);
+        assert_eq!(event["backoff_secs"], 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_throttle() {
+        let throttle = Throttle::new(ByteRate(1000));
+        // 最多积累1秒的令牌, 超出部分按速率等待
+        assert!(throttle.take(1000).is_zero());
+        let wait = throttle.take(500);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_download_resume() -> AnyResult<()> {
+        // 第一次响应在4字节后断开, 第二次按Range返回剩余部分
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let server = thread::spawn(move || {
+            let responses = [
+                "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nConnection: close\r\n\r\n0123",
+                "HTTP/1.1 206 Partial Content\r\nContent-Length: 6\r\nContent-Range: bytes 4-9/10\r\nConnection: close\r\n\r\n456789",
+            ];
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    request.push_str(&line.to_lowercase());
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+
+        let path = std::env::temp_dir().join(format!("transfer-test-{}", std::process::id()));
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        let url = format!("http://127.0.0.1:{port}/a.zip");
+        let transferred = download(
+            &Client::new(),
+            &url,
+            None,
+            &path,
+            false,
+            1,
+            None,
+            None,
+            &cancellation,
+        )?;
+        let requests = server.join().unwrap();
+        assert!(requests[1].contains("range: bytes=4-"));
+        assert_eq!(std::fs::read(&path)?, b"0123456789");
+        assert_eq!(transferred.size, 10);
+        assert_eq!(transferred.sha256, fsutil::sha256_file(&path)?);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_download_ranges() -> AnyResult<()> {
+        // 按请求的Range返回对应的字节
+        let content = b"0123456789";
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let server = thread::spawn(move || {
+            let mut ranges = Vec::new();
+            for _ in 0..3 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut range = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("range: bytes=") {
+                        range = value.trim().to_string();
+                    }
+                }
+                let (start, end) = range.split_once('-').unwrap();
+                let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+                let body = &content[start..=end];
+                let header = format!(
+                    "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {start}-{end}/10\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(header.as_bytes()).unwrap();
+                stream.write_all(body).unwrap();
+                ranges.push(range);
+            }
+            ranges
+        });
+
+        let path = std::env::temp_dir().join(format!("segment-test-{}", std::process::id()));
+        let cancellation = Cancellation::new(&CancellationToken::new(), None);
+        let url = format!("http://127.0.0.1:{port}/a.zip");
+        let transferred = download_ranges(
+            &Client::new(),
+            &url,
+            None,
+            &path,
+            10,
+            3,
+            0,
+            None,
+            &cancellation,
+        )?;
+        let mut ranges = server.join().unwrap();
+        ranges.sort();
+        assert_eq!(ranges, ["0-3", "4-7", "8-9"]);
+        assert_eq!(std::fs::read(&path)?, content);
+        assert_eq!(transferred.size, 10);
+        assert_eq!(transferred.sha256, fsutil::sha256_file(&path)?);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+//! http传输: github风格接口(github, gitee, gitea)的请求和附件的下载上传
+//! - 默认基于reqwest(ReqwestTransport), 测试或库的调用方可替换为模拟的实现, 无需真实的网络请求
+//! - 通过SyncOptionsBuilder::transport设置
+
+use crate::AnyResult;
+use crate::cancel::Cancellation;
+pub use crate::http::Conditional;
+use crate::progress::ProgressSink;
+pub use crate::transfer::{Transferred, UploadBody};
+use crate::{http, transfer};
+use reqwest::blocking::Client;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 附件下载的参数
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DownloadOptions {
+    /// 先写临时文件, fsync后重命名(网络文件系统)
+    pub durable: bool,
+    /// 按字节范围并行下载的分段数, 1为不分段
+    pub segments: usize,
+    pub retry_times: u32,
+    /// 每次请求的超时时间, None为client的超时时间
+    pub timeout: Option<Duration>,
+}
+
+/// http传输, authorization为Authorization请求头(如: token xxx)
+/// - 失败的响应返回ApiError(按状态码判断是否重试、速率限制等)
+pub trait Transport: Send + Sync {
+    fn get(&self, url: &str, authorization: Option<String>) -> AnyResult<String>;
+
+    /// 携带If-None-Match的GET请求, 304时返回NotModified
+    fn get_if_none_match(
+        &self,
+        url: &str,
+        authorization: Option<String>,
+        etag: Option<&str>,
+    ) -> AnyResult<Conditional>;
+
+    fn post(&self, url: &str, authorization: &str, json: &Value) -> AnyResult<String>;
+
+    fn patch(&self, url: &str, authorization: &str, json: &Value) -> AnyResult<String>;
+
+    fn delete(&self, url: &str, authorization: &str) -> AnyResult<()>;
+
+    /// 下载到本地文件, 返回大小和sha256
+    fn download(
+        &self,
+        url: &str,
+        authorization: Option<&str>,
+        file_path: &Path,
+        options: DownloadOptions,
+        cancellation: &Cancellation,
+    ) -> AnyResult<Transferred>;
+
+    fn upload(
+        &self,
+        url: &str,
+        authorization: &str,
+        file_path: &Path,
+        body: UploadBody,
+        retry_times: u32,
+        cancellation: &Cancellation,
+    ) -> AnyResult<Transferred>;
+}
+
+/// 基于reqwest的http传输: 进度显示、断点续传、重试、限速
+pub struct ReqwestTransport {
+    client: Client,
+    // 附件传输进度的接收方, None为不通知
+    sink: Option<Arc<dyn ProgressSink>>,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: &Client, sink: Option<Arc<dyn ProgressSink>>) -> Self {
+        ReqwestTransport {
+            client: client.clone(),
+            sink,
+        }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn get(&self, url: &str, authorization: Option<String>) -> AnyResult<String> {
+        http::get(&self.client, url, authorization)
+    }
+
+    fn get_if_none_match(
+        &self,
+        url: &str,
+        authorization: Option<String>,
+        etag: Option<&str>,
+    ) -> AnyResult<Conditional> {
+        http::get_if_none_match(&self.client, url, authorization, etag)
+    }
+
+    fn post(&self, url: &str, authorization: &str, json: &Value) -> AnyResult<String> {
+        http::post(&self.client, url, authorization, json)
+    }
+
+    fn patch(&self, url: &str, authorization: &str, json: &Value) -> AnyResult<String> {
+        http::patch(&self.client, url, authorization, json)
+    }
+
+    fn delete(&self, url: &str, authorization: &str) -> AnyResult<()> {
+        http::delete(&self.client, url, authorization)
+    }
+
+    fn download(
+        &self,
+        url: &str,
+        authorization: Option<&str>,
+        file_path: &Path,
+        options: DownloadOptions,
+        cancellation: &Cancellation,
+    ) -> AnyResult<Transferred> {
+        if options.segments > 1 {
+            return transfer::download_segmented(
+                &self.client,
+                url,
+                authorization,
+                file_path,
+                options.durable,
+                options.segments,
+                options.retry_times,
+                self.sink.as_ref(),
+                cancellation,
+            );
+        }
+        transfer::download(
+            &self.client,
+            url,
+            authorization,
+            file_path,
+            options.durable,
+            options.retry_times,
+            options.timeout,
+            self.sink.as_ref(),
+            cancellation,
+        )
+    }
+
+    fn upload(
+        &self,
+        url: &str,
+        authorization: &str,
+        file_path: &Path,
+        body: UploadBody,
+        retry_times: u32,
+        cancellation: &Cancellation,
+    ) -> AnyResult<Transferred> {
+        transfer::upload(
+            &self.client,
+            url,
+            authorization,
+            file_path,
+            body,
+            retry_times,
+            self.sink.as_ref(),
+            cancellation,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+    use crate::model::Cli;
+    use crate::provider::{ReleaseTarget, RestForge};
+    use anyhow::bail;
+    use clap::Parser;
+    use std::sync::{Arc, Mutex};
+
+    /// 模拟的gitee接口: 记录请求, 返回固定的响应
+    #[derive(Default)]
+    struct FakeTransport {
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl FakeTransport {
+        fn record(&self, request: String) {
+            self.requests.lock().unwrap().push(request);
+        }
+    }
+
+    impl Transport for FakeTransport {
+        fn get(&self, url: &str, _: Option<String>) -> AnyResult<String> {
+            bail!("unexpected get: {url}")
+        }
+
+        fn get_if_none_match(
+            &self,
+            url: &str,
+            authorization: Option<String>,
+            _: Option<&str>,
+        ) -> AnyResult<Conditional> {
+            self.record(format!("GET {url} {authorization:?}"));
+            Ok(Conditional::Modified {
+                text: r#"[{"id":1,"tag_name":"v1.0.0","name":"v1.0.0","body":null,"prerelease":false,"target_commitish":"main","assets":[]}]"#.to_string(),
+                etag: None,
+            })
+        }
+
+        fn post(&self, url: &str, _: &str, json: &Value) -> AnyResult<String> {
+            self.record(format!("POST {url} {}", json["tag_name"]));
+            let mut release = json.clone();
+            release["id"] = 2.into();
+            Ok(release.to_string())
+        }
+
+        fn patch(&self, url: &str, _: &str, _: &Value) -> AnyResult<String> {
+            bail!("unexpected patch: {url}")
+        }
+
+        fn delete(&self, url: &str, _: &str) -> AnyResult<()> {
+            bail!("unexpected delete: {url}")
+        }
+
+        fn download(
+            &self,
+            url: &str,
+            _: Option<&str>,
+            _: &Path,
+            _: DownloadOptions,
+            _: &Cancellation,
+        ) -> AnyResult<Transferred> {
+            bail!("unexpected download: {url}")
+        }
+
+        fn upload(
+            &self,
+            url: &str,
+            _: &str,
+            _: &Path,
+            _: UploadBody,
+            _: u32,
+            _: &Cancellation,
+        ) -> AnyResult<Transferred> {
+            bail!("unexpected upload: {url}")
+        }
+    }
+
+    #[test]
+    fn test_fake_transport() -> AnyResult<()> {
+        let cli = Cli::parse_from([
+            "release2gitee",
+            "--github-owner=o",
+            "--github-repo=r",
+            "--gitee-owner=o",
+            "--gitee-repo=r",
+            "--gitee-token=t",
+        ]);
+        let transport = Arc::new(FakeTransport::default());
+        let target = RestForge::with_transport(&cli, transport.clone(), cli.target());
+        let releases = target.releases()?;
+        assert_eq!(releases[0].tag_name, "v1.0.0");
+
+        let mut release = releases[0].clone();
+        release.tag_name = "v1.1.0".to_string();
+        let created = target.create_release(&release)?;
+        assert_eq!((created.id, created.tag_name.as_str()), (2, "v1.1.0"));
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("GET https://gitee.com/api/v5/repos/o/r/releases?"));
+        assert_eq!(
+            requests[1],
+            r#"POST https://gitee.com/api/v5/repos/o/r/releases "v1.1.0""#
+        );
+        Ok(())
+    }
+}
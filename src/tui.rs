@@ -0,0 +1,341 @@
+use crate::report::ReleaseAction;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+#[cfg(feature = "tui")]
+use {
+    crate::AnyResult,
+    crate::cancel::CancellationToken,
+    crate::model::Cli,
+    anyhow::anyhow,
+    log::warn,
+    ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    ratatui::layout::{Constraint, Layout, Rect},
+    ratatui::style::{Color, Style},
+    ratatui::widgets::{Block, LineGauge, List, ListItem, Paragraph},
+    ratatui::{DefaultTerminal, Frame},
+    std::thread,
+    std::time::Duration,
+};
+
+/// 日志面板保留的行数(退出后输出到标准错误)
+const LOG_LINES: usize = 1000;
+
+/// 终端界面(--tui)是否正在显示: 显示期间日志写入日志面板, 传输进度显示在传输面板
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+static DASHBOARD: Mutex<Dashboard> = Mutex::new(Dashboard {
+    releases: Vec::new(),
+    transfers: Vec::new(),
+    logs: VecDeque::new(),
+    next_id: 0,
+});
+
+/// 终端界面的数据: 各目标平台的release状态, 正在进行的附件传输, 最近的日志
+struct Dashboard {
+    releases: Vec<ReleaseRow>,
+    transfers: Vec<TransferRow>,
+    logs: VecDeque<String>,
+    next_id: u64,
+}
+
+/// release的同步状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseState {
+    Syncing,
+    Synced(ReleaseAction),
+    Failed,
+}
+
+struct ReleaseRow {
+    target: String,
+    tag_name: String,
+    state: ReleaseState,
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+struct TransferRow {
+    id: u64,
+    label: String,
+    size: u64,
+    position: u64,
+    // 断点续传时已传输的位置, 不计入速度
+    initial: u64,
+    started: Instant,
+}
+
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+fn dashboard() -> MutexGuard<'static, Dashboard> {
+    DASHBOARD.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// 更新release的同步状态(并行同步多个目标平台时按目标平台区分)
+pub fn release_state(target: &str, tag_name: &str, state: ReleaseState) {
+    if !is_active() {
+        return;
+    }
+    let mut dashboard = dashboard();
+    match dashboard
+        .releases
+        .iter_mut()
+        .find(|row| row.target == target && row.tag_name == tag_name)
+    {
+        Some(row) => row.state = state,
+        None => dashboard.releases.push(ReleaseRow {
+            target: target.to_string(),
+            tag_name: tag_name.to_string(),
+            state,
+        }),
+    }
+}
+
+/// 开始一个附件传输, 返回传输面板中的编号; 终端界面未显示时返回None
+pub(crate) fn transfer_start(label: &str, size: u64) -> Option<u64> {
+    if !is_active() {
+        return None;
+    }
+    let mut dashboard = dashboard();
+    let id = dashboard.next_id;
+    dashboard.next_id += 1;
+    dashboard.transfers.push(TransferRow {
+        id,
+        label: label.to_string(),
+        size,
+        position: 0,
+        initial: 0,
+        started: Instant::now(),
+    });
+    Some(id)
+}
+
+/// 断点续传: 从已传输的位置开始
+pub(crate) fn transfer_resume(id: u64, position: u64) {
+    if let Some(row) = dashboard().transfers.iter_mut().find(|row| row.id == id) {
+        row.position = position;
+        row.initial = position;
+    }
+}
+
+pub(crate) fn transfer_inc(id: u64, n: u64) {
+    if let Some(row) = dashboard().transfers.iter_mut().find(|row| row.id == id) {
+        row.position += n;
+    }
+}
+
+pub(crate) fn transfer_end(id: u64) {
+    dashboard().transfers.retain(|row| row.id != id);
+}
+
+/// 日志输出目标: 终端界面显示期间写入日志面板, 否则写入标准错误; 同时写入日志文件(--log-file)
+pub struct LogPane(pub Option<Box<dyn Write + Send>>);
+
+impl Write for LogPane {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if is_active() {
+            let mut dashboard = dashboard();
+            for line in String::from_utf8_lossy(buf).lines() {
+                if dashboard.logs.len() == LOG_LINES {
+                    dashboard.logs.pop_front();
+                }
+                dashboard.logs.push_back(line.to_string());
+            }
+        } else {
+            io::stderr().write_all(buf)?;
+        }
+        if let Some(file) = &mut self.0 {
+            file.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()?;
+        match &mut self.0 {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 在终端界面中运行同步: 同步在后台线程执行, 按q或Ctrl-C取消同步
+/// - 退出后恢复终端, 日志面板中的日志输出到标准错误
+#[cfg(feature = "tui")]
+pub fn run<T: Send>(
+    cli: &Cli,
+    cancel: &CancellationToken,
+    sync: impl FnOnce() -> T + Send,
+) -> AnyResult<T> {
+    let targets = cli
+        .fan_out()
+        .iter()
+        .map(|cli| cli.target().to_string())
+        .collect::<Vec<_>>();
+    let title = format!(
+        " release2gitee: {} -> {} ",
+        cli.source(),
+        targets.join(", ")
+    );
+
+    let mut terminal = ratatui::try_init()?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    let (joined, ui) = thread::scope(|scope| {
+        let handle = scope.spawn(sync);
+        let ui = ui_loop(&mut terminal, &title, cancel, || handle.is_finished());
+        (handle.join(), ui)
+    });
+    ACTIVE.store(false, Ordering::SeqCst);
+    ratatui::restore();
+
+    let logs = std::mem::take(&mut dashboard().logs);
+    let mut stderr = io::stderr().lock();
+    for line in logs {
+        let _ = writeln!(stderr, "{line}");
+    }
+    drop(stderr);
+    if let Err(e) = ui {
+        warn!("tui error: {e:#}");
+    }
+    joined.map_err(|_| anyhow!("sync thread panicked"))
+}
+
+#[cfg(not(feature = "tui"))]
+pub fn run<T>(
+    _: &crate::model::Cli,
+    _: &crate::cancel::CancellationToken,
+    _: impl FnOnce() -> T,
+) -> crate::AnyResult<T> {
+    anyhow::bail!("--tui is not supported, rebuild with --features tui")
+}
+
+/// 每200毫秒刷新一次界面, 同步结束后返回
+#[cfg(feature = "tui")]
+fn ui_loop(
+    terminal: &mut DefaultTerminal,
+    title: &str,
+    cancel: &CancellationToken,
+    is_finished: impl Fn() -> bool,
+) -> AnyResult<()> {
+    while !is_finished() {
+        terminal.draw(|frame| draw(frame, title, cancel.is_cancelled()))?;
+        if event::poll(Duration::from_millis(200))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && (key.code == KeyCode::Char('q')
+                || (key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(KeyModifiers::CONTROL)))
+        {
+            cancel.cancel();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
+fn draw(frame: &mut Frame, title: &str, cancelled: bool) {
+    let dashboard = dashboard();
+    let [top, logs] =
+        Layout::vertical([Constraint::Percentage(55), Constraint::Min(5)]).areas(frame.area());
+    let [releases, transfers] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(top);
+
+    let hint = if cancelled {
+        " cancelling... "
+    } else {
+        " q: cancel "
+    };
+    let items = dashboard
+        .releases
+        .iter()
+        .map(|row| {
+            let (state, color) = match row.state {
+                ReleaseState::Syncing => ("syncing".to_string(), Color::Yellow),
+                ReleaseState::Synced(action) => (action.to_string(), Color::Green),
+                ReleaseState::Failed => ("failed".to_string(), Color::Red),
+            };
+            let text = if targets_differ(&dashboard) {
+                format!("{} {} {state}", row.target, row.tag_name)
+            } else {
+                format!("{} {state}", row.tag_name)
+            };
+            ListItem::new(text).style(Style::new().fg(color))
+        })
+        .collect::<Vec<_>>();
+    let block = Block::bordered().title(title).title_bottom(hint);
+    frame.render_widget(List::new(items).block(block), releases);
+
+    let block = Block::bordered().title(" transfers ");
+    let inner = block.inner(transfers);
+    frame.render_widget(block, transfers);
+    for (i, row) in dashboard.transfers.iter().enumerate() {
+        if i as u16 >= inner.height {
+            break;
+        }
+        let area = Rect {
+            y: inner.y + i as u16,
+            height: 1,
+            ..inner
+        };
+        frame.render_widget(transfer_gauge(row), area);
+    }
+
+    let block = Block::bordered().title(" log ");
+    let height = block.inner(logs).height as usize;
+    let skip = dashboard.logs.len().saturating_sub(height);
+    let text = dashboard
+        .logs
+        .iter()
+        .skip(skip)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n");
+    frame.render_widget(Paragraph::new(text).block(block), logs);
+}
+
+/// 只有一个目标平台时不显示目标平台
+#[cfg(feature = "tui")]
+fn targets_differ(dashboard: &Dashboard) -> bool {
+    dashboard.releases.first().is_some_and(|first| {
+        dashboard
+            .releases
+            .iter()
+            .any(|row| row.target != first.target)
+    })
+}
+
+#[cfg(feature = "tui")]
+fn transfer_gauge(row: &TransferRow) -> LineGauge<'_> {
+    use crate::units::ByteSize;
+    let elapsed = row.started.elapsed().as_secs_f64().max(0.001);
+    let speed = ByteSize((row.position.saturating_sub(row.initial) as f64 / elapsed) as u64);
+    let ratio = match row.size {
+        0 => 0.0,
+        size => (row.position.min(size) as f64 / size as f64).clamp(0.0, 1.0),
+    };
+    LineGauge::default()
+        .ratio(ratio)
+        .label(format!(
+            "{} {}/{} {speed}/s",
+            row.label,
+            ByteSize(row.position),
+            ByteSize(row.size)
+        ))
+        .filled_style(Style::new().fg(Color::Cyan))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inactive_dashboard() {
+        // 终端界面未显示时不记录
+        release_state("gitee:o/r", "v1.0.0", ReleaseState::Syncing);
+        assert!(transfer_start("download a.zip", 100).is_none());
+        assert!(dashboard().releases.is_empty());
+    }
+}
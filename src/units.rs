@@ -0,0 +1,211 @@
+//! 命令行参数中的时长/大小/速率: 支持`45s`、`1.5GB`、`5MB/s`等写法
+//! 解析失败时由clap在错误信息中指出对应的参数
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// 时长: `500ms`, `45s`, `10m`, `2h`, `1d`, `1h30m`, 纯数字为秒
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HumanDuration(pub Duration);
+
+impl HumanDuration {
+    pub fn from_secs(secs: u64) -> Self {
+        HumanDuration(Duration::from_secs(secs))
+    }
+
+    pub fn as_duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        if text.is_empty() {
+            return Err("empty duration".to_string());
+        }
+        if let Ok(secs) = text.parse::<f64>() {
+            return secs_to_duration(secs, s);
+        }
+
+        // 多段组合: 1h30m, 1m30s
+        let mut total = 0f64;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let number_len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .ok_or_else(|| format!("missing unit in duration: {s}"))?;
+            let unit_len = rest[number_len..]
+                .find(|c: char| c.is_ascii_digit() || c == '.')
+                .unwrap_or(rest.len() - number_len);
+            let number: f64 = rest[..number_len]
+                .parse()
+                .map_err(|_| format!("invalid duration: {s}"))?;
+            let unit = &rest[number_len..number_len + unit_len];
+            let factor = match unit.trim().to_ascii_lowercase().as_str() {
+                "ms" => 0.001,
+                "s" | "sec" | "secs" => 1.0,
+                "m" | "min" | "mins" => 60.0,
+                "h" | "hour" | "hours" => 3600.0,
+                "d" | "day" | "days" => 86400.0,
+                _ => return Err(format!("unknown duration unit '{unit}' in: {s}")),
+            };
+            total += number * factor;
+            rest = &rest[number_len + unit_len..];
+        }
+        secs_to_duration(total, s)
+    }
+}
+
+fn secs_to_duration(secs: f64, s: &str) -> Result<HumanDuration, String> {
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(format!("invalid duration: {s}"));
+    }
+    Ok(HumanDuration(Duration::from_secs_f64(secs)))
+}
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let millis = self.0.as_millis();
+        if !millis.is_multiple_of(1000) {
+            return write!(f, "{millis}ms");
+        }
+        let secs = self.0.as_secs();
+        match secs {
+            0 => write!(f, "0s"),
+            s if s % 86400 == 0 => write!(f, "{}d", s / 86400),
+            s if s % 3600 == 0 => write!(f, "{}h", s / 3600),
+            s if s % 60 == 0 => write!(f, "{}m", s / 60),
+            s => write!(f, "{s}s"),
+        }
+    }
+}
+
+/// 大小(字节): `1024`, `100KB`, `1.5GB`(1000进制), `512MiB`, `10M`(1024进制)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let number_len = text
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(text.len());
+        let number: f64 = text[..number_len]
+            .parse()
+            .map_err(|_| format!("invalid size: {s}"))?;
+        let unit = text[number_len..].trim();
+        let factor: u64 = match unit.to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "kb" => 1000,
+            "mb" => 1000u64.pow(2),
+            "gb" => 1000u64.pow(3),
+            "tb" => 1000u64.pow(4),
+            "k" | "kib" => 1 << 10,
+            "m" | "mib" => 1 << 20,
+            "g" | "gib" => 1 << 30,
+            "t" | "tib" => 1 << 40,
+            _ => return Err(format!("unknown size unit '{unit}' in: {s}")),
+        };
+        let bytes = number * factor as f64;
+        if !bytes.is_finite() || bytes > u64::MAX as f64 {
+            return Err(format!("invalid size: {s}"));
+        }
+        Ok(ByteSize(bytes.round() as u64))
+    }
+}
+
+impl Display for ByteSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut size = self.0 as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 || size.fract() == 0.0 {
+            write!(f, "{}{}", size, UNITS[unit])
+        } else {
+            write!(f, "{:.2}{}", size, UNITS[unit])
+        }
+    }
+}
+
+/// 速率(字节/秒): `5MB/s`, `512KiB/s`, 也可省略`/s`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteRate(pub u64);
+
+impl ByteRate {
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.0
+    }
+}
+
+impl FromStr for ByteRate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let size = text
+            .strip_suffix("/s")
+            .or_else(|| text.strip_suffix("ps"))
+            .unwrap_or(text);
+        let size: ByteSize = size.parse().map_err(|e| format!("invalid rate: {e}"))?;
+        if size.0 == 0 {
+            return Err(format!("rate must be greater than 0: {s}"));
+        }
+        Ok(ByteRate(size.0))
+    }
+}
+
+impl Display for ByteRate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/s", ByteSize(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_duration() {
+        let secs = |s: &str| s.parse::<HumanDuration>().unwrap().0.as_secs_f64();
+        assert_eq!(secs("45s"), 45.0);
+        assert_eq!(secs("60"), 60.0);
+        assert_eq!(secs("1h30m"), 5400.0);
+        assert_eq!(secs("500ms"), 0.5);
+        assert_eq!(secs("1.5m"), 90.0);
+        assert!("10x".parse::<HumanDuration>().is_err());
+        assert!("".parse::<HumanDuration>().is_err());
+        assert_eq!(HumanDuration::from_secs(3600).to_string(), "1h");
+        assert_eq!(HumanDuration::from_secs(90).to_string(), "90s");
+    }
+
+    #[test]
+    fn test_byte_size_and_rate() {
+        let size = |s: &str| s.parse::<ByteSize>().unwrap().0;
+        assert_eq!(size("1.5GB"), 1_500_000_000);
+        assert_eq!(size("512MiB"), 512 << 20);
+        assert_eq!(size("10M"), 10 << 20);
+        assert_eq!(size("1024"), 1024);
+        assert!("1.5XB".parse::<ByteSize>().is_err());
+        assert_eq!(ByteSize(1536).to_string(), "1.50KiB");
+
+        assert_eq!("5MB/s".parse::<ByteRate>().unwrap().0, 5_000_000);
+        assert_eq!("2MiB".parse::<ByteRate>().unwrap().0, 2 << 20);
+        assert!("0/s".parse::<ByteRate>().is_err());
+    }
+}